@@ -0,0 +1,211 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::ModuleSpecifier;
+use std::fmt;
+
+/// A credential which is sent in the `Authorization` header of the requests
+/// for the remote modules of a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthTokenData {
+  Bearer(String),
+  Basic { username: String, password: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthToken {
+  host: String,
+  token: AuthTokenData,
+}
+
+impl fmt::Display for AuthToken {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.token {
+      AuthTokenData::Bearer(token) => write!(f, "Bearer {}", token),
+      AuthTokenData::Basic { username, password } => {
+        let credentials = format!("{}:{}", username, password);
+        write!(f, "Basic {}", base64::encode(credentials))
+      }
+    }
+  }
+}
+
+impl AuthToken {
+  /// Check if the token applies to the host of a specifier. A token only
+  /// matches its exact host, unless the host is given as `*.host`, which
+  /// matches the subdomains of the host. When the token has a port it only
+  /// matches that port.
+  fn matches(&self, specifier: &ModuleSpecifier) -> bool {
+    let url = specifier.as_url();
+    let host = match url.host_str() {
+      Some(host) => host.to_lowercase(),
+      None => return false,
+    };
+    let (token_host, token_port) = match self.host.rfind(':') {
+      Some(index) if !self.host.ends_with(']') => {
+        let (token_host, port) = self.host.split_at(index);
+        (token_host, Some(&port[1..]))
+      }
+      _ => (self.host.as_str(), None),
+    };
+    if let Some(token_port) = token_port {
+      match url.port_or_known_default() {
+        Some(port) if port.to_string() == token_port => {}
+        _ => return false,
+      }
+    }
+    match token_host.strip_prefix("*.") {
+      Some(domain) => host.ends_with(&format!(".{}", domain)),
+      None => host == token_host,
+    }
+  }
+}
+
+/// The credentials of the private module registries, which are parsed from a
+/// string of `;` separated entries, where each entry is either
+/// `token@host[:port]` for a bearer token or `username:password@host[:port]`
+/// for basic authentication.
+#[derive(Debug, Clone, Default)]
+pub struct AuthTokens(Vec<AuthToken>);
+
+impl AuthTokens {
+  pub fn new(maybe_tokens_str: Option<String>) -> Self {
+    let mut tokens = Vec::new();
+    if let Some(tokens_str) = maybe_tokens_str {
+      for token_str in tokens_str.split(';') {
+        let token_str = token_str.trim();
+        if token_str.is_empty() {
+          continue;
+        }
+        if let Some(index) = token_str.rfind('@') {
+          let (credentials, host) = token_str.split_at(index);
+          let host = host[1..].to_lowercase();
+          let token = match credentials.find(':') {
+            Some(index) => {
+              let (username, password) = credentials.split_at(index);
+              AuthTokenData::Basic {
+                username: username.to_string(),
+                password: password[1..].to_string(),
+              }
+            }
+            None => AuthTokenData::Bearer(credentials.to_string()),
+          };
+          tokens.push(AuthToken { host, token });
+        } else {
+          // the token itself is not printed, as it is a secret
+          error!("Badly formed auth token discarded.");
+        }
+      }
+      debug!("Parsed {} auth token(s).", tokens.len());
+    }
+
+    Self(tokens)
+  }
+
+  /// Return the token to use for a remote specifier, if any. When several
+  /// tokens match, the one of the most specific host is used.
+  pub fn get(&self, specifier: &ModuleSpecifier) -> Option<AuthToken> {
+    self
+      .0
+      .iter()
+      .filter(|token| token.matches(specifier))
+      .max_by_key(|token| token.host.len())
+      .cloned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn specifier(s: &str) -> ModuleSpecifier {
+    ModuleSpecifier::resolve_url(s).unwrap()
+  }
+
+  #[test]
+  fn test_auth_token() {
+    let auth_tokens = AuthTokens::new(Some("abc123@deno.land".to_string()));
+    let fixture = specifier("https://deno.land/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = specifier("https://www.deno.land/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = specifier("http://127.0.0.1:8080/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = specifier("https://evildeno.land/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = specifier("https://deno.land.example.com/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_multiple() {
+    let auth_tokens = AuthTokens::new(Some(
+      "abc123@deno.land;def456@www.deno.land; ;bad_token".to_string(),
+    ));
+    let fixture = specifier("https://deno.land/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = specifier("https://www.deno.land/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer def456"
+    );
+  }
+
+  #[test]
+  fn test_auth_tokens_wildcard() {
+    let auth_tokens = AuthTokens::new(Some(
+      "abc123@*.deno.land;def456@cdn.deno.land".to_string(),
+    ));
+    let fixture = specifier("https://www.deno.land/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = specifier("https://cdn.deno.land/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer def456"
+    );
+    let fixture = specifier("https://deno.land/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = specifier("https://evildeno.land/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_port() {
+    let auth_tokens =
+      AuthTokens::new(Some("abc123@localhost:4545".to_string()));
+    let fixture = specifier("http://localhost:4545/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+    let fixture = specifier("http://localhost:4546/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+    let fixture = specifier("http://localhost/x/mod.ts");
+    assert_eq!(auth_tokens.get(&fixture), None);
+  }
+
+  #[test]
+  fn test_auth_tokens_basic() {
+    let auth_tokens = AuthTokens::new(Some(
+      "user:p@ss@git.example.com;abc123@example.com".to_string(),
+    ));
+    let fixture = specifier("https://git.example.com/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Basic dXNlcjpwQHNz"
+    );
+    let fixture = specifier("https://example.com/x/mod.ts");
+    assert_eq!(
+      auth_tokens.get(&fixture).unwrap().to_string(),
+      "Bearer abc123"
+    );
+  }
+}
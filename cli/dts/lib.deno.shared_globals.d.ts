@@ -585,8 +585,11 @@ declare class URLSearchParams {
 /** The URL interface represents an object providing static methods used for creating object URLs. */
 declare class URL {
   constructor(url: string, base?: string | URL);
-  createObjectURL(object: any): string;
-  revokeObjectURL(url: string): void;
+  /** Creates a `blob:` URL for the content of a `Blob`, which can be imported
+   * as a module or used as the module of a worker until it is revoked. */
+  static createObjectURL(blob: Blob): string;
+  /** Releases a `blob:` URL created with `URL.createObjectURL()`. */
+  static revokeObjectURL(url: string): void;
 
   hash: string;
   host: string;
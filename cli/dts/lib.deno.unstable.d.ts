@@ -962,12 +962,166 @@ declare namespace Deno {
     options: ConnectOptions | UnixConnectOptions,
   ): Promise<Conn>;
 
+  export interface ListenTlsOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application-Layer Protocol Negotiation (ALPN) protocols supported by
+     * the server, in order of preference. If not specified, no protocol is
+     * negotiated. */
+    alpnProtocols?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Requests a certificate from the clients, which is verified with the CAs
+     * of `clientCaFile`. With `"required"`, the handshake fails for clients
+     * which don't present a valid certificate. With `"optional"`, clients may
+     * connect without one. If not specified, no certificate is requested.
+     *
+     * Requires `allow-read` permission for `clientCaFile`. */
+    clientAuth?: "optional" | "required";
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The CA certificates which verify the client certificates, required for
+     * `clientAuth` unless `clientCaCerts` is specified. */
+    clientCaFile?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM encoded CA certificates which verify the client certificates, in
+     * addition to those of `clientCaFile`. */
+    clientCaCerts?: string[];
+  }
+
+  export interface ListenTlsPemOptions
+    extends Omit<ListenTlsOptions, "certFile" | "keyFile"> {
+    /** The PEM encoded certificate chain the server presents to clients. */
+    certChain: string;
+    /** The PEM encoded private key of `certChain`. */
+    privateKey: string;
+  }
+
+  export interface TlsCertificateOptions {
+    /** Path to a file with the certificate chain, must be specified with
+     * `keyFile`. */
+    certFile?: string;
+    /** Path to a file with the private key of `certFile`. */
+    keyFile?: string;
+    /** The PEM encoded certificate chain, must be specified with
+     * `privateKey`. */
+    certChain?: string;
+    /** The PEM encoded private key of `certChain`. */
+    privateKey?: string;
+  }
+
+  export interface TlsListener extends Listener {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Replaces the certificate chain and private key the listener presents to
+     * clients, from files or PEM strings. Connections which are already
+     * accepted are not affected, which allows certificates to be rotated
+     * without restarting the listener.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   certChain: Deno.env.get("TLS_CERT")!,
+     *   privateKey: Deno.env.get("TLS_KEY")!,
+     * });
+     * // later
+     * listener.reloadCertificates({
+     *   certChain: Deno.env.get("TLS_CERT")!,
+     *   privateKey: Deno.env.get("TLS_KEY")!,
+     * });
+     * ```
+     *
+     * Requires `allow-read` permission for `certFile` and `keyFile`. */
+    reloadCertificates(options: TlsCertificateOptions): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Listen for TLS connections with a certificate chain and private key which
+   * are given as PEM strings instead of files, for example when they are
+   * loaded from the environment or a secret store.
+   *
+   * Requires `allow-net` permission. */
+  export function listenTls(options: ListenTlsPemOptions): TlsListener;
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The listener returned by `Deno.listenTls()` can reload its certificates,
+   * see `Deno.TlsListener`. */
+  export function listenTls(options: ListenTlsOptions): TlsListener;
+
+  export interface ConnectTlsOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Application-Layer Protocol Negotiation (ALPN) protocols to announce to
+     * the server, in order of preference. If not specified, no protocol is
+     * negotiated. */
+    alpnProtocols?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The certificate chain the client presents when the server requests a
+     * client certificate, must be specified with `clientKeyFile`. */
+    clientCertFile?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The private key of `clientCertFile`. */
+    clientKeyFile?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * PEM encoded CA certificates which verify the server certificate, in
+     * addition to Mozilla's root certificates and those of `certFile`. */
+    caCerts?: string[];
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The PEM encoded certificate chain the client presents when the server
+     * requests a client certificate, must be specified with `privateKey`.
+     * Can't be combined with `clientCertFile`. */
+    certChain?: string;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The PEM encoded private key of `certChain`. */
+    privateKey?: string;
+  }
+
+  export interface Conn {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The protocol negotiated with ALPN during the handshake of a TLS
+     * connection, or `null` if none was. It is `undefined` for connections
+     * which don't use TLS. */
+    readonly alpnProtocol?: string | null;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * The DER encoded certificate chain the peer of a TLS connection presented
+     * and which was verified during the handshake, starting with the peer's
+     * own certificate. It is `null` if the peer presented none, like a client
+     * of a listener without `clientAuth`, and `undefined` for connections
+     * which don't use TLS. */
+    readonly peerCertificates?: Uint8Array[] | null;
+  }
+
   export interface StartTlsOptions {
     /** A literal IP address or host name that can be resolved to an IP address.
      * If not specified, defaults to `127.0.0.1`. */
     hostname?: string;
     /** Server certificate file. */
     certFile?: string;
+    /** The certificate chain the client presents when the server requests a
+     * client certificate, must be specified with `clientKeyFile`. */
+    clientCertFile?: string;
+    /** The private key of `clientCertFile`. */
+    clientKeyFile?: string;
+    /** PEM encoded CA certificates which verify the server certificate. */
+    caCerts?: string[];
+    /** The PEM encoded certificate chain the client presents when the server
+     * requests a client certificate, must be specified with `privateKey`. */
+    certChain?: string;
+    /** The PEM encoded private key of `certChain`. */
+    privateKey?: string;
+    /** Application-Layer Protocol Negotiation (ALPN) protocols to announce to
+     * the server, in order of preference. If not specified, no protocol is
+     * negotiated. */
+    alpnProtocols?: string[];
   }
 
   /** **UNSTABLE**: new API, yet to be vetted.
@@ -991,6 +1145,44 @@ declare namespace Deno {
     options?: StartTlsOptions,
   ): Promise<Conn>;
 
+  export interface RequestEvent {
+    readonly request: Request;
+    respondWith(r: Response | Promise<Response>): Promise<void>;
+  }
+
+  export interface HttpConn extends AsyncIterable<RequestEvent> {
+    readonly rid: number;
+
+    nextRequest(): Promise<RequestEvent | null>;
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Serves HTTP/1.1 on a connection accepted with `Deno.listen()` or
+   * `Deno.listenTls()`. The connection is parsed natively and supports
+   * keep-alive and pipelining; each request is answered with `respondWith()`.
+   * HTTP/2 is served instead on TLS connections which negotiated `"h2"` with
+   * ALPN, see the `alpnProtocols` option of `Deno.listenTls()`.
+   * Once served, the connection can't be read from or written to directly.
+   *
+   * ```ts
+   * const listener = Deno.listen({ port: 4500 });
+   * for await (const conn of listener) {
+   *   (async () => {
+   *     const httpConn = Deno.serveHttp(conn);
+   *     for await (const { request, respondWith } of httpConn) {
+   *       await respondWith(new Response("hello world"));
+   *     }
+   *   })();
+   * }
+   * ```
+   *
+   * If the body of the response is a `ReadableStream`, it is streamed to the
+   * client with chunked encoding.
+   */
+  export function serveHttp(conn: Conn): HttpConn;
+
   /** **UNSTABLE**: The `signo` argument may change to require the Deno.Signal
    * enum.
    *
@@ -1009,6 +1201,61 @@ declare namespace Deno {
    * Requires `allow-run` permission. */
   export function kill(pid: number, signo: number): void;
 
+  export interface RunOptions {
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Don't inherit the environment variables of this process, the child only
+     * gets those of `env`. */
+    clearEnv?: boolean;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Run the child as the user with this id. Changing the user usually
+     * requires the process to be privileged. Not supported on Windows. */
+    uid?: number;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Run the child as the group with this id. Not supported on Windows. */
+    gid?: number;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Start the child in a new process group, whose id is the pid of the
+     * child, so the whole group can be signaled with `Deno.kill(-p.pid)`. */
+    newProcessGroup?: boolean;
+    /** **UNSTABLE**: new API, yet to be vetted.
+     *
+     * Start the child in a new session, which detaches it from the
+     * controlling terminal. Implies a new process group, can't be combined
+     * with `newProcessGroup`. Not supported on Windows. */
+    newSession?: boolean;
+  }
+
+  export interface ProcessOutput {
+    status: ProcessStatus;
+    /** The output of the child, or `null` if `stdout` wasn't piped. */
+    stdout: Uint8Array | null;
+    /** The error output of the child, or `null` if `stderr` wasn't piped. */
+    stderr: Uint8Array | null;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Spawns a subprocess like `Deno.run()` and waits for it to exit, while
+   * collecting its stdout and stderr concurrently, so the subprocess can't
+   * block on a full pipe. Unless specified otherwise, stdout and stderr are
+   * piped and stdin is `"null"`.
+   *
+   * ```ts
+   * const { status, stdout, stderr } = await Deno.output({
+   *   cmd: ["git", "status"],
+   * });
+   * ```
+   *
+   * Besides rids of files, stdio options accept rids of sockets and pipes on
+   * Linux and Mac OS, also with `Deno.run()`.
+   *
+   * Requires `allow-run` permission. */
+  export function output(options: RunOptions): Promise<ProcessOutput>;
+
   /** The name of a "powerful feature" which needs permission.
    *
    * See: https://w3c.github.io/permissions/#permission-registry
@@ -1229,6 +1476,24 @@ declare namespace Deno {
     /** A certificate authority to use when validating TLS certificates. Certificate data must be PEM encoded.
      */
     caData?: string;
+
+    /** A certificate chain the client authenticates with to the servers which
+     * request a client certificate, must be specified with `clientKeyFile`.
+     *
+     * Requires `allow-read` permission.
+     */
+    clientCertFile?: string;
+
+    /** The private key of `clientCertFile`.
+     *
+     * Requires `allow-read` permission.
+     */
+    clientKeyFile?: string;
+
+    /** Whether HTTP/2 is used with the servers which select it with ALPN
+     * during the TLS handshake. Defaults to `true`.
+     */
+    http2?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1,5 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
+use crate::auth_tokens::AuthTokens;
 use crate::colors;
 use crate::http_cache::HttpCache;
 use crate::http_util::create_http_client;
@@ -8,6 +9,7 @@ use crate::http_util::get_user_agent;
 use crate::http_util::FetchOnceResult;
 use crate::media_type::MediaType;
 use crate::text_encoding;
+use crate::url_rewrites::UrlRewrites;
 use deno_runtime::permissions::Permissions;
 
 use deno_core::error::custom_error;
@@ -16,9 +18,12 @@ use deno_core::error::uri_error;
 use deno_core::error::AnyError;
 use deno_core::futures;
 use deno_core::futures::future::FutureExt;
+use deno_core::url::Position;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::BlobUrlStore;
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::future::Future;
 use std::io::Read;
@@ -27,7 +32,11 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
 
-pub const SUPPORTED_SCHEMES: [&str; 3] = ["http", "https", "file"];
+/// The number of redirects which are followed when fetching a remote module.
+const REDIRECT_LIMIT: usize = 10;
+
+pub const SUPPORTED_SCHEMES: [&str; 5] =
+  ["http", "https", "file", "data", "blob"];
 
 /// A structure representing a source file.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -78,6 +87,11 @@ pub enum CacheSetting {
   /// `--reload=https://deno.land/std` or
   /// `--reload=https://deno.land/std,https://deno.land/x/example`.
   ReloadSome(Vec<String>),
+  /// The cached remote source files should be used while they are fresh
+  /// according to their `cache-control` and `expires` headers, and revalidated
+  /// with a conditional request once they are stale.  This is the equivalent
+  /// of `--revalidate` in the CLI.
+  RespectHeaders,
   /// The cached source files should be used for local modules.  This is the
   /// default behavior of the CLI.
   Use,
@@ -88,7 +102,9 @@ impl CacheSetting {
   pub fn should_use(&self, specifier: &ModuleSpecifier) -> bool {
     match self {
       CacheSetting::ReloadAll => false,
-      CacheSetting::Use | CacheSetting::Only => true,
+      CacheSetting::Use | CacheSetting::Only | CacheSetting::RespectHeaders => {
+        true
+      }
       CacheSetting::ReloadSome(list) => {
         let mut url = specifier.as_url().clone();
         url.set_fragment(None);
@@ -130,6 +146,89 @@ fn fetch_local(specifier: &ModuleSpecifier) -> Result<File, AnyError> {
   })
 }
 
+/// Fetch a source file from the content of a `data:` URL, where the media type
+/// of the source file is resolved from the MIME type of the URL. See
+/// <https://fetch.spec.whatwg.org/#data-url-processor>.
+fn fetch_data_url(specifier: &ModuleSpecifier) -> Result<File, AnyError> {
+  let url = specifier.as_url();
+  // the fragment is not part of the content
+  let data_url = &url[Position::BeforePath..Position::AfterQuery];
+  let index = data_url.find(',').ok_or_else(|| {
+    uri_error(format!(
+      "Invalid data URL, a comma is missing.\n  Specifier: {}",
+      specifier
+    ))
+  })?;
+  let (mime_type, data) = data_url.split_at(index);
+  let mut params: Vec<&str> = mime_type.split(';').map(str::trim).collect();
+  let is_base64 =
+    params.len() > 1 && params.last().unwrap().eq_ignore_ascii_case("base64");
+  if is_base64 {
+    params.pop();
+  }
+  if params[0].is_empty() {
+    params[0] = "text/plain";
+    if params.len() == 1 {
+      params.push("charset=US-ASCII");
+    }
+  }
+  let content_type = params.join(";");
+
+  let bytes: Vec<u8> =
+    percent_encoding::percent_decode_str(&data[1..]).collect();
+  let bytes = if is_base64 {
+    let bytes: Vec<u8> = bytes
+      .into_iter()
+      .filter(|b| !b.is_ascii_whitespace())
+      .collect();
+    base64::decode(&bytes).map_err(|err| {
+      uri_error(format!(
+        "Invalid data URL, {}.\n  Specifier: {}",
+        err, specifier
+      ))
+    })?
+  } else {
+    bytes
+  };
+  let (media_type, maybe_charset) =
+    map_content_type(specifier, Some(content_type));
+  let source = strip_shebang(get_source_from_bytes(bytes, maybe_charset)?);
+
+  Ok(File {
+    local: PathBuf::new(),
+    maybe_types: None,
+    media_type,
+    source,
+    specifier: specifier.clone(),
+  })
+}
+
+/// Fetch a source file from a `blob:` URL created with `URL.createObjectURL()`,
+/// where the media type of the source file is resolved from the type of the
+/// blob.
+fn fetch_blob_url(
+  specifier: &ModuleSpecifier,
+  blob_url_store: &BlobUrlStore,
+) -> Result<File, AnyError> {
+  let blob = blob_url_store.get(specifier.as_url()).ok_or_else(|| {
+    custom_error(
+      "NotFound",
+      format!("Blob URL not found: \"{}\".", specifier),
+    )
+  })?;
+  let (media_type, maybe_charset) =
+    map_content_type(specifier, Some(blob.media_type));
+  let source = strip_shebang(get_source_from_bytes(blob.data, maybe_charset)?);
+
+  Ok(File {
+    local: PathBuf::new(),
+    maybe_types: None,
+    media_type,
+    source,
+    specifier: specifier.clone(),
+  })
+}
+
 /// Given a vector of bytes and optionally a charset, decode the bytes to a
 /// string.
 pub fn get_source_from_bytes(
@@ -212,6 +311,10 @@ fn map_js_like_extension(
   default: MediaType,
 ) -> MediaType {
   let url = specifier.as_url();
+  // the path of a data or blob URL has no extension
+  if url.scheme() == "data" || url.scheme() == "blob" {
+    return default;
+  }
   let path = if url.scheme() == "file" {
     if let Ok(path) = url.to_file_path() {
       path
@@ -272,10 +375,13 @@ fn strip_shebang(mut value: String) -> String {
 #[derive(Clone)]
 pub struct FileFetcher {
   allow_remote: bool,
+  auth_tokens: AuthTokens,
+  blob_url_store: BlobUrlStore,
   cache: FileCache,
   cache_setting: CacheSetting,
   http_cache: HttpCache,
   http_client: reqwest::Client,
+  url_rewrites: UrlRewrites,
 }
 
 impl FileFetcher {
@@ -284,13 +390,17 @@ impl FileFetcher {
     cache_setting: CacheSetting,
     allow_remote: bool,
     maybe_ca_file: Option<&str>,
+    blob_url_store: BlobUrlStore,
   ) -> Result<Self, AnyError> {
     Ok(Self {
       allow_remote,
       cache: FileCache::default(),
+      auth_tokens: AuthTokens::new(env::var("DENO_AUTH_TOKENS").ok()),
+      blob_url_store,
       cache_setting,
       http_cache,
       http_client: create_http_client(get_user_agent(), maybe_ca_file)?,
+      url_rewrites: UrlRewrites::new(env::var("DENO_URL_REWRITES").ok()),
     })
   }
 
@@ -354,6 +464,22 @@ impl FileFetcher {
     Ok(Some(file))
   }
 
+  /// Check if the cached remote file for a specifier can be used without
+  /// revalidating it. When the cache setting respects the caching headers,
+  /// the file and the redirects leading to it all have to be fresh.
+  fn is_fresh(&self, specifier: &ModuleSpecifier) -> bool {
+    if self.cache_setting != CacheSetting::RespectHeaders {
+      return true;
+    }
+    let redirects = match self.get_redirects(specifier) {
+      Ok(redirects) => redirects,
+      Err(_) => return false,
+    };
+    std::iter::once(specifier.clone())
+      .chain(redirects.into_iter().map(|(_, to)| to))
+      .all(|specifier| self.http_cache.is_fresh(specifier.as_url()))
+  }
+
   /// Asynchronously fetch remote source file specified by the URL following
   /// redirects.
   ///
@@ -375,7 +501,7 @@ impl FileFetcher {
       return futures::future::err(err).boxed();
     }
 
-    if self.cache_setting.should_use(specifier) {
+    if self.cache_setting.should_use(specifier) && self.is_fresh(specifier) {
       match self.fetch_cached(specifier, redirect_limit) {
         Ok(Some(file)) => {
           return futures::future::ok(file).boxed();
@@ -401,21 +527,56 @@ impl FileFetcher {
     info!("{} {}", colors::green("Download"), specifier);
 
     let file_fetcher = self.clone();
-    let cached_etag = match self.http_cache.get(specifier.as_url()) {
-      Ok((_, headers)) => headers.get("etag").cloned(),
-      _ => None,
-    };
+    let (cached_etag, cached_last_modified) =
+      match self.http_cache.get(specifier.as_url()) {
+        Ok((_, headers)) => (
+          headers.get("etag").cloned(),
+          headers.get("last-modified").cloned(),
+        ),
+        _ => (None, None),
+      };
     let specifier = specifier.clone();
     let permissions = permissions.clone();
     let http_client = self.http_client.clone();
+    // The module is fetched from the rewritten URL, but it is still cached and
+    // identified by its original specifier.
+    let request_specifier = match self.url_rewrites.rewrite(&specifier) {
+      Some(url) => {
+        debug!("Rewrite {} to {}", specifier, url);
+        ModuleSpecifier::from(url)
+      }
+      None => specifier.clone(),
+    };
+    let maybe_auth_token = self.auth_tokens.get(&request_specifier);
     // A single pass of fetch either yields code or yields a redirect.
     async move {
-      match fetch_once(http_client, specifier.as_url(), cached_etag).await? {
-        FetchOnceResult::NotModified => {
+      match fetch_once(
+        http_client,
+        request_specifier.as_url(),
+        cached_etag,
+        cached_last_modified,
+        maybe_auth_token,
+      )
+      .await?
+      {
+        FetchOnceResult::NotModified(headers) => {
+          file_fetcher
+            .http_cache
+            .revalidate(specifier.as_url(), headers)?;
           let file = file_fetcher.fetch_cached(&specifier, 10)?.unwrap();
           Ok(file)
         }
-        FetchOnceResult::Redirect(redirect_url, headers) => {
+        FetchOnceResult::Redirect(mut redirect_url, mut headers) => {
+          // The location of a redirect of a rewritten URL is relative to the
+          // URL it was fetched from, so it is cached as an absolute URL which
+          // is mapped back to the original prefix.
+          if request_specifier != specifier {
+            if let Some(url) = file_fetcher.url_rewrites.restore(&redirect_url)
+            {
+              redirect_url = url;
+            }
+            headers.insert("location".to_string(), redirect_url.to_string());
+          }
           file_fetcher
             .http_cache
             .set(specifier.as_url(), headers, &[])?;
@@ -454,13 +615,19 @@ impl FileFetcher {
       let is_local = scheme == "file";
       if is_local {
         fetch_local(specifier)
+      } else if scheme == "data" {
+        fetch_data_url(specifier)
+      } else if scheme == "blob" {
+        fetch_blob_url(specifier, &self.blob_url_store)
       } else if !self.allow_remote {
         Err(custom_error(
           "NoRemote",
           format!("A remote specifier was requested: \"{}\", but --no-remote is specified.", specifier),
         ))
       } else {
-        let result = self.fetch_remote(specifier, permissions, 10).await;
+        let result = self
+          .fetch_remote(specifier, permissions, REDIRECT_LIMIT as i64)
+          .await;
         // only cache remote resources, as they are the only things that would
         // be "expensive" to fetch multiple times during an invocation, and it
         // also allows local file sources to be changed, enabling things like
@@ -473,6 +640,36 @@ impl FileFetcher {
     }
   }
 
+  /// Get the redirect chain of a remote specifier from the `location` headers
+  /// recorded in the HTTP cache, as pairs of the redirected specifier and the
+  /// specifier it was redirected to. Like fetching the specifier, this fails
+  /// when the chain is longer than the redirect limit.
+  pub fn get_redirects(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<Vec<(ModuleSpecifier, ModuleSpecifier)>, AnyError> {
+    let mut redirects = Vec::new();
+    let mut specifier = specifier.clone();
+    loop {
+      let redirect = match self.http_cache.get(specifier.as_url()) {
+        Ok((_, headers)) => headers.get("location").and_then(|location| {
+          ModuleSpecifier::resolve_import(location, specifier.as_str()).ok()
+        }),
+        Err(_) => None,
+      };
+      if let Some(redirect) = redirect {
+        if redirects.len() == REDIRECT_LIMIT {
+          return Err(custom_error("Http", "Too many redirects."));
+        }
+        redirects.push((specifier, redirect.clone()));
+        specifier = redirect;
+      } else {
+        break;
+      }
+    }
+    Ok(redirects)
+  }
+
   /// Get the location of the current HTTP cache associated with the fetcher.
   pub fn get_http_cache_location(&self) -> PathBuf {
     self.http_cache.location.clone()
@@ -484,13 +681,12 @@ impl FileFetcher {
   pub fn get_source(&self, specifier: &ModuleSpecifier) -> Option<File> {
     let maybe_file = self.cache.get(specifier);
     if maybe_file.is_none() {
-      let is_local = specifier.as_url().scheme() == "file";
-      if is_local {
-        if let Ok(file) = fetch_local(specifier) {
-          return Some(file);
-        }
+      match specifier.as_url().scheme() {
+        "file" => fetch_local(specifier).ok(),
+        "data" => fetch_data_url(specifier).ok(),
+        "blob" => fetch_blob_url(specifier, &self.blob_url_store).ok(),
+        _ => None,
       }
-      None
     } else {
       maybe_file
     }
@@ -506,6 +702,7 @@ impl FileFetcher {
 mod tests {
   use super::*;
   use deno_core::error::get_custom_error_class;
+  use deno_runtime::deno_fetch::Blob;
   use std::rc::Rc;
   use tempfile::TempDir;
 
@@ -517,9 +714,14 @@ mod tests {
       Rc::new(TempDir::new().expect("failed to create temp directory"))
     });
     let location = temp_dir.path().join("deps");
-    let file_fetcher =
-      FileFetcher::new(HttpCache::new(&location), cache_setting, true, None)
-        .expect("setup failed");
+    let file_fetcher = FileFetcher::new(
+      HttpCache::new(&location),
+      cache_setting,
+      true,
+      None,
+      BlobUrlStore::default(),
+    )
+    .expect("setup failed");
     (file_fetcher, temp_dir)
   }
 
@@ -588,6 +790,12 @@ mod tests {
       ("http://deno.land/x/mod.ts", true, "http"),
       ("file:///a/b/c.ts", true, "file"),
       ("file:///C:/a/b/c.ts", true, "file"),
+      ("data:,some%20text", true, "data"),
+      (
+        "blob:null/a0a1a2a3-b0b1-c0c1-d0d1-e0e1e2e3e4e5",
+        true,
+        "blob",
+      ),
       ("ftp://a/b/c.ts", false, ""),
       ("mailto:dino@deno.land", false, ""),
     ];
@@ -629,6 +837,7 @@ mod tests {
         Some("application/typescript".to_string()),
         MediaType::TypeScript,
         None,
+        BlobUrlStore::default(),
       ),
       (
         "https://deno.land/x/mod",
@@ -901,6 +1110,7 @@ mod tests {
       CacheSetting::ReloadAll,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("setup failed");
     let result = file_fetcher
@@ -927,6 +1137,7 @@ mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let specifier = ModuleSpecifier::resolve_url(
@@ -954,6 +1165,7 @@ mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let result = file_fetcher_02
@@ -1095,6 +1307,21 @@ mod tests {
       .get(redirected_02_specifier.as_url())
       .expect("could not get file");
     assert!(headers.get("location").is_none());
+
+    assert_eq!(
+      file_fetcher.get_redirects(&specifier).unwrap(),
+      vec![
+        (specifier.clone(), redirected_01_specifier.clone()),
+        (
+          redirected_01_specifier.clone(),
+          redirected_02_specifier.clone()
+        ),
+      ]
+    );
+    assert!(file_fetcher
+      .get_redirects(&redirected_02_specifier)
+      .unwrap()
+      .is_empty());
   }
 
   #[tokio::test]
@@ -1109,6 +1336,7 @@ mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let specifier = ModuleSpecifier::resolve_url(
@@ -1140,6 +1368,7 @@ mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let result = file_fetcher_02
@@ -1186,6 +1415,25 @@ mod tests {
     assert!(result.is_err());
   }
 
+  #[tokio::test]
+  async fn test_get_redirects_limit() {
+    let _http_server_guard = test_util::http_server();
+    let (file_fetcher, _) = setup(CacheSetting::Use, None);
+    // the server redirects every request to the same URL
+    let specifier = ModuleSpecifier::resolve_url(
+      "http://localhost:4549/cli/tests/subdir/redirects/redirect1.js",
+    )
+    .unwrap();
+
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert_eq!(result.unwrap_err().to_string(), "Too many redirects.");
+
+    let result = file_fetcher.get_redirects(&specifier);
+    assert_eq!(result.unwrap_err().to_string(), "Too many redirects.");
+  }
+
   #[tokio::test]
   async fn test_fetch_same_host_redirect() {
     let _http_server_guard = test_util::http_server();
@@ -1247,6 +1495,7 @@ mod tests {
       CacheSetting::Use,
       false,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let specifier = ModuleSpecifier::resolve_url(
@@ -1275,6 +1524,7 @@ mod tests {
       CacheSetting::Only,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let file_fetcher_02 = FileFetcher::new(
@@ -1282,6 +1532,7 @@ mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not create file fetcher");
     let specifier = ModuleSpecifier::resolve_url(
@@ -1365,6 +1616,121 @@ mod tests {
     test_fetch_local_encoded("utf-8", expected).await;
   }
 
+  #[tokio::test]
+  async fn test_fetch_data_url() {
+    let (file_fetcher, _) = setup(CacheSetting::Use, None);
+    let fixtures = vec![
+      (
+        "data:application/javascript,console.log(%22hello%20deno%22)%3B",
+        MediaType::JavaScript,
+        "console.log(\"hello deno\");",
+      ),
+      (
+        "data:application/typescript;base64,ZXhwb3J0IGNvbnN0IGE6IHN0cmluZyA9ICJhIjsK",
+        MediaType::TypeScript,
+        "export const a: string = \"a\";\n",
+      ),
+      (
+        "data:text/typescript;charset=utf-8,export%20const%20b%20%3D%20%22%C3%A9%22%3B",
+        MediaType::TypeScript,
+        "export const b = \"é\";",
+      ),
+      (
+        "data:application/json,%7B%22a%22%3A1%7D#fragment",
+        MediaType::Json,
+        r#"{"a":1}"#,
+      ),
+    ];
+
+    for (specifier, media_type, source) in fixtures {
+      let specifier = ModuleSpecifier::resolve_url(specifier).unwrap();
+      let file = file_fetcher
+        .fetch(&specifier, &Permissions::allow_all())
+        .await
+        .unwrap();
+      assert_eq!(file.media_type, media_type);
+      assert_eq!(file.source, source);
+      assert_eq!(file.specifier, specifier);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_fetch_data_url_invalid() {
+    let (file_fetcher, _) = setup(CacheSetting::Use, None);
+    let fixtures = vec![
+      "data:application/javascript;base64",
+      "data:application/javascript;base64,!!!",
+    ];
+
+    for specifier in fixtures {
+      let specifier = ModuleSpecifier::resolve_url(specifier).unwrap();
+      let result = file_fetcher
+        .fetch(&specifier, &Permissions::allow_all())
+        .await;
+      assert!(result.is_err());
+    }
+  }
+
+  #[tokio::test]
+  async fn test_fetch_blob_url() {
+    let blob_url_store = BlobUrlStore::default();
+    let temp_dir = TempDir::new().expect("could not create temp dir");
+    let file_fetcher = FileFetcher::new(
+      HttpCache::new(&temp_dir.path().join("deps")),
+      CacheSetting::Use,
+      false,
+      None,
+      blob_url_store.clone(),
+    )
+    .expect("could not create file fetcher");
+    let url = blob_url_store.insert(Blob {
+      data: "export const a: string = \"é\";".as_bytes().to_vec(),
+      media_type: "application/typescript;charset=utf-8".to_string(),
+    });
+    let specifier = ModuleSpecifier::resolve_url(url.as_str()).unwrap();
+
+    let file = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await
+      .unwrap();
+    assert_eq!(file.media_type, MediaType::TypeScript);
+    assert_eq!(file.source, "export const a: string = \"é\";");
+    assert_eq!(file.specifier, specifier);
+
+    blob_url_store.remove(&url);
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_err());
+    assert_eq!(
+      get_custom_error_class(&result.unwrap_err()),
+      Some("NotFound")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_fetch_data_url_no_remote() {
+    let temp_dir = TempDir::new().expect("could not create temp dir");
+    let location = temp_dir.path().join("deps");
+    let file_fetcher = FileFetcher::new(
+      HttpCache::new(&location),
+      CacheSetting::Use,
+      false,
+      None,
+      BlobUrlStore::default(),
+    )
+    .expect("could not create file fetcher");
+    let specifier =
+      ModuleSpecifier::resolve_url("data:application/javascript,export%20{}")
+        .unwrap();
+
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_ok());
+    assert!(!location.exists());
+  }
+
   #[tokio::test]
   async fn test_fetch_remote_with_types() {
     let specifier = ModuleSpecifier::resolve_url_or_path(
@@ -1378,6 +1744,133 @@ mod tests {
     );
   }
 
+  #[tokio::test]
+  async fn test_fetch_remote_with_auth_token() {
+    let _http_server_guard = test_util::http_server();
+    let (mut file_fetcher, _) = setup(CacheSetting::ReloadAll, None);
+    let specifier = ModuleSpecifier::resolve_url(
+      "http://localhost:4545/cli/tests/subdir/auth_required.ts",
+    )
+    .unwrap();
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_err());
+
+    file_fetcher.auth_tokens =
+      AuthTokens::new(Some("abcdef123456789@localhost:4545".to_string()));
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_ok());
+    let file = result.unwrap();
+    assert_eq!(file.source, "console.log('Hello from a private host');");
+  }
+
+  #[tokio::test]
+  async fn test_fetch_remote_auth_token_not_sent_on_redirect() {
+    let _http_server_guard = test_util::http_server();
+    let (mut file_fetcher, _) = setup(CacheSetting::ReloadAll, None);
+    // the token only matches the host which redirects, so it must not be sent
+    // to the location of the redirect
+    file_fetcher.auth_tokens =
+      AuthTokens::new(Some("abcdef123456789@localhost:4546".to_string()));
+    let specifier = ModuleSpecifier::resolve_url(
+      "http://localhost:4546/cli/tests/subdir/auth_required.ts",
+    )
+    .unwrap();
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_fetch_remote_with_url_rewrite() {
+    let _http_server_guard = test_util::http_server();
+    let (mut file_fetcher, _) = setup(CacheSetting::ReloadAll, None);
+    file_fetcher.url_rewrites = UrlRewrites::new(Some(
+      "http://example.com/=http://localhost:4550/".to_string(),
+    ));
+    let specifier = ModuleSpecifier::resolve_url(
+      "http://example.com/REDIRECT/cli/tests/subdir/mod2.ts",
+    )
+    .unwrap();
+    let redirected_specifier = ModuleSpecifier::resolve_url(
+      "http://example.com/cli/tests/subdir/mod2.ts",
+    )
+    .unwrap();
+    let result = file_fetcher
+      .fetch(&specifier, &Permissions::allow_all())
+      .await;
+    assert!(result.is_ok());
+    let file = result.unwrap();
+    assert!(file.source.contains("printHello"));
+    assert_eq!(file.specifier, redirected_specifier);
+    assert_eq!(
+      file.local,
+      file_fetcher
+        .http_cache
+        .get_cache_filename(redirected_specifier.as_url())
+    );
+    assert_eq!(
+      file_fetcher.get_redirects(&specifier).unwrap(),
+      vec![(specifier, redirected_specifier)]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_fetch_respect_headers() {
+    let _http_server_guard = test_util::http_server();
+    let (file_fetcher, temp_dir) = setup(CacheSetting::Use, None);
+    let specifier =
+      ModuleSpecifier::resolve_url("http://localhost:4545/etag_script.ts")
+        .unwrap();
+    let cache_with = |cache_control: &str, etag: &str, source: &str| {
+      let mut headers = HashMap::new();
+      headers.insert(
+        "content-type".to_string(),
+        "application/typescript".to_string(),
+      );
+      headers.insert("cache-control".to_string(), cache_control.to_string());
+      headers.insert("etag".to_string(), etag.to_string());
+      file_fetcher
+        .http_cache
+        .set(specifier.as_url(), headers, source.as_bytes())
+        .unwrap();
+    };
+    async fn fetch(
+      specifier: &ModuleSpecifier,
+      temp_dir: &Rc<TempDir>,
+    ) -> String {
+      let (file_fetcher, _) =
+        setup(CacheSetting::RespectHeaders, Some(temp_dir.clone()));
+      file_fetcher
+        .fetch(specifier, &Permissions::allow_all())
+        .await
+        .unwrap()
+        .source
+    }
+
+    // a fresh response is used without a request
+    cache_with(
+      "max-age=3600",
+      "33a64df551425fcc55e",
+      "console.log('fresh')",
+    );
+    assert_eq!(fetch(&specifier, &temp_dir).await, "console.log('fresh')");
+
+    // a stale response which did not change is revalidated
+    cache_with("no-cache", "33a64df551425fcc55e", "console.log('cached')");
+    assert_eq!(fetch(&specifier, &temp_dir).await, "console.log('cached')");
+
+    // a stale response which changed is downloaded again
+    cache_with("no-cache", "0000000000", "console.log('stale')");
+    assert_eq!(fetch(&specifier, &temp_dir).await, "console.log('etag')");
+    let (_, headers) = file_fetcher.http_cache.get(specifier.as_url()).unwrap();
+    assert_eq!(headers.get("etag").unwrap(), "33a64df551425fcc55e");
+  }
+
   #[tokio::test]
   async fn test_fetch_remote_utf16_le() {
     let expected =
@@ -25,10 +25,22 @@ pub enum DenoSubcommand {
   Compile {
     source_file: String,
     output: Option<PathBuf>,
+    workers: Vec<String>,
+    include: Vec<PathBuf>,
+    target: Option<String>,
+    manifest: bool,
+    runtime_unstable: bool,
   },
   Completions {
     buf: Box<[u8]>,
   },
+  Coverage {
+    files: Vec<PathBuf>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    reporter: CoverageReporterKind,
+    output: Option<PathBuf>,
+  },
   Doc {
     private: bool,
     json: bool,
@@ -45,9 +57,17 @@ pub enum DenoSubcommand {
     files: Vec<PathBuf>,
     ignore: Vec<PathBuf>,
   },
+  Gc {
+    files: Vec<String>,
+    list: bool,
+    json: bool,
+    older_than: Option<u64>,
+    dry_run: bool,
+  },
   Info {
     json: bool,
     file: Option<String>,
+    why: Option<String>,
   },
   Install {
     module_url: String,
@@ -63,6 +83,11 @@ pub enum DenoSubcommand {
     rules: bool,
     json: bool,
   },
+  Outdated {
+    files: Vec<PathBuf>,
+    update: bool,
+    registry_api: Option<String>,
+  },
   Repl,
   Run {
     script: String,
@@ -74,6 +99,8 @@ pub enum DenoSubcommand {
     allow_none: bool,
     include: Option<Vec<String>>,
     filter: Option<String>,
+    coverage_reporter: CoverageReporterKind,
+    coverage_output: Option<PathBuf>,
   },
   Types,
   Upgrade {
@@ -84,6 +111,40 @@ pub enum DenoSubcommand {
     output: Option<PathBuf>,
     ca_file: Option<String>,
   },
+  Vendor {
+    files: Vec<String>,
+    output: Option<PathBuf>,
+    force: bool,
+  },
+}
+
+/// The format in which collected coverage is reported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoverageReporterKind {
+  Pretty,
+  Summary,
+  Lcov,
+  Html,
+}
+
+impl Default for CoverageReporterKind {
+  fn default() -> CoverageReporterKind {
+    CoverageReporterKind::Pretty
+  }
+}
+
+impl FromStr for CoverageReporterKind {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "pretty" => Ok(CoverageReporterKind::Pretty),
+      "summary" => Ok(CoverageReporterKind::Summary),
+      "lcov" => Ok(CoverageReporterKind::Lcov),
+      "html" => Ok(CoverageReporterKind::Html),
+      _ => Err(format!("Unknown coverage reporter: {}", s)),
+    }
+  }
 }
 
 impl Default for DenoSubcommand {
@@ -116,6 +177,8 @@ pub struct Flags {
   pub inspect: Option<SocketAddr>,
   pub inspect_brk: Option<SocketAddr>,
   pub lock: Option<PathBuf>,
+  pub lock_prune: bool,
+  pub lock_update: bool,
   pub lock_write: bool,
   pub log_level: Option<Level>,
   pub no_check: bool,
@@ -123,6 +186,7 @@ pub struct Flags {
   pub no_remote: bool,
   pub reload: bool,
   pub repl: bool,
+  pub revalidate: bool,
   pub seed: Option<u64>,
   pub unstable: bool,
   pub v8_flags: Vec<String>,
@@ -212,10 +276,17 @@ impl From<Flags> for PermissionsOptions {
 }
 
 static ENV_VARIABLES_HELP: &str = "ENVIRONMENT VARIABLES:
+    DENO_AUTH_TOKENS     A semi-colon separated list of bearer tokens and
+                         hostnames to use when fetching remote modules from
+                         private repositories
+                         (e.g. \"abcde12345@deno.land;54321edcba@github.com\")
     DENO_DIR             Set the cache directory
     DENO_INSTALL_ROOT    Set deno install's output directory
                          (defaults to $HOME/.deno/bin)
     DENO_CERT            Load certificate authority from PEM encoded file
+    DENO_URL_REWRITES    A semi-colon separated list of URL prefixes to fetch
+                         remote modules from instead of their own
+                         (e.g. \"https://deno.land/x/=https://mirror/x/\")
     NO_COLOR             Set to disable color
     HTTP_PROXY           Proxy address for HTTP requests
                          (module downloads, fetch)
@@ -296,6 +367,8 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     cache_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("info") {
     info_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("gc") {
+    gc_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("eval") {
     eval_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("repl") {
@@ -306,6 +379,8 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     install_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("completions") {
     completions_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("coverage") {
+    coverage_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("test") {
     test_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("upgrade") {
@@ -318,6 +393,10 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     compile_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("lsp") {
     language_server_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("vendor") {
+    vendor_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("outdated") {
+    outdated_parse(&mut flags, m);
   } else {
     repl_parse(&mut flags, &matches);
   }
@@ -369,18 +448,22 @@ If the flag is set, restrict these messages to errors.",
     .subcommand(cache_subcommand())
     .subcommand(compile_subcommand())
     .subcommand(completions_subcommand())
+    .subcommand(coverage_subcommand())
     .subcommand(doc_subcommand())
     .subcommand(eval_subcommand())
     .subcommand(fmt_subcommand())
+    .subcommand(gc_subcommand())
     .subcommand(info_subcommand())
     .subcommand(install_subcommand())
     .subcommand(language_server_subcommand())
     .subcommand(lint_subcommand())
+    .subcommand(outdated_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
     .subcommand(test_subcommand())
     .subcommand(types_subcommand())
     .subcommand(upgrade_subcommand())
+    .subcommand(vendor_subcommand())
     .long_about(DENO_HELP)
     .after_help(ENV_VARIABLES_HELP)
 }
@@ -438,13 +521,71 @@ fn install_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
 fn compile_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   compile_args_parse(flags, matches);
+  permission_args_parse(flags, matches);
+  v8_flags_arg_parse(flags, matches);
+  seed_arg_parse(flags, matches);
 
   let source_file = matches.value_of("source_file").unwrap().to_string();
   let output = matches.value_of("output").map(PathBuf::from);
+  let workers = match matches.values_of("worker") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  let include = match matches.values_of("include") {
+    Some(f) => f.map(PathBuf::from).collect(),
+    None => vec![],
+  };
+  let target = matches.value_of("target").map(String::from);
+  let manifest = matches.is_present("manifest");
+  let runtime_unstable = matches.is_present("runtime-unstable");
 
   flags.subcommand = DenoSubcommand::Compile {
     source_file,
     output,
+    workers,
+    include,
+    target,
+    manifest,
+    runtime_unstable,
+  };
+}
+
+fn vendor_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  import_map_arg_parse(flags, matches);
+  reload_arg_parse(flags, matches);
+  lock_args_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+
+  let files = matches
+    .values_of("file")
+    .unwrap()
+    .map(String::from)
+    .collect();
+  let output = matches.value_of("output").map(PathBuf::from);
+  let force = matches.is_present("force");
+
+  flags.subcommand = DenoSubcommand::Vendor {
+    files,
+    output,
+    force,
+  };
+}
+
+fn outdated_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  import_map_arg_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+
+  let files = match matches.values_of("files") {
+    Some(f) => f.map(PathBuf::from).collect(),
+    None => vec![],
+  };
+  let update = matches.is_present("update");
+  let registry_api = matches.value_of("registry-api").map(String::from);
+
+  flags.subcommand = DenoSubcommand::Outdated {
+    files,
+    update,
+    registry_api,
   };
 }
 
@@ -532,6 +673,28 @@ fn info_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   flags.subcommand = DenoSubcommand::Info {
     file: matches.value_of("file").map(|f| f.to_string()),
     json,
+    why: matches.value_of("why").map(|f| f.to_string()),
+  };
+}
+
+fn gc_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  import_map_arg_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+
+  let files = match matches.values_of("file") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  let older_than = matches
+    .value_of("older-than")
+    .map(|days| days.parse::<u64>().unwrap());
+
+  flags.subcommand = DenoSubcommand::Gc {
+    files,
+    list: matches.is_present("list"),
+    json: matches.is_present("json"),
+    older_than,
+    dry_run: matches.is_present("dry-run"),
   };
 }
 
@@ -553,6 +716,12 @@ fn lock_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("lock-write") {
     flags.lock_write = true;
   }
+  if matches.is_present("lock-update") {
+    flags.lock_update = true;
+  }
+  if matches.is_present("lock-prune") {
+    flags.lock_prune = true;
+  }
 }
 
 fn compile_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
@@ -562,8 +731,10 @@ fn compile_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
     .arg(config_arg())
     .arg(no_check_arg())
     .arg(reload_arg())
+    .arg(revalidate_arg())
     .arg(lock_arg())
     .arg(lock_write_arg())
+    .arg(lock_update_arg())
     .arg(ca_file_arg())
 }
 
@@ -573,6 +744,7 @@ fn compile_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   config_arg_parse(flags, matches);
   no_check_arg_parse(flags, matches);
   reload_arg_parse(flags, matches);
+  revalidate_arg_parse(flags, matches);
   lock_args_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
 }
@@ -673,6 +845,12 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None
   };
 
+  let coverage_reporter = matches
+    .value_of("coverage-reporter")
+    .map(|s| s.parse().unwrap())
+    .unwrap_or_default();
+  let coverage_output = matches.value_of("coverage-output").map(PathBuf::from);
+
   flags.subcommand = DenoSubcommand::Test {
     no_run,
     fail_fast,
@@ -680,6 +858,36 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     include,
     filter,
     allow_none,
+    coverage_reporter,
+    coverage_output,
+  };
+}
+
+fn coverage_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let files = matches
+    .values_of("files")
+    .unwrap()
+    .map(PathBuf::from)
+    .collect();
+  let include = match matches.values_of("include") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  let exclude = match matches.values_of("exclude") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  let reporter = matches
+    .value_of("reporter")
+    .map(|s| s.parse().unwrap())
+    .unwrap_or(CoverageReporterKind::Summary);
+  let output = matches.value_of("output").map(PathBuf::from);
+  flags.subcommand = DenoSubcommand::Coverage {
+    files,
+    include,
+    exclude,
+    reporter,
+    output,
   };
 }
 
@@ -859,7 +1067,9 @@ These must be added to the path manually if required.")
 }
 
 fn compile_subcommand<'a, 'b>() -> App<'a, 'b> {
-  compile_args(SubCommand::with_name("compile"))
+  permission_args(compile_args(SubCommand::with_name("compile")))
+    .arg(v8_flags_arg())
+    .arg(seed_arg())
     .arg(
       Arg::with_name("source_file")
         .takes_value(true)
@@ -872,6 +1082,47 @@ fn compile_subcommand<'a, 'b>() -> App<'a, 'b> {
         .help("Output file (defaults to $PWD/<inferred-name>)")
         .takes_value(true)
     )
+    .arg(
+      Arg::with_name("worker")
+        .long("worker")
+        .help("Embed an additional entrypoint, like a worker, with its dependencies")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1),
+    )
+    .arg(
+      Arg::with_name("include")
+        .long("include")
+        .help("Embed a file or directory, readable with `Deno.readFile()`")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1),
+    )
+    .arg(
+      Arg::with_name("target")
+        .long("target")
+        .help("Target OS architecture")
+        .takes_value(true)
+        .possible_values(&[
+          "x86_64-unknown-linux-gnu",
+          "aarch64-unknown-linux-gnu",
+          "x86_64-unknown-linux-musl",
+          "aarch64-unknown-linux-musl",
+          "x86_64-apple-darwin",
+          "aarch64-apple-darwin",
+          "x86_64-pc-windows-msvc",
+        ]),
+    )
+    .arg(
+      Arg::with_name("manifest")
+        .long("manifest")
+        .help("Embed a manifest of the module specifiers and their hashes"),
+    )
+    .arg(
+      Arg::with_name("runtime-unstable")
+        .long("runtime-unstable")
+        .help("Enable unstable APIs in the executable"),
+    )
     .about("Compile the script into a self contained executable")
     .long_about(
       "Compiles the given script into a self contained executable.
@@ -886,7 +1137,127 @@ The executable name is inferred by default:
     settle with the generic name.
   - If the resulting name has an '@...' suffix, strip it.
 
-Cross compiling binaries for different platforms is not currently possible.",
+The module graph of the script is embedded in the executable, so static and
+dynamic imports are loaded without network access. Only dynamic imports with a
+string literal specifier can be discovered; other modules, like worker
+entrypoints, have to be listed with --worker:
+  deno compile --unstable --worker=./worker.ts main.ts
+  // main.ts
+  new Worker(new URL('./worker.ts', import.meta.url).href, { type: 'module' });
+
+Files and directories given with --include are embedded as a read-only file
+system. Opening them for reading, with their path relative to the directory
+compile was run in, reads the embedded contents instead of the file on disk,
+wherever the executable is run, without requiring --allow-read:
+  deno compile --unstable --include=./templates main.ts
+
+The permission flags, --seed and --v8-flags given to this command are embedded
+in the executable and apply every time it is run:
+  deno compile --unstable --allow-read=/etc --allow-net https://deno.land/std/http/file_server.ts
+
+The --unstable flag is required by the compile command itself and is not
+embedded. To enable the unstable APIs in the executable, pass --runtime-unstable:
+  deno compile --unstable --runtime-unstable main.ts
+
+To compile for a different platform, pass its target triple with --target. The
+executable is then based on a deno binary of the same version built for that
+target, which has to be placed in the DENO_DIR as
+'dl/denort-<version>-<target>' ('.exe' appended for Windows targets):
+  deno compile --unstable --target=aarch64-unknown-linux-gnu main.ts
+
+With --manifest, the specifiers of all embedded modules and the hashes of their
+sources are embedded as well. The executable prints them when it is run with
+--deno-manifest:
+  deno compile --unstable --manifest --lock=lock.json main.ts
+  ./main --deno-manifest",
+    )
+}
+
+fn outdated_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("outdated")
+    .arg(import_map_arg())
+    .arg(ca_file_arg())
+    .arg(
+      Arg::with_name("files")
+        .takes_value(true)
+        .multiple(true)
+        .required(false),
+    )
+    .arg(
+      Arg::with_name("update")
+        .long("update")
+        .help("Update the outdated specifiers to the latest versions"),
+    )
+    .arg(
+      Arg::with_name("registry-api")
+        .long("registry-api")
+        .value_name("URL")
+        .help("The API of the registry which lists the versions of the modules")
+        .takes_value(true),
+    )
+    .about("Check the versioned registry URLs for newer versions")
+    .long_about(
+      "Check the specifiers of the deno.land registry which are pinned to a
+version, like https://deno.land/std@0.83.0/fs/mod.ts, for newer versions.
+
+The imports of the source files in the current directory, or in the given
+files and directories, are checked:
+  deno outdated
+  deno outdated src/ deps.ts
+
+The addresses of an import map are checked too:
+  deno outdated --unstable --import-map=import_map.json
+
+Update the outdated specifiers to the latest versions in place:
+  deno outdated --update
+
+The versions of a module are requested from <URL>/<module>/meta/versions.json,
+where the URL of the registry API can be set, for example to a mirror:
+  deno outdated --registry-api=https://registry.example.com",
+    )
+}
+
+fn vendor_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("vendor")
+    .arg(import_map_arg())
+    .arg(reload_arg())
+    .arg(lock_arg())
+    .arg(ca_file_arg())
+    .arg(
+      Arg::with_name("file")
+        .takes_value(true)
+        .required(true)
+        .min_values(1),
+    )
+    .arg(
+      Arg::with_name("output")
+        .long("output")
+        .short("o")
+        .help("The directory to vendor the modules into (defaults to ./vendor)")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("force")
+        .long("force")
+        .short("f")
+        .help("Vendor into the output directory even if it is not empty"),
+    )
+    .about("Vendor remote modules into a local directory")
+    .long_about(
+      "Vendor the remote dependencies of the given modules into a local directory.
+
+Each remote module is copied to a path made of its host and its path, and an
+import map which maps the remote specifiers to the copies is generated in the
+directory:
+  deno vendor main.ts
+  deno run --unstable --import-map=vendor/import_map.json main.ts
+
+The vendored modules are meant to be committed to source control, so that
+the modules can be reviewed and run without network access.
+
+When an import map is given, its mappings to remote modules are rewritten to
+the vendored copies in the generated import map:
+  deno vendor --unstable --import-map=import_map.json main.ts",
     )
 }
 
@@ -979,6 +1350,10 @@ compiled: Local path of compiled source code. (TypeScript only.)
 map: Local path of source map. (TypeScript only.)
 deps: Dependency tree of the source file.
 
+Show the import chains from a module to the modules which match a specifier or
+a URL prefix, to find out why they are imported:
+  deno info --why https://deno.land/std/ main.ts
+
 Without any additional arguments, 'deno info' shows:
 
 DENO_DIR: Directory containing Deno-managed files.
@@ -997,10 +1372,86 @@ TypeScript compiler cache: Subdirectory containing TS compiler output.",
         .help("Outputs the information in JSON format")
         .takes_value(false),
     )
+    .arg(
+      Arg::with_name("why")
+        .long("why")
+        .value_name("SPECIFIER")
+        .help("Show the import chains to a specifier or URL prefix")
+        .takes_value(true)
+        .requires("file"),
+    )
+}
+
+fn gc_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("gc")
+    .arg(import_map_arg())
+    .arg(ca_file_arg())
+    .arg(
+      Arg::with_name("file")
+        .takes_value(true)
+        .multiple(true)
+        .conflicts_with("list"),
+    )
+    .arg(
+      Arg::with_name("list")
+        .long("list")
+        .help("List the remote modules in the cache"),
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .requires("list")
+        .help("Outputs the list in JSON format"),
+    )
+    .arg(
+      Arg::with_name("older-than")
+        .long("older-than")
+        .value_name("DAYS")
+        .help(
+          "Only remove the entries which were cached more than DAYS days ago",
+        )
+        .takes_value(true)
+        .conflicts_with("list")
+        .validator(|val: String| match val.parse::<u64>() {
+          Ok(_) => Ok(()),
+          Err(_) => Err("The number of days should be a number".to_string()),
+        }),
+    )
+    .arg(
+      Arg::with_name("dry-run")
+        .long("dry-run")
+        .conflicts_with("list")
+        .help("Show the files which would be removed, without removing them"),
+    )
+    .about("Inspect and clean up the cache")
+    .long_about(
+      "Inspect and clean up the cache in the DENO_DIR.
+
+List the remote modules in the cache, with their sizes and the sizes of their
+compiled code:
+  deno gc --list
+
+Remove the remote modules which are not dependencies of the given modules
+anymore, with their compiled code:
+  deno gc main.ts test_deps.ts
+
+Remove the remote modules which were cached more than 30 days ago:
+  deno gc --older-than=30
+
+When both modules and --older-than are given, only the remote modules which
+match both are removed. Files which are left over in the cache, like the
+compiled code of local files which do not exist anymore, are always removed.",
+    )
 }
 
 fn cache_subcommand<'a, 'b>() -> App<'a, 'b> {
   compile_args(SubCommand::with_name("cache"))
+    .arg(
+      Arg::with_name("lock-prune")
+        .long("lock-prune")
+        .requires("lock-update")
+        .help("Remove lock file entries which are not used by the given modules (use with --lock-update)"),
+    )
     .arg(
       Arg::with_name("file")
         .takes_value(true)
@@ -1016,7 +1467,11 @@ in the local cache, without running any code:
   deno cache https://deno.land/std/http/file_server.ts
 
 Future runs of this module will trigger no downloads or compilation unless
---reload is specified.",
+--reload is specified.
+
+Add the dependencies which are not in a lock file yet, keeping its other
+entries, and remove the entries which are not used by the given modules anymore:
+  deno cache --lock=lock.json --lock-update --lock-prune src/deps.ts",
     )
 }
 
@@ -1066,6 +1521,78 @@ update to a different location, use the --output flag
     .arg(ca_file_arg())
 }
 
+fn coverage_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("coverage")
+    .about("Print coverage reports")
+    .long_about(
+      "Print coverage reports from the raw profiles written by --coverage.
+
+Profiles of the same module are merged, so the directories of several runs
+can be combined into a single report:
+  deno coverage --unstable shard1/ shard2/
+
+By default only local modules which are not test modules are reported, the
+urls of the modules to report can be selected with regular expressions:
+  deno coverage --unstable --include='^file:' --exclude='_test\\.ts$' cov/
+
+An --exclude pattern replaces the default one, which excludes the modules named
+like test modules (test.ts, *_test.ts or *.test.ts). Repeat it to keep
+excluding them:
+  deno coverage --unstable --exclude='fixtures/' --exclude='[/._]test\\.ts$' cov/
+
+Write an lcov tracefile instead of the summary, to stdout unless --output is
+given:
+  deno coverage --unstable --reporter=lcov --output=cov.lcov cov/",
+    )
+    .arg(
+      Arg::with_name("include")
+        .long("include")
+        .takes_value(true)
+        .value_name("regex")
+        .multiple(true)
+        .number_of_values(1)
+        .require_equals(true)
+        .default_value(r"^file:")
+        .help("Include source files in the report"),
+    )
+    .arg(
+      Arg::with_name("exclude")
+        .long("exclude")
+        .takes_value(true)
+        .value_name("regex")
+        .multiple(true)
+        .number_of_values(1)
+        .require_equals(true)
+        .default_value(r"[/._]test\.(js|mjs|ts|jsx|tsx)$")
+        .help("Exclude source files from the report, replacing the default"),
+    )
+    .arg(
+      Arg::with_name("reporter")
+        .long("reporter")
+        .takes_value(true)
+        .possible_values(&["summary", "pretty", "lcov", "html"])
+        .help("Format of the coverage report"),
+    )
+    .arg(
+      Arg::with_name("output")
+        .long("output")
+        .takes_value(true)
+        .requires("reporter")
+        .help("Write the lcov or html coverage report to this location")
+        .long_help(
+          "Write the lcov or html coverage report to this location. Without it
+the lcov report is written to stdout and the html report to '<first dir>/html'.",
+        ),
+    )
+    .arg(
+      Arg::with_name("files")
+        .takes_value(true)
+        .multiple(true)
+        .required(true)
+        .help("Directories of raw coverage profiles"),
+    )
+}
+
 fn doc_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("doc")
     .about("Show documentation for a module")
@@ -1321,6 +1848,26 @@ fn test_subcommand<'a, 'b>() -> App<'a, 'b> {
         .conflicts_with("inspect-brk")
         .help("Collect coverage information"),
     )
+    .arg(
+      Arg::with_name("coverage-reporter")
+        .long("coverage-reporter")
+        .takes_value(true)
+        .possible_values(&["pretty", "summary", "lcov", "html"])
+        .requires("coverage")
+        .help("Format of the coverage report")
+        .long_help(
+          "Format of the coverage report. 'lcov' writes a tracefile to
+'<coverage dir>/lcov.info' and 'html' writes a static site to
+'<coverage dir>/html' unless --coverage-output is given.",
+        ),
+    )
+    .arg(
+      Arg::with_name("coverage-output")
+        .long("coverage-output")
+        .takes_value(true)
+        .requires("coverage-reporter")
+        .help("Write the lcov or html coverage report to this location"),
+    )
     .arg(
       Arg::with_name("files")
         .help("List of file names to run")
@@ -1371,6 +1918,16 @@ fn lock_write_arg<'a, 'b>() -> Arg<'a, 'b> {
     .help("Write lock file (use with --lock)")
 }
 
+fn lock_update_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("lock-update")
+    .long("lock-update")
+    .requires("lock")
+    .conflicts_with("lock-write")
+    .help(
+      "Add new entries to the lock file and report changes (use with --lock)",
+    )
+}
+
 fn config_arg<'a, 'b>() -> Arg<'a, 'b> {
   Arg::with_name("config")
     .short("c")
@@ -1488,6 +2045,25 @@ fn reload_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
   }
 }
 
+fn revalidate_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("revalidate")
+    .long("revalidate")
+    .conflicts_with("reload")
+    .help("Revalidate cached remote modules according to their caching headers")
+    .long_help(
+      "Revalidate cached remote modules according to their caching headers.
+A cached module is used while it is fresh according to the Cache-Control and
+Expires headers of its response, and it is then revalidated with a conditional
+request, which only downloads it again if it changed.",
+    )
+}
+
+fn revalidate_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
+  if matches.is_present("revalidate") {
+    flags.revalidate = true;
+  }
+}
+
 fn import_map_arg<'a, 'b>() -> Arg<'a, 'b> {
   Arg::with_name("import-map")
     .long("import-map")
@@ -1732,17 +2308,42 @@ mod tests {
   }
 
   #[test]
-  fn run_watch() {
-    let r = flags_from_vec_safe(svec![
-      "deno",
-      "run",
-      "--unstable",
-      "--watch",
-      "script.ts"
-    ]);
-    let flags = r.unwrap();
+  fn run_revalidate() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--revalidate", "script.ts"]);
     assert_eq!(
-      flags,
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        revalidate: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--revalidate",
+      "--reload",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn run_watch() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--unstable",
+      "--watch",
+      "script.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
       Flags {
         subcommand: DenoSubcommand::Run {
           script: "script.ts".to_string(),
@@ -2128,6 +2729,7 @@ mod tests {
         subcommand: DenoSubcommand::Info {
           json: false,
           file: Some("script.ts".to_string()),
+          why: None,
         },
         ..Flags::default()
       }
@@ -2140,6 +2742,7 @@ mod tests {
         subcommand: DenoSubcommand::Info {
           json: false,
           file: Some("script.ts".to_string()),
+          why: None,
         },
         reload: true,
         ..Flags::default()
@@ -2153,18 +2756,42 @@ mod tests {
         subcommand: DenoSubcommand::Info {
           json: true,
           file: Some("script.ts".to_string()),
+          why: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "info",
+      "--why",
+      "https://deno.land/std/",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info {
+          json: false,
+          file: Some("script.ts".to_string()),
+          why: Some("https://deno.land/std/".to_string()),
         },
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec_safe(svec!["deno", "info", "--why", "a.ts"]);
+    assert!(r.is_err());
+
     let r = flags_from_vec_safe(svec!["deno", "info"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Info {
           json: false,
-          file: None
+          file: None,
+          why: None,
         },
         ..Flags::default()
       }
@@ -2176,7 +2803,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info {
           json: true,
-          file: None
+          file: None,
+          why: None,
         },
         ..Flags::default()
       }
@@ -2642,6 +3270,7 @@ mod tests {
         subcommand: DenoSubcommand::Info {
           file: Some("script.ts".to_string()),
           json: false,
+          why: None,
         },
         unstable: true,
         import_map_path: Some("import_map.json".to_owned()),
@@ -3062,6 +3691,203 @@ mod tests {
     );
   }
 
+  #[test]
+  fn gc() {
+    let r = flags_from_vec_safe(svec!["deno", "gc", "--list", "--json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Gc {
+          files: vec![],
+          list: true,
+          json: true,
+          older_than: None,
+          dry_run: false,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "gc",
+      "--older-than=30",
+      "--dry-run",
+      "main.ts",
+      "test.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Gc {
+          files: svec!["main.ts", "test.ts"],
+          list: false,
+          json: false,
+          older_than: Some(30),
+          dry_run: true,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "gc", "--list", "main.ts"]);
+    assert!(r.is_err());
+    let r = flags_from_vec_safe(svec!["deno", "gc", "--older-than=a"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn vendor() {
+    let r = flags_from_vec_safe(svec!["deno", "vendor", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Vendor {
+          files: svec!["main.ts"],
+          output: None,
+          force: false,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "vendor",
+      "--unstable",
+      "--import-map",
+      "import_map.json",
+      "--lock",
+      "lock.json",
+      "--output",
+      "deps",
+      "--force",
+      "main.ts",
+      "worker.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Vendor {
+          files: svec!["main.ts", "worker.ts"],
+          output: Some(PathBuf::from("deps")),
+          force: true,
+        },
+        unstable: true,
+        import_map_path: Some("import_map.json".to_string()),
+        lock: Some(PathBuf::from("lock.json")),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn outdated() {
+    let r = flags_from_vec_safe(svec!["deno", "outdated"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Outdated {
+          files: vec![],
+          update: false,
+          registry_api: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "outdated",
+      "--unstable",
+      "--import-map",
+      "import_map.json",
+      "--update",
+      "--registry-api",
+      "http://localhost:4545/registry",
+      "src/",
+      "deps.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Outdated {
+          files: vec![PathBuf::from("src/"), PathBuf::from("deps.ts")],
+          update: true,
+          registry_api: Some("http://localhost:4545/registry".to_string()),
+        },
+        unstable: true,
+        import_map_path: Some("import_map.json".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn lock_update() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--lock-update",
+      "--lock=lock.json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        lock_update: true,
+        lock: Some(PathBuf::from("lock.json")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--lock-update",
+      "--lock-write",
+      "--lock=lock.json",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn cache_with_lock_prune() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "cache",
+      "--lock=lock.json",
+      "--lock-update",
+      "--lock-prune",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache {
+          files: svec!["script.ts"],
+        },
+        lock_prune: true,
+        lock_update: true,
+        lock: Some(PathBuf::from("lock.json")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "cache",
+      "--lock=lock.json",
+      "--lock-prune",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn test_with_flags() {
     #[rustfmt::skip]
@@ -3076,6 +3902,8 @@ mod tests {
           allow_none: true,
           quiet: false,
           include: Some(svec!["dir1/", "dir2/"]),
+          coverage_reporter: CoverageReporterKind::Pretty,
+          coverage_output: None,
         },
         unstable: true,
         coverage_dir: Some("cov".to_string()),
@@ -3086,6 +3914,83 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_with_coverage_reporter() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "test", "--unstable", "--coverage=cov", "--coverage-reporter=lcov", "--coverage-output=cov.lcov"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test {
+          no_run: false,
+          fail_fast: false,
+          filter: None,
+          allow_none: false,
+          quiet: false,
+          include: None,
+          coverage_reporter: CoverageReporterKind::Lcov,
+          coverage_output: Some(PathBuf::from("cov.lcov")),
+        },
+        unstable: true,
+        coverage_dir: Some("cov".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn coverage() {
+    let r = flags_from_vec_safe(svec!["deno", "coverage", "cov/"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage {
+          files: vec![PathBuf::from("cov/")],
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"[/._]test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          reporter: CoverageReporterKind::Summary,
+          output: None,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn coverage_default_exclude() {
+    let r = flags_from_vec_safe(svec!["deno", "coverage", "cov/"]);
+    let exclude = match r.unwrap().subcommand {
+      DenoSubcommand::Coverage { exclude, .. } => exclude,
+      _ => unreachable!(),
+    };
+    let exclude = regex::Regex::new(&exclude[0]).unwrap();
+    assert!(exclude.is_match("file:///a/mod_test.ts"));
+    assert!(exclude.is_match("file:///a/mod.test.tsx"));
+    assert!(exclude.is_match("file:///a/test.js"));
+    assert!(!exclude.is_match("file:///a/latest.ts"));
+    assert!(!exclude.is_match("file:///a/contest.mjs"));
+  }
+
+  #[test]
+  fn coverage_with_filters() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "coverage", "--unstable", "--include=^file:", "--include=^https:", "--exclude=_test", "--reporter=lcov", "--output=cov.lcov", "cov1/", "cov2/"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage {
+          files: vec![PathBuf::from("cov1/"), PathBuf::from("cov2/")],
+          include: svec!["^file:", "^https:"],
+          exclude: svec!["_test"],
+          reporter: CoverageReporterKind::Lcov,
+          output: Some(PathBuf::from("cov.lcov")),
+        },
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn run_with_cafile() {
     let r = flags_from_vec_safe(svec![
@@ -3187,6 +4092,7 @@ mod tests {
         subcommand: DenoSubcommand::Info {
           json: false,
           file: Some("https://example.com".to_string()),
+          why: None,
         },
         ca_file: Some("example.crt".to_owned()),
         ..Flags::default()
@@ -3306,8 +4212,159 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Compile {
           source_file: "https://deno.land/std/examples/colors.ts".to_string(),
-          output: None
+          output: None,
+          workers: vec![],
+          include: vec![],
+          target: None,
+          manifest: false,
+          runtime_unstable: false,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_runtime_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--allow-read=/etc", "--allow-net", "--seed", "1", "--v8-flags=--expose-gc", "https://deno.land/std/examples/colors.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "https://deno.land/std/examples/colors.ts".to_string(),
+          output: None,
+          workers: vec![],
+          include: vec![],
+          target: None,
+          manifest: false,
+          runtime_unstable: false,
         },
+        unstable: true,
+        allow_read: Some(vec![PathBuf::from("/etc")]),
+        allow_net: Some(vec![]),
+        seed: Some(1),
+        v8_flags: svec!["--expose-gc", "--random-seed=1"],
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_runtime_unstable() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "compile",
+      "--unstable",
+      "--runtime-unstable",
+      "main.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "main.ts".to_string(),
+          output: None,
+          workers: vec![],
+          include: vec![],
+          target: None,
+          manifest: false,
+          runtime_unstable: true,
+        },
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_workers() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--worker", "worker1.ts", "--worker=worker2.ts", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "main.ts".to_string(),
+          output: None,
+          workers: svec!["worker1.ts", "worker2.ts"],
+          include: vec![],
+          target: None,
+          manifest: false,
+          runtime_unstable: false,
+        },
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_include() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--include", "templates", "--include=schema.json", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "main.ts".to_string(),
+          output: None,
+          workers: vec![],
+          include: vec![
+            PathBuf::from("templates"),
+            PathBuf::from("schema.json")
+          ],
+        },
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile_with_target() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--target", "aarch64-unknown-linux-gnu", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "main.ts".to_string(),
+          output: None,
+          workers: vec![],
+          include: vec![],
+          target: Some("aarch64-unknown-linux-gnu".to_string()),
+          manifest: false,
+          runtime_unstable: false,
+        },
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--target", "mips-unknown-linux-gnu", "main.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn compile_with_manifest() {
+    #[rustfmt::skip]
+    let r = flags_from_vec_safe(svec!["deno", "compile", "--unstable", "--manifest", "--lock", "lock.json", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "main.ts".to_string(),
+          output: None,
+          workers: vec![],
+          include: vec![],
+          target: None,
+          manifest: true,
+          runtime_unstable: false,
+        },
+        unstable: true,
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
@@ -3322,7 +4379,12 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Compile {
           source_file: "https://deno.land/std/examples/colors.ts".to_string(),
-          output: Some(PathBuf::from("colors"))
+          output: Some(PathBuf::from("colors")),
+          workers: vec![],
+          include: vec![],
+          target: None,
+          manifest: false,
+          runtime_unstable: false,
         },
         unstable: true,
         import_map_path: Some("import_map.json".to_string()),
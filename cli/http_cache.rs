@@ -16,9 +16,16 @@ use std::fs::File;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
 
 pub const CACHE_PERM: u32 = 0o644;
 
+/// The headers of a response which are updated in the cache when a conditional
+/// request for it gets a `304 Not Modified` response.
+const REVALIDATED_HEADERS: &[&str] =
+  &["cache-control", "date", "etag", "expires", "last-modified"];
+
 /// Turn base of url (scheme, hostname, port) into a valid filename.
 /// This method replaces port part with a special string token (because
 /// ":" cannot be used in filename on some platforms).
@@ -38,6 +45,8 @@ fn base_url_to_filename(url: &Url) -> PathBuf {
       };
       out.push(host_port);
     }
+    // the content of a data URL is in its path, which is hashed
+    "data" => {}
     scheme => {
       unimplemented!(
         "Don't know how to create cache name for scheme: {}",
@@ -91,7 +100,6 @@ impl Metadata {
     Ok(())
   }
 
-  #[cfg(test)]
   pub fn read(cache_filename: &Path) -> Result<Metadata, AnyError> {
     let metadata_filename = Metadata::filename(&cache_filename);
     let metadata = fs::read_to_string(metadata_filename)?;
@@ -136,9 +144,6 @@ impl HttpCache {
     self.location.join(url_to_filename(url))
   }
 
-  // TODO(bartlomieju): this method should check headers file
-  // and validate against ETAG/Last-modified-as headers.
-  // ETAG check is currently done in `cli/file_fetcher.rs`.
   pub fn get(&self, url: &Url) -> Result<(File, HeadersMap), AnyError> {
     let cache_filename = self.location.join(url_to_filename(url));
     let metadata_filename = Metadata::filename(&cache_filename);
@@ -169,6 +174,105 @@ impl HttpCache {
     };
     metadata.write(&cache_filename)
   }
+
+  /// Update the headers of a cached response from the headers of a `304 Not
+  /// Modified` response, which also resets the time the response was cached.
+  pub fn revalidate(
+    &self,
+    url: &Url,
+    headers_map: HeadersMap,
+  ) -> Result<(), AnyError> {
+    let cache_filename = self.location.join(url_to_filename(url));
+    let mut metadata = Metadata::read(&cache_filename)?;
+    for (key, value) in headers_map {
+      if REVALIDATED_HEADERS.contains(&key.as_str()) {
+        metadata.headers.insert(key, value);
+      }
+    }
+    metadata.write(&cache_filename)
+  }
+
+  /// Check if a cached response can still be used without revalidating it with
+  /// the server, based on its `cache-control`, `expires` and `last-modified`
+  /// headers. The time the response was cached is the time its metadata was
+  /// written.
+  pub fn is_fresh(&self, url: &Url) -> bool {
+    let cache_filename = self.location.join(url_to_filename(url));
+    let metadata = match Metadata::read(&cache_filename) {
+      Ok(metadata) => metadata,
+      Err(_) => return false,
+    };
+    let response_time = match fs::metadata(Metadata::filename(&cache_filename))
+      .and_then(|m| m.modified())
+    {
+      Ok(time) => time,
+      Err(_) => return false,
+    };
+    is_fresh(&metadata.headers, response_time, SystemTime::now())
+  }
+}
+
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+  httpdate::parse_http_date(value.trim()).ok()
+}
+
+/// Return the freshness lifetime of a response as defined in
+/// <https://tools.ietf.org/html/rfc7234#section-4.2.1>, for a private cache.
+/// A response without explicit expiration time gets 10% of the time since it
+/// was last modified, and a response without either has to be revalidated.
+fn freshness_lifetime(headers: &HeadersMap) -> Duration {
+  if let Some(cache_control) = headers.get("cache-control") {
+    let mut max_age = None;
+    for directive in cache_control.split(',') {
+      let directive = directive.trim().to_lowercase();
+      if directive == "no-cache" || directive == "no-store" {
+        return Duration::from_secs(0);
+      }
+      if let Some(value) = directive.strip_prefix("max-age=") {
+        max_age = Some(value.trim_matches('"').parse::<u64>().unwrap_or(0));
+      }
+    }
+    if let Some(max_age) = max_age {
+      return Duration::from_secs(max_age);
+    }
+  }
+  let maybe_date = headers.get("date").and_then(|d| parse_http_date(d));
+  if let Some(expires) = headers.get("expires") {
+    // an invalid date, like "0", means the response is already expired
+    return match (parse_http_date(expires), maybe_date) {
+      (Some(expires), Some(date)) => {
+        expires.duration_since(date).unwrap_or_default()
+      }
+      _ => Duration::from_secs(0),
+    };
+  }
+  match (
+    headers
+      .get("last-modified")
+      .and_then(|d| parse_http_date(d)),
+    maybe_date,
+  ) {
+    (Some(last_modified), Some(date)) => {
+      date.duration_since(last_modified).unwrap_or_default() / 10
+    }
+    _ => Duration::from_secs(0),
+  }
+}
+
+/// Check if a response received at `response_time` is still fresh at `now`,
+/// as defined in <https://tools.ietf.org/html/rfc7234#section-4.2>.
+fn is_fresh(
+  headers: &HeadersMap,
+  response_time: SystemTime,
+  now: SystemTime,
+) -> bool {
+  let age = headers
+    .get("age")
+    .and_then(|age| age.trim().parse::<u64>().ok())
+    .map(Duration::from_secs)
+    .unwrap_or_default()
+    + now.duration_since(response_time).unwrap_or_default();
+  age < freshness_lifetime(headers)
 }
 
 #[cfg(test)]
@@ -234,6 +338,109 @@ mod tests {
     assert_eq!(headers.get("foobar"), None);
   }
 
+  #[test]
+  fn test_revalidate() {
+    let dir = TempDir::new().unwrap();
+    let cache = HttpCache::new(dir.path());
+    let url = Url::parse("https://deno.land/x/welcome.ts").unwrap();
+    let mut headers = HashMap::new();
+    headers.insert(
+      "content-type".to_string(),
+      "application/typescript".to_string(),
+    );
+    headers.insert("cache-control".to_string(), "no-cache".to_string());
+    cache.set(&url, headers, b"Hello world").unwrap();
+    assert!(!cache.is_fresh(&url));
+
+    let mut headers = HashMap::new();
+    headers.insert("cache-control".to_string(), "max-age=600".to_string());
+    headers.insert("content-length".to_string(), "0".to_string());
+    cache.revalidate(&url, headers).unwrap();
+    assert!(cache.is_fresh(&url));
+    let (_, headers) = cache.get(&url).unwrap();
+    assert_eq!(headers.get("cache-control").unwrap(), "max-age=600");
+    assert_eq!(
+      headers.get("content-type").unwrap(),
+      "application/typescript"
+    );
+    assert_eq!(headers.get("content-length"), None);
+
+    let url = Url::parse("https://deno.land/x/missing.ts").unwrap();
+    assert!(!cache.is_fresh(&url));
+  }
+
+  #[test]
+  fn test_is_fresh() {
+    let response_time =
+      httpdate::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+    let now = response_time + Duration::from_secs(60);
+    let headers = |entries: &[(&str, &str)]| -> HeadersMap {
+      entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    };
+
+    let fixtures = vec![
+      (headers(&[]), false),
+      (headers(&[("cache-control", "max-age=120")]), true),
+      (headers(&[("cache-control", "public, max-age=30")]), false),
+      (
+        headers(&[("cache-control", "max-age=120"), ("age", "90")]),
+        false,
+      ),
+      (
+        headers(&[("cache-control", "max-age=120, no-cache")]),
+        false,
+      ),
+      (headers(&[("cache-control", "no-store")]), false),
+      (
+        headers(&[
+          ("cache-control", "max-age=120"),
+          ("expires", "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ]),
+        true,
+      ),
+      (
+        headers(&[
+          ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+          ("expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+        ]),
+        true,
+      ),
+      (
+        headers(&[
+          ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+          ("expires", "Sun, 06 Nov 1994 08:50:00 GMT"),
+        ]),
+        false,
+      ),
+      (headers(&[("expires", "0")]), false),
+      (
+        headers(&[
+          ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+          ("last-modified", "Sat, 05 Nov 1994 08:49:37 GMT"),
+        ]),
+        true,
+      ),
+      (
+        headers(&[
+          ("date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+          ("last-modified", "Sun, 06 Nov 1994 08:44:37 GMT"),
+        ]),
+        false,
+      ),
+    ];
+    for (headers, expected) in fixtures {
+      assert_eq!(
+        is_fresh(&headers, response_time, now),
+        expected,
+        "{:?}",
+        headers
+      );
+    }
+  }
+
   #[test]
   fn test_url_to_filename() {
     let test_cases = [
@@ -1,5 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
+use crate::auth_tokens::AuthToken;
 use crate::version;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
@@ -7,6 +8,8 @@ use deno_core::url::Url;
 use deno_runtime::deno_fetch::reqwest;
 use deno_runtime::deno_fetch::reqwest::header::HeaderMap;
 use deno_runtime::deno_fetch::reqwest::header::HeaderValue;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
+use deno_runtime::deno_fetch::reqwest::header::IF_MODIFIED_SINCE;
 use deno_runtime::deno_fetch::reqwest::header::IF_NONE_MATCH;
 use deno_runtime::deno_fetch::reqwest::header::LOCATION;
 use deno_runtime::deno_fetch::reqwest::header::USER_AGENT;
@@ -80,7 +83,7 @@ pub type HeadersMap = HashMap<String, String>;
 #[derive(Debug, PartialEq)]
 pub enum FetchOnceResult {
   Code(Vec<u8>, HeadersMap),
-  NotModified,
+  NotModified(HeadersMap),
   Redirect(Url, HeadersMap),
 }
 
@@ -89,10 +92,16 @@ pub enum FetchOnceResult {
 /// yields Code(ResultPayload).
 /// If redirect occurs, does not follow and
 /// yields Redirect(url).
+/// When the validators of a cached response are given, the request is
+/// conditional and yields NotModified(headers) if the response didn't change.
+/// The auth token is only sent with this request, so it is never sent to the
+/// location of a redirect.
 pub async fn fetch_once(
   client: Client,
   url: &Url,
   cached_etag: Option<String>,
+  cached_last_modified: Option<String>,
+  maybe_auth_token: Option<AuthToken>,
 ) -> Result<FetchOnceResult, AnyError> {
   let url = url.clone();
 
@@ -102,11 +111,17 @@ pub async fn fetch_once(
     let if_none_match_val = HeaderValue::from_str(&etag).unwrap();
     request = request.header(IF_NONE_MATCH, if_none_match_val);
   }
-  let response = request.send().await?;
-
-  if response.status() == StatusCode::NOT_MODIFIED {
-    return Ok(FetchOnceResult::NotModified);
+  if let Some(last_modified) = cached_last_modified {
+    if let Ok(if_modified_since_val) = HeaderValue::from_str(&last_modified) {
+      request = request.header(IF_MODIFIED_SINCE, if_modified_since_val);
+    }
+  }
+  if let Some(auth_token) = maybe_auth_token {
+    let mut authorization_val = HeaderValue::from_str(&auth_token.to_string())?;
+    authorization_val.set_sensitive(true);
+    request = request.header(AUTHORIZATION, authorization_val);
   }
+  let response = request.send().await?;
 
   let mut headers_: HashMap<String, String> = HashMap::new();
   let headers = response.headers();
@@ -130,6 +145,10 @@ pub async fn fetch_once(
     headers_.insert(key_str, values_str);
   }
 
+  if response.status() == StatusCode::NOT_MODIFIED {
+    return Ok(FetchOnceResult::NotModified(headers_));
+  }
+
   if response.status().is_redirection() {
     if let Some(location) = response.headers().get(LOCATION) {
       let location_string = location.to_str().unwrap();
@@ -171,7 +190,7 @@ mod tests {
     let url =
       Url::parse("http://127.0.0.1:4545/cli/tests/fixture.json").unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(headers.get("content-type").unwrap(), "application/json");
@@ -191,7 +210,7 @@ mod tests {
     )
     .unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('gzip')");
       assert_eq!(
@@ -210,7 +229,7 @@ mod tests {
     let _http_server_guard = test_util::http_server();
     let url = Url::parse("http://127.0.0.1:4545/etag_script.ts").unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client.clone(), &url, None).await;
+    let result = fetch_once(client.clone(), &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('etag')");
@@ -223,9 +242,15 @@ mod tests {
       panic!();
     }
 
-    let res =
-      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string())).await;
-    assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
+    let res = fetch_once(
+      client,
+      &url,
+      Some("33a64df551425fcc55e".to_string()),
+      None,
+      None,
+    )
+    .await;
+    assert!(matches!(res.unwrap(), FetchOnceResult::NotModified(_)));
   }
 
   #[tokio::test]
@@ -237,7 +262,7 @@ mod tests {
     )
     .unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('brotli');");
@@ -262,7 +287,7 @@ mod tests {
     let target_url =
       Url::parse("http://localhost:4545/cli/tests/fixture.json").unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Redirect(url, _)) = result {
       assert_eq!(url, target_url);
     } else {
@@ -325,7 +350,7 @@ mod tests {
       ),
     )
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(headers.get("content-type").unwrap(), "application/json");
@@ -354,7 +379,7 @@ mod tests {
       ),
     )
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('gzip')");
       assert_eq!(
@@ -382,7 +407,7 @@ mod tests {
       ),
     )
     .unwrap();
-    let result = fetch_once(client.clone(), &url, None).await;
+    let result = fetch_once(client.clone(), &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('etag')");
@@ -396,9 +421,15 @@ mod tests {
       panic!();
     }
 
-    let res =
-      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string())).await;
-    assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
+    let res = fetch_once(
+      client,
+      &url,
+      Some("33a64df551425fcc55e".to_string()),
+      None,
+      None,
+    )
+    .await;
+    assert!(matches!(res.unwrap(), FetchOnceResult::NotModified(_)));
   }
 
   #[tokio::test]
@@ -419,7 +450,7 @@ mod tests {
       ),
     )
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('brotli');");
@@ -440,7 +471,7 @@ mod tests {
     let url_str = "http://127.0.0.1:4545/bad_redirect";
     let url = Url::parse(url_str).unwrap();
     let client = create_test_client(None);
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None, None).await;
     assert!(result.is_err());
     let err = result.unwrap_err();
     // Check that the error message contains the original URL
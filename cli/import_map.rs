@@ -7,6 +7,7 @@ use deno_core::serde_json::Value;
 use deno_core::url::Url;
 use deno_core::ModuleSpecifier;
 use indexmap::IndexMap;
+use serde::Serialize;
 use std::cmp::Ordering;
 use std::error::Error;
 use std::fmt;
@@ -41,6 +42,20 @@ const SUPPORTED_FETCH_SCHEMES: [&str; 3] = ["http", "https", "file"];
 type SpecifierMap = IndexMap<String, Vec<ModuleSpecifier>>;
 type ScopesMap = IndexMap<String, SpecifierMap>;
 
+/// The entry of an import map which a specifier was resolved with.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportMapMatch {
+  /// The key of the entry, which is either the specifier itself or a prefix of
+  /// it which ends with a `/`.
+  pub key: String,
+  /// The address the key of the entry is mapped to.
+  pub address: ModuleSpecifier,
+  /// The scope the entry is part of, or `None` when the specifier fell back to
+  /// the top level `imports` of the import map.
+  pub scope: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ImportMap {
   base_url: String,
@@ -469,6 +484,62 @@ impl ImportMap {
       normalized_specifier
     )))
   }
+
+  /// Return the entry of the import map which a specifier is resolved with,
+  /// following the same order as `resolve()`: the scope which is the referrer,
+  /// the scopes which are a prefix of the referrer from the most specific one,
+  /// and then the top level `imports`.
+  pub fn resolve_match(
+    &self,
+    specifier: &str,
+    referrer: &str,
+  ) -> Option<ImportMapMatch> {
+    let normalized_specifier =
+      match ImportMap::try_url_like_specifier(specifier, referrer) {
+        Some(url) => url.to_string(),
+        None => specifier.to_string(),
+      };
+
+    let scopes = self.scopes.get_key_value(referrer).into_iter().chain(
+      self.scopes.iter().filter(|(scope, _)| {
+        scope.ends_with('/') && referrer.starts_with(scope.as_str())
+      }),
+    );
+    for (scope, scope_imports) in scopes {
+      if let Some(import_map_match) =
+        ImportMap::find_match(scope_imports, &normalized_specifier)
+      {
+        return Some(ImportMapMatch {
+          scope: Some(scope.clone()),
+          ..import_map_match
+        });
+      }
+    }
+
+    ImportMap::find_match(&self.imports, &normalized_specifier)
+  }
+
+  /// Find the entry of a specifier map which `resolve_imports_match()` maps a
+  /// normalized specifier with, where the map is sorted so the most specific
+  /// prefix is found first.
+  fn find_match(
+    imports: &SpecifierMap,
+    normalized_specifier: &str,
+  ) -> Option<ImportMapMatch> {
+    let (key, address_vec) =
+      imports.get_key_value(normalized_specifier).or_else(|| {
+        imports.iter().find(|(key, _)| {
+          key.ends_with('/') && normalized_specifier.starts_with(key.as_str())
+        })
+      })?;
+    let address = address_vec.first()?;
+
+    Some(ImportMapMatch {
+      key: key.clone(),
+      address: address.clone(),
+      scope: None,
+    })
+  }
 }
 
 #[cfg(test)]
@@ -1977,6 +2048,69 @@ mod tests {
     );
   }
 
+  #[test]
+  fn resolve_match_scopes() {
+    let base_url = "https://example.com/app/main.ts";
+
+    let json_map = r#"{
+    "imports": {
+      "a": "/a-1.mjs",
+      "b/": "/b-1/",
+      "https://example.com/c/": "/c-1/"
+    },
+    "scopes": {
+      "/scope2/": {
+        "a": "/a-2.mjs"
+      },
+      "/scope2/scope3/": {
+        "b/": "/b-3/"
+      }
+    }
+  }"#;
+    let import_map = ImportMap::from_json(base_url, json_map).unwrap();
+
+    let scope_1_url = "https://example.com/scope1/foo.mjs";
+    let scope_3_url = "https://example.com/scope2/scope3/foo.mjs";
+
+    assert_eq!(
+      import_map.resolve_match("a", scope_1_url),
+      Some(ImportMapMatch {
+        key: "a".to_string(),
+        address: ModuleSpecifier::resolve_url("https://example.com/a-1.mjs")
+          .unwrap(),
+        scope: None,
+      })
+    );
+    assert_eq!(
+      import_map.resolve_match("a", scope_3_url),
+      Some(ImportMapMatch {
+        key: "a".to_string(),
+        address: ModuleSpecifier::resolve_url("https://example.com/a-2.mjs")
+          .unwrap(),
+        scope: Some("https://example.com/scope2/".to_string()),
+      })
+    );
+    assert_eq!(
+      import_map.resolve_match("b/mod.mjs", scope_3_url),
+      Some(ImportMapMatch {
+        key: "b/".to_string(),
+        address: ModuleSpecifier::resolve_url("https://example.com/b-3/")
+          .unwrap(),
+        scope: Some("https://example.com/scope2/scope3/".to_string()),
+      })
+    );
+    assert_eq!(
+      import_map.resolve_match("/c/mod.mjs", scope_1_url),
+      Some(ImportMapMatch {
+        key: "https://example.com/c/".to_string(),
+        address: ModuleSpecifier::resolve_url("https://example.com/c-1/")
+          .unwrap(),
+        scope: None,
+      })
+    );
+    assert_eq!(import_map.resolve_match("./d.mjs", scope_1_url), None);
+  }
+
   #[test]
   fn resolve_scopes_relative_url_keys() {
     // https://github.com/WICG/import-maps#scope-inheritance
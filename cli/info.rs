@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
 use crate::colors;
+use crate::import_map::ImportMapMatch;
 use crate::media_type::serialize_media_type;
 use crate::MediaType;
 use crate::ModuleSpecifier;
@@ -145,6 +146,58 @@ impl ModuleInfo {
   }
 }
 
+/// The import chains from the root module of a module graph to the modules
+/// which match a target specifier or URL prefix. This is used to represent
+/// information as part of the `info --why` subcommand.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportChainsInfo {
+  pub chains: Vec<Vec<ModuleSpecifier>>,
+  pub module: ModuleSpecifier,
+  pub target: String,
+  /// Set when there are more chains than the ones which were collected.
+  pub truncated: bool,
+}
+
+impl fmt::Display for ImportChainsInfo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.chains.is_empty() {
+      return writeln!(f, "{} is not imported by {}", self.target, self.module);
+    }
+    writeln!(f, "{} {}", colors::bold("target:"), self.target)?;
+    let truncated = if self.truncated {
+      colors::gray(" (truncated)").to_string()
+    } else {
+      "".to_string()
+    };
+    writeln!(
+      f,
+      "{} {}{}",
+      colors::bold("chains:"),
+      self.chains.len(),
+      truncated
+    )?;
+    for chain in &self.chains {
+      writeln!(f)?;
+      for (idx, specifier) in chain.iter().enumerate() {
+        if idx == 0 {
+          writeln!(f, "{}", specifier)?;
+        } else {
+          writeln!(
+            f,
+            "{}{} {}",
+            " ".repeat((idx - 1) * 2),
+            colors::gray("└─"),
+            specifier
+          )?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
 /// A flat map of dependencies for a given module graph.
 #[derive(Debug)]
 pub struct ModuleInfoMap(pub HashMap<ModuleSpecifier, ModuleInfoMapItem>);
@@ -173,10 +226,32 @@ impl Serialize for ModuleInfoMap {
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModuleInfoMapItem {
+  pub dependencies: Vec<DependencyInfo>,
   pub deps: Vec<ModuleSpecifier>,
   pub size: usize,
 }
 
+/// Describes how a dependency of a module was resolved, from the specifier in
+/// the source code of the module to the module which was loaded for it.
+#[derive(Debug, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyInfo {
+  /// The specifier as it is written in the source code of the module.
+  pub specifier: String,
+  pub is_dynamic: bool,
+  /// The resolved specifier of the runtime code of the dependency.
+  pub code: Option<ModuleSpecifier>,
+  /// The resolved specifier of the type only dependency, or of the types of
+  /// a `@deno-types` comment.
+  #[serde(rename = "type")]
+  pub maybe_type: Option<ModuleSpecifier>,
+  /// The entry of the import map which resolved the specifier, if any.
+  pub import_map: Option<ImportMapMatch>,
+  /// The redirects which were followed from the resolved specifier, where the
+  /// last one is the module which was loaded.
+  pub redirects: Vec<ModuleSpecifier>,
+}
+
 /// A function that converts a float to a string the represents a human
 /// readable version of that number.
 pub fn human_size(size: f64) -> String {
@@ -240,6 +315,7 @@ mod test {
     items.insert(
       spec_c,
       ModuleInfoMapItem {
+        dependencies: Vec::new(),
         deps: vec![spec_d.clone()],
         size: 12345,
       },
@@ -247,6 +323,7 @@ mod test {
     items.insert(
       spec_d,
       ModuleInfoMapItem {
+        dependencies: Vec::new(),
         deps: Vec::new(),
         size: 12345,
       },
@@ -269,6 +346,50 @@ mod test {
     }
   }
 
+  #[test]
+  fn test_import_chains_info_display() {
+    let spec_a =
+      ModuleSpecifier::resolve_url_or_path("file:///a/main.ts").unwrap();
+    let spec_b =
+      ModuleSpecifier::resolve_url_or_path("file:///a/deps.ts").unwrap();
+    let spec_c =
+      ModuleSpecifier::resolve_url_or_path("https://deno.land/x/c.ts").unwrap();
+    let fixture = ImportChainsInfo {
+      chains: vec![
+        vec![spec_a.clone(), spec_b, spec_c.clone()],
+        vec![spec_a.clone(), spec_c],
+      ],
+      module: spec_a.clone(),
+      target: "https://deno.land/x/".to_string(),
+      truncated: false,
+    };
+    let actual = colors::strip_ansi_codes(&fixture.to_string()).to_string();
+    assert_eq!(
+      actual,
+      r#"target: https://deno.land/x/
+chains: 2
+
+file:///a/main.ts
+└─ file:///a/deps.ts
+  └─ https://deno.land/x/c.ts
+
+file:///a/main.ts
+└─ https://deno.land/x/c.ts
+"#
+    );
+
+    let fixture = ImportChainsInfo {
+      chains: Vec::new(),
+      module: spec_a,
+      target: "https://deno.land/std/".to_string(),
+      truncated: false,
+    };
+    assert_eq!(
+      fixture.to_string(),
+      "https://deno.land/std/ is not imported by file:///a/main.ts\n"
+    );
+  }
+
   #[test]
   fn test_module_graph_info_display() {
     let fixture = get_fixture();
@@ -291,6 +412,7 @@ mod test {
         "fileType": "TypeScript",
         "files": {
           "https://deno.land/x/a/b/c.ts":{
+            "dependencies": [],
             "deps": [],
             "size": 12345
           }
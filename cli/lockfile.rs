@@ -1,41 +1,168 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
+use crate::colors;
 use deno_core::serde_json;
-use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Error;
+use std::io::ErrorKind;
 use std::io::Result;
 use std::path::PathBuf;
 
+/// The version of the lock file format which is written.
+const LOCKFILE_VERSION: &str = "2";
+
+/// The contents of a version 2 lock file. Version 1 lock files are a flat map
+/// of specifiers to the hashes of their sources, which is read as `remote`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LockfileContent {
+  version: String,
+  /// The hashes of the sources of remote modules, including type definition
+  /// files.
+  remote: BTreeMap<String, String>,
+  /// The redirects which were followed when fetching remote modules, one
+  /// entry for each hop of a redirect chain.
+  redirects: BTreeMap<String, String>,
+  /// The type definition files of modules, given with `@deno-types`, a
+  /// triple-slash reference or a `X-TypeScript-Types` header.
+  types: BTreeMap<String, String>,
+}
+
+/// A change of an entry of the lock file, which is reported when the lock
+/// file is updated with `--lock-update`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockfileChange {
+  Added(String),
+  Changed(String),
+  Removed(String),
+}
+
+impl fmt::Display for LockfileChange {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LockfileChange::Added(entry) => {
+        write!(f, "{} {}", colors::green("Add"), entry)
+      }
+      LockfileChange::Changed(entry) => {
+        write!(f, "{} {}", colors::yellow("Change"), entry)
+      }
+      LockfileChange::Removed(entry) => {
+        write!(f, "{} {}", colors::red("Remove"), entry)
+      }
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct Lockfile {
   write: bool,
-  map: BTreeMap<String, String>,
+  update: bool,
+  content: LockfileContent,
+  /// The content of the lock file the last time it was read or written, which
+  /// the changes of an update are reported against.
+  written: LockfileContent,
+  /// The entries which were checked or inserted since the lock file was
+  /// loaded, which are kept when the lock file is pruned.
+  seen: LockfileContent,
   pub filename: PathBuf,
 }
 
 impl Lockfile {
   pub fn new(filename: PathBuf, write: bool) -> Result<Lockfile> {
-    let map = if write {
-      BTreeMap::new()
+    let content = if write {
+      LockfileContent {
+        version: LOCKFILE_VERSION.to_string(),
+        ..Default::default()
+      }
     } else {
       let s = std::fs::read_to_string(&filename)?;
-      serde_json::from_str(&s)?
+      parse_content(&s)?
     };
 
     Ok(Lockfile {
       write,
-      map,
+      update: false,
+      written: content.clone(),
+      content,
+      seen: Default::default(),
+      filename,
+    })
+  }
+
+  /// Create a lock file for `--lock-update`, where the entries of the existing
+  /// lock file are kept and new entries are merged into it. The lock file is
+  /// migrated to the current version when it is written.
+  pub fn new_update(filename: PathBuf) -> Result<Lockfile> {
+    let written = match std::fs::read_to_string(&filename) {
+      Ok(s) => parse_content(&s)?,
+      Err(err) if err.kind() == ErrorKind::NotFound => LockfileContent {
+        version: LOCKFILE_VERSION.to_string(),
+        ..Default::default()
+      },
+      Err(err) => return Err(err),
+    };
+    let content = LockfileContent {
+      version: LOCKFILE_VERSION.to_string(),
+      ..written.clone()
+    };
+
+    Ok(Lockfile {
+      write: true,
+      update: true,
+      content,
+      written,
+      seen: Default::default(),
       filename,
     })
   }
 
+  /// Remove the entries which were not checked since the lock file was loaded,
+  /// as they are not used by the modules which were loaded anymore.
+  pub fn prune(&mut self) {
+    self.content.remote = self.seen.remote.clone();
+    self.content.redirects = self.seen.redirects.clone();
+    self.content.types = self.seen.types.clone();
+  }
+
+  /// Return the changes of the entries since the lock file was last read or
+  /// written.
+  pub fn changes(&self) -> Vec<LockfileChange> {
+    let mut changes = Vec::new();
+    diff_entries(
+      &self.written.remote,
+      &self.content.remote,
+      |specifier, _| specifier.to_string(),
+      &mut changes,
+    );
+    diff_entries(
+      &self.written.redirects,
+      &self.content.redirects,
+      |from, to| format!("redirect {} -> {}", from, to),
+      &mut changes,
+    );
+    diff_entries(
+      &self.written.types,
+      &self.content.types,
+      |specifier, types| format!("types {} -> {}", specifier, types),
+      &mut changes,
+    );
+    changes
+  }
+
   // Synchronize lock file to disk - noop if --lock-write file is not specified.
-  pub fn write(&self) -> Result<()> {
+  pub fn write(&mut self) -> Result<()> {
     if !self.write {
       return Ok(());
     }
-    let j = json!(&self.map);
-    let s = serde_json::to_string_pretty(&j).unwrap();
+    if self.update {
+      for change in self.changes() {
+        eprintln!("{}", change);
+      }
+    }
+    let s = serde_json::to_string_pretty(&self.content).unwrap();
     let mut f = std::fs::OpenOptions::new()
       .write(true)
       .create(true)
@@ -44,9 +171,16 @@ impl Lockfile {
     use std::io::Write;
     f.write_all(s.as_bytes())?;
     debug!("lockfile write {}", self.filename.display());
+    self.written = self.content.clone();
     Ok(())
   }
 
+  /// Lock files of version 1 only record the hashes of remote modules, so
+  /// redirects and type definitions are not checked against them.
+  fn is_v1(&self) -> bool {
+    self.content.version == "1"
+  }
+
   pub fn check_or_insert(&mut self, specifier: &str, code: &str) -> bool {
     if self.write {
       // In case --lock-write is specified check always passes
@@ -63,7 +197,7 @@ impl Lockfile {
     if specifier.starts_with("file:") {
       return true;
     }
-    if let Some(lockfile_checksum) = self.map.get(specifier) {
+    if let Some(lockfile_checksum) = self.content.remote.get(specifier) {
       let compiled_checksum = crate::checksum::gen(&[code.as_bytes()]);
       lockfile_checksum == &compiled_checksum
     } else {
@@ -76,7 +210,103 @@ impl Lockfile {
       return;
     }
     let checksum = crate::checksum::gen(&[code.as_bytes()]);
-    self.map.insert(specifier.to_string(), checksum);
+    self
+      .seen
+      .remote
+      .insert(specifier.to_string(), checksum.clone());
+    self.content.remote.insert(specifier.to_string(), checksum);
+  }
+
+  /// Check a redirect from one remote specifier to another against the lock
+  /// file, or record it when writing the lock file.
+  pub fn check_or_insert_redirect(&mut self, from: &str, to: &str) -> bool {
+    if from.starts_with("file:") {
+      return true;
+    }
+    if self.write {
+      self.seen.redirects.insert(from.to_string(), to.to_string());
+      self
+        .content
+        .redirects
+        .insert(from.to_string(), to.to_string());
+      true
+    } else if self.is_v1() {
+      true
+    } else {
+      self.content.redirects.get(from).map(|s| s.as_str()) == Some(to)
+    }
+  }
+
+  /// Check the type definition file of a module against the lock file, or
+  /// record it when writing the lock file.
+  pub fn check_or_insert_types(
+    &mut self,
+    specifier: &str,
+    types: &str,
+  ) -> bool {
+    if specifier.starts_with("file:") && types.starts_with("file:") {
+      return true;
+    }
+    if self.write {
+      self
+        .seen
+        .types
+        .insert(specifier.to_string(), types.to_string());
+      self
+        .content
+        .types
+        .insert(specifier.to_string(), types.to_string());
+      true
+    } else if self.is_v1() {
+      true
+    } else {
+      self.content.types.get(specifier).map(|s| s.as_str()) == Some(types)
+    }
+  }
+}
+
+/// Push the changes between two maps of entries of a lock file, where
+/// `describe` formats an entry from its key and value.
+fn diff_entries<F>(
+  old: &BTreeMap<String, String>,
+  new: &BTreeMap<String, String>,
+  describe: F,
+  changes: &mut Vec<LockfileChange>,
+) where
+  F: Fn(&str, &str) -> String,
+{
+  for (key, value) in new.iter() {
+    match old.get(key) {
+      None => changes.push(LockfileChange::Added(describe(key, value))),
+      Some(old_value) if old_value != value => {
+        changes.push(LockfileChange::Changed(describe(key, value)))
+      }
+      _ => {}
+    }
+  }
+  for (key, value) in old.iter() {
+    if !new.contains_key(key) {
+      changes.push(LockfileChange::Removed(describe(key, value)));
+    }
+  }
+}
+
+/// Parse the contents of a lock file, migrating a version 1 lock file.
+fn parse_content(s: &str) -> Result<LockfileContent> {
+  let value: Value = serde_json::from_str(s)?;
+  match value.get("version") {
+    Some(Value::String(version)) if version == LOCKFILE_VERSION => {
+      Ok(serde_json::from_value(value)?)
+    }
+    Some(version) => Err(Error::new(
+      ErrorKind::InvalidData,
+      format!("Unsupported lock file version: {}", version),
+    )),
+    None => Ok(LockfileContent {
+      version: "1".to_string(),
+      remote: serde_json::from_value(value)?,
+      ..Default::default()
+    }),
   }
 }
 
@@ -124,7 +354,7 @@ mod tests {
 
     let result = Lockfile::new(file_path, false).unwrap();
 
-    let keys: Vec<String> = result.map.keys().cloned().collect();
+    let keys: Vec<String> = result.content.remote.keys().cloned().collect();
     let expected_keys = vec![
       String::from("https://deno.land/std@0.71.0/async/delay.ts"),
       String::from("https://deno.land/std@0.71.0/textproto/mod.ts"),
@@ -147,7 +377,7 @@ mod tests {
       "Here is some source code",
     );
 
-    let keys: Vec<String> = lockfile.map.keys().cloned().collect();
+    let keys: Vec<String> = lockfile.content.remote.keys().cloned().collect();
     let expected_keys = vec![
       String::from("https://deno.land/std@0.71.0/async/delay.ts"),
       String::from("https://deno.land/std@0.71.0/io/util.ts"),
@@ -192,7 +422,8 @@ mod tests {
 
     let contents_json =
       serde_json::from_str::<serde_json::Value>(&contents).unwrap();
-    let object = contents_json.as_object().unwrap();
+    assert_eq!(contents_json["version"], json!("2"));
+    let object = contents_json["remote"].as_object().unwrap();
 
     assert_eq!(
       object
@@ -243,4 +474,220 @@ mod tests {
 
     teardown(temp_dir);
   }
+
+  #[test]
+  fn new_v2_lockfile_and_check() {
+    let temp_dir = TempDir::new().expect("could not create temp dir");
+    let file_path = temp_dir.path().join("lockfile_v2.json");
+    let value: serde_json::Value = json!({
+      "version": "2",
+      "remote": {
+        "https://deno.land/x/a@1.0.0/mod.js": "fedebba9bb82cce293196f54b21875b649e457f0eaf55556f1e318204947a28f"
+      },
+      "redirects": {
+        "https://deno.land/x/a/mod.js": "https://deno.land/x/a@1.0.0/mod.js"
+      },
+      "types": {
+        "https://deno.land/x/a@1.0.0/mod.js": "https://deno.land/x/a@1.0.0/mod.d.ts"
+      }
+    });
+    std::fs::write(&file_path, value.to_string()).unwrap();
+
+    let mut lockfile = Lockfile::new(file_path, false).unwrap();
+    assert!(lockfile.check_or_insert(
+      "https://deno.land/x/a@1.0.0/mod.js",
+      "Here is some source code"
+    ));
+    assert!(lockfile.check_or_insert_redirect(
+      "https://deno.land/x/a/mod.js",
+      "https://deno.land/x/a@1.0.0/mod.js"
+    ));
+    assert!(!lockfile.check_or_insert_redirect(
+      "https://deno.land/x/a/mod.js",
+      "https://deno.land/x/a@2.0.0/mod.js"
+    ));
+    assert!(!lockfile.check_or_insert_redirect(
+      "https://deno.land/x/b/mod.js",
+      "https://deno.land/x/b@1.0.0/mod.js"
+    ));
+    assert!(lockfile.check_or_insert_types(
+      "https://deno.land/x/a@1.0.0/mod.js",
+      "https://deno.land/x/a@1.0.0/mod.d.ts"
+    ));
+    assert!(!lockfile.check_or_insert_types(
+      "https://deno.land/x/a@1.0.0/mod.js",
+      "https://deno.land/x/a@1.0.0/other.d.ts"
+    ));
+    assert!(
+      lockfile.check_or_insert_types("file:///a/mod.js", "file:///a/mod.d.ts")
+    );
+  }
+
+  #[test]
+  fn v1_lockfile_skips_redirects_and_types() {
+    let (temp_dir, file_path) = setup();
+
+    let mut lockfile = Lockfile::new(file_path, false).unwrap();
+    assert!(lockfile.is_v1());
+    assert!(lockfile.check_or_insert_redirect(
+      "https://deno.land/std/async/delay.ts",
+      "https://deno.land/std@0.71.0/async/delay.ts"
+    ));
+    assert!(lockfile.check_or_insert_types(
+      "https://deno.land/std@0.71.0/async/delay.ts",
+      "https://deno.land/std@0.71.0/async/delay.d.ts"
+    ));
+
+    teardown(temp_dir);
+  }
+
+  #[test]
+  fn write_migrates_to_v2() {
+    let (temp_dir, file_path) = setup();
+
+    let mut lockfile = Lockfile::new(file_path.clone(), true).unwrap();
+    lockfile.check_or_insert(
+      "https://deno.land/std@0.71.0/async/delay.ts",
+      "this source is really exciting",
+    );
+    lockfile.check_or_insert_redirect(
+      "https://deno.land/std/async/delay.ts",
+      "https://deno.land/std@0.71.0/async/delay.ts",
+    );
+    lockfile.write().expect("unable to write");
+
+    let lockfile = Lockfile::new(file_path, false).unwrap();
+    assert!(!lockfile.is_v1());
+    assert_eq!(lockfile.content.remote.len(), 1);
+    assert_eq!(
+      lockfile
+        .content
+        .redirects
+        .get("https://deno.land/std/async/delay.ts")
+        .map(|s| s.as_str()),
+      Some("https://deno.land/std@0.71.0/async/delay.ts")
+    );
+
+    teardown(temp_dir);
+  }
+
+  #[test]
+  fn update_migrates_v1_keeping_hashes() {
+    let (temp_dir, file_path) = setup();
+    let v1_content = Lockfile::new(file_path.clone(), false).unwrap().content;
+
+    let mut lockfile = Lockfile::new_update(file_path.clone()).unwrap();
+    assert!(lockfile.changes().is_empty());
+    lockfile.write().expect("unable to write");
+
+    let lockfile = Lockfile::new(file_path, false).unwrap();
+    assert!(!lockfile.is_v1());
+    assert_eq!(lockfile.content.remote, v1_content.remote);
+
+    teardown(temp_dir);
+  }
+
+  #[test]
+  fn update_lockfile_merges_and_reports_changes() {
+    let (temp_dir, file_path) = setup();
+
+    let mut lockfile = Lockfile::new_update(file_path.clone()).unwrap();
+    assert!(lockfile.changes().is_empty());
+    assert!(lockfile.check_or_insert(
+      "https://deno.land/std@0.71.0/io/util.ts",
+      "more source code here",
+    ));
+    assert!(lockfile.check_or_insert(
+      "https://deno.land/std@0.71.0/async/delay.ts",
+      "this source is really exciting",
+    ));
+    assert!(lockfile.check_or_insert_redirect(
+      "https://deno.land/std/async/delay.ts",
+      "https://deno.land/std@0.71.0/async/delay.ts"
+    ));
+    assert_eq!(
+      lockfile.changes(),
+      vec![
+        LockfileChange::Changed(
+          "https://deno.land/std@0.71.0/async/delay.ts".to_string()
+        ),
+        LockfileChange::Added(
+          "https://deno.land/std@0.71.0/io/util.ts".to_string()
+        ),
+        LockfileChange::Added(
+          "redirect https://deno.land/std/async/delay.ts -> https://deno.land/std@0.71.0/async/delay.ts".to_string()
+        ),
+      ]
+    );
+    lockfile.write().expect("unable to write");
+    assert!(lockfile.changes().is_empty());
+
+    let lockfile = Lockfile::new(file_path, false).unwrap();
+    assert!(!lockfile.is_v1());
+    let keys: Vec<String> = lockfile.content.remote.keys().cloned().collect();
+    assert_eq!(
+      keys,
+      vec![
+        "https://deno.land/std@0.71.0/async/delay.ts",
+        "https://deno.land/std@0.71.0/io/util.ts",
+        "https://deno.land/std@0.71.0/textproto/mod.ts",
+      ]
+    );
+
+    teardown(temp_dir);
+  }
+
+  #[test]
+  fn update_lockfile_and_prune() {
+    let (temp_dir, file_path) = setup();
+
+    let mut lockfile = Lockfile::new_update(file_path.clone()).unwrap();
+    lockfile.check_or_insert(
+      "https://deno.land/std@0.71.0/io/util.ts",
+      "more source code here",
+    );
+    lockfile.prune();
+    assert_eq!(
+      lockfile.changes(),
+      vec![
+        LockfileChange::Added(
+          "https://deno.land/std@0.71.0/io/util.ts".to_string()
+        ),
+        LockfileChange::Removed(
+          "https://deno.land/std@0.71.0/async/delay.ts".to_string()
+        ),
+        LockfileChange::Removed(
+          "https://deno.land/std@0.71.0/textproto/mod.ts".to_string()
+        ),
+      ]
+    );
+    lockfile.write().expect("unable to write");
+
+    let lockfile = Lockfile::new(file_path, false).unwrap();
+    let keys: Vec<String> = lockfile.content.remote.keys().cloned().collect();
+    assert_eq!(keys, vec!["https://deno.land/std@0.71.0/io/util.ts"]);
+
+    teardown(temp_dir);
+  }
+
+  #[test]
+  fn update_nonexistent_lockfile() {
+    let temp_dir = TempDir::new().expect("could not create temp dir");
+    let file_path = temp_dir.path().join("new_lockfile.json");
+    let mut lockfile = Lockfile::new_update(file_path.clone()).unwrap();
+    lockfile.check_or_insert(
+      "https://deno.land/std@0.71.0/io/util.ts",
+      "more source code here",
+    );
+    lockfile.write().expect("unable to write");
+    assert!(Lockfile::new(file_path, false).is_ok());
+  }
+
+  #[test]
+  fn unsupported_lockfile_version() {
+    let temp_dir = TempDir::new().expect("could not create temp dir");
+    let file_path = temp_dir.path().join("lockfile_v3.json");
+    std::fs::write(&file_path, r#"{ "version": "3" }"#).unwrap();
+    assert!(Lockfile::new(file_path, false).is_err());
+  }
 }
@@ -8,6 +8,7 @@ extern crate lazy_static;
 extern crate log;
 
 mod ast;
+mod auth_tokens;
 mod checksum;
 mod colors;
 mod deno_dir;
@@ -41,11 +42,13 @@ mod tokio_util;
 mod tools;
 mod tsc;
 mod tsc_config;
+mod url_rewrites;
 mod version;
 
 use crate::file_fetcher::File;
 use crate::file_fetcher::FileFetcher;
 use crate::file_watcher::ModuleResolutionResult;
+use crate::flags::CoverageReporterKind;
 use crate::flags::DenoSubcommand;
 use crate::flags::Flags;
 use crate::fmt_errors::PrettyJsError;
@@ -76,6 +79,7 @@ use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use log::Level;
 use log::LevelFilter;
+use std::collections::HashSet;
 use std::env;
 use std::io::Read;
 use std::io::Write;
@@ -83,6 +87,8 @@ use std::iter::once;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -126,6 +132,7 @@ fn create_web_worker_callback(
       ts_version: version::TYPESCRIPT.to_string(),
       no_color: !colors::use_color(),
       get_error_class_fn: Some(&crate::errors::get_error_class_name),
+      blob_url_store: program_state.blob_url_store.clone(),
     };
 
     let mut worker = WebWorker::from_options(
@@ -202,6 +209,7 @@ pub fn create_main_worker(
     ts_version: version::TYPESCRIPT.to_string(),
     no_color: !colors::use_color(),
     get_error_class_fn: Some(&crate::errors::get_error_class_name),
+    blob_url_store: program_state.blob_url_store.clone(),
   };
 
   let mut worker = MainWorker::from_options(main_module, permissions, &options);
@@ -295,6 +303,11 @@ async fn compile_command(
   flags: Flags,
   source_file: String,
   output: Option<PathBuf>,
+  workers: Vec<String>,
+  include: Vec<PathBuf>,
+  target: Option<String>,
+  manifest: bool,
+  runtime_unstable: bool,
 ) -> Result<(), AnyError> {
   if !flags.unstable {
     exit_unstable("compile");
@@ -311,27 +324,74 @@ async fn compile_command(
     "An executable name was not provided. One could not be inferred from the URL. Aborting.",
   ))?;
 
-  let module_graph = create_module_graph_and_maybe_check(
-    module_specifier.clone(),
-    program_state.clone(),
-    debug,
-  )
-  .await?;
+  let target = target.unwrap_or_else(|| env!("TARGET").to_string());
+  // read the runtime binary first, to not type check for a missing target
+  let original_bin =
+    standalone::get_base_binary(&program_state.dir, &target).await?;
 
-  info!(
-    "{} {}",
-    colors::green("Bundle"),
-    module_specifier.to_string()
-  );
-  let bundle_str = bundle_module_graph(module_graph, flags, debug)?;
+  let mut roots = vec![(module_specifier.clone(), window_type_lib(&flags))];
+  for worker in workers {
+    let worker_specifier = ModuleSpecifier::resolve_url_or_path(&worker)?;
+    roots.push((worker_specifier, worker_type_lib(&flags)));
+  }
+
+  let mut modules = standalone::EmbeddedModules::default();
+  let mut maybe_manifest = if manifest {
+    let maybe_import_map = match flags.import_map_path.as_ref() {
+      Some(path) => Some(ModuleSpecifier::resolve_url_or_path(path)?),
+      None => None,
+    };
+    Some(standalone::Manifest::new(maybe_import_map))
+  } else {
+    None
+  };
+  for (root, lib) in roots {
+    let mut module_graph = create_module_graph_and_maybe_check(
+      root,
+      program_state.clone(),
+      lib,
+      debug,
+    )
+    .await?;
+    let result_info =
+      module_graph.transpile(module_graph::TranspileOptions {
+        debug,
+        maybe_config_path: flags.config_path.clone(),
+        reload: flags.reload,
+      })?;
+    debug!("{}", result_info.stats);
+    // the ignored options have already been reported when type checking
+    if flags.no_check {
+      if let Some(ignored_options) = result_info.maybe_ignored_options {
+        eprintln!("{}", ignored_options);
+      }
+    }
+    if let Some(manifest) = maybe_manifest.as_mut() {
+      manifest.add_graph(&module_graph);
+    }
+    modules.add_graph(&module_graph, result_info.loadable_modules);
+  }
+  let cwd = std::env::current_dir()?;
+  for path in include {
+    modules.include(&cwd, &path)?;
+  }
 
   info!(
     "{} {}",
     colors::green("Compile"),
     module_specifier.to_string()
   );
-  create_standalone_binary(bundle_str.as_bytes().to_vec(), output.clone())
-    .await?;
+  let mut metadata =
+    standalone::Metadata::new(&module_specifier, &flags, runtime_unstable);
+  metadata.manifest = maybe_manifest;
+  create_standalone_binary(
+    original_bin,
+    modules,
+    metadata,
+    output.clone(),
+    &target,
+  )
+  .await?;
 
   info!("{} {}", colors::green("Emit"), output.display());
 
@@ -342,6 +402,7 @@ async fn info_command(
   flags: Flags,
   maybe_specifier: Option<String>,
   json: bool,
+  maybe_why: Option<String>,
 ) -> Result<(), AnyError> {
   if json && !flags.unstable {
     exit_unstable("--json");
@@ -362,8 +423,21 @@ async fn info_command(
     );
     builder.add(&specifier, false).await?;
     let graph = builder.get_graph();
-    let info = graph.info()?;
 
+    if let Some(why) = maybe_why {
+      // the target is normalized like a specifier, but it is matched as a
+      // prefix of the specifiers of the modules
+      let target = ModuleSpecifier::resolve_url_or_path(&why)?;
+      let info = graph.import_chains(target.as_str())?;
+      if json {
+        write_json_to_stdout(&json!(info))?;
+      } else {
+        write_to_stdout_ignore_sigpipe(info.to_string().as_bytes())?;
+      }
+      return Ok(());
+    }
+
+    let info = graph.info()?;
     if json {
       write_json_to_stdout(&json!(info))?;
     } else {
@@ -430,9 +504,71 @@ async fn cache_command(
     module_graph::TypeLib::DenoWindow
   };
   let program_state = ProgramState::new(flags)?;
+  let specifiers = files
+    .iter()
+    .map(|file| ModuleSpecifier::resolve_url_or_path(file))
+    .collect::<Result<Vec<_>, _>>()?;
 
-  for file in files {
-    let specifier = ModuleSpecifier::resolve_url_or_path(&file)?;
+  // all the modules are fetched before they are checked, so every module which
+  // fails to load is reported, instead of only the first one
+  let handler = Arc::new(Mutex::new(FetchHandler::new(
+    &program_state,
+    Permissions::allow_all(),
+  )?));
+  let mut builder = module_graph::GraphBuilder::new(
+    handler,
+    program_state.maybe_import_map.clone(),
+    None,
+  );
+  builder.set_keep_going(true);
+  let show_progress = atty::is(atty::Stream::Stderr)
+    && program_state.flags.log_level != Some(Level::Error);
+  if show_progress {
+    PROGRESS_SHOWN.store(true, Ordering::Relaxed);
+    builder.set_progress_callback(Box::new(|progress| {
+      // the cursor is moved back to the start of the line, so the next log
+      // message overwrites the progress, after the logger cleared the line
+      eprint!(
+        "\r\x1b[K{} {}/{} modules ({}){}\r",
+        colors::green("Progress"),
+        progress.completed,
+        progress.total,
+        info::human_size(progress.size as f64),
+        if progress.failed > 0 {
+          format!(", {} failed", progress.failed)
+        } else {
+          "".to_string()
+        }
+      );
+    }));
+  }
+  for specifier in specifiers.iter() {
+    builder.add(specifier, false).await?;
+  }
+  if show_progress {
+    PROGRESS_SHOWN.store(false, Ordering::Relaxed);
+    eprint!("\r\x1b[K");
+  }
+  let errors = builder.get_graph().get_errors();
+  if !errors.is_empty() {
+    for failed_module in errors.iter() {
+      eprintln!("{}: {}", colors::red_bold("error"), failed_module.error);
+      for importer in failed_module.chain.iter().rev().skip(1) {
+        eprintln!("    imported from {}", importer);
+      }
+    }
+    return Err(generic_error(format!(
+      "{} {} failed to load.",
+      errors.len(),
+      if errors.len() == 1 {
+        "module"
+      } else {
+        "modules"
+      }
+    )));
+  }
+
+  for specifier in specifiers {
     program_state
       .prepare_module_load(
         specifier,
@@ -444,9 +580,167 @@ async fn cache_command(
       .await?;
   }
 
+  // entries which are not used by any of the modules are only known once all
+  // of them have been loaded
+  if program_state.flags.lock_prune {
+    if let Some(lockfile) = program_state.lockfile.as_ref() {
+      let mut lockfile = lockfile.lock().unwrap();
+      lockfile.prune();
+      lockfile.write()?;
+    }
+  }
+
   Ok(())
 }
 
+async fn gc_command(
+  flags: Flags,
+  files: Vec<String>,
+  list: bool,
+  json: bool,
+  older_than: Option<u64>,
+  dry_run: bool,
+) -> Result<(), AnyError> {
+  let program_state = ProgramState::new(flags)?;
+  let deps_location = program_state.file_fetcher.get_http_cache_location();
+  let gen_cache = &program_state.dir.gen_cache;
+
+  if list {
+    let entries = tools::gc::get_entries(&deps_location, gen_cache);
+    if json {
+      write_json_to_stdout(&json!(entries))?;
+    } else {
+      write_to_stdout_ignore_sigpipe(
+        tools::gc::format_entries(&entries).as_bytes(),
+      )?;
+    }
+    return Ok(());
+  }
+
+  let maybe_reachable = if files.is_empty() {
+    None
+  } else {
+    let handler = Arc::new(Mutex::new(FetchHandler::new(
+      &program_state,
+      // every module of the graph is kept, including the dynamically imported
+      // ones, so we allow access to all of them.
+      Permissions::allow_all(),
+    )?));
+    let mut builder = module_graph::GraphBuilder::new(
+      handler,
+      program_state.maybe_import_map.clone(),
+      None,
+    );
+    for file in files {
+      let specifier = ModuleSpecifier::resolve_url_or_path(&file)?;
+      builder.add(&specifier, false).await?;
+    }
+    let graph = builder.get_graph();
+    let mut reachable: HashSet<String> =
+      graph.get_modules().iter().map(|s| s.to_string()).collect();
+    for (from, to) in graph.get_redirects() {
+      reachable.insert(from.to_string());
+      reachable.insert(to.to_string());
+    }
+    Some(reachable)
+  };
+  let older_than =
+    older_than.map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60));
+
+  tools::gc::collect_garbage(
+    &deps_location,
+    gen_cache,
+    maybe_reachable,
+    older_than,
+    dry_run,
+  )
+}
+
+async fn vendor_command(
+  flags: Flags,
+  files: Vec<String>,
+  output: Option<PathBuf>,
+  force: bool,
+) -> Result<(), AnyError> {
+  let output_dir = output.unwrap_or_else(|| PathBuf::from("vendor"));
+  let output_dir = fs_util::resolve_from_cwd(&output_dir)?;
+  tools::vendor::ensure_output_dir(&output_dir, force)?;
+
+  let program_state = ProgramState::new(flags.clone())?;
+  let handler = Arc::new(Mutex::new(FetchHandler::new(
+    &program_state,
+    // vendoring copies every module of the graph, including the dynamically
+    // imported ones, so we allow access to all of them.
+    Permissions::allow_all(),
+  )?));
+  let mut builder = module_graph::GraphBuilder::new(
+    handler,
+    program_state.maybe_import_map.clone(),
+    program_state.lockfile.clone(),
+  );
+  for file in files {
+    let specifier = ModuleSpecifier::resolve_url_or_path(&file)?;
+    builder.add(&specifier, false).await?;
+  }
+  let graph = builder.get_graph();
+  if let Some(lockfile) = program_state.lockfile.as_ref() {
+    lockfile.lock().unwrap().write()?;
+  }
+
+  let maybe_import_map = match flags.import_map_path.as_ref() {
+    Some(path) => {
+      let base = ModuleSpecifier::resolve_url_or_path(path)?;
+      let contents = std::fs::read_to_string(fs_util::resolve_from_cwd(
+        &PathBuf::from(path),
+      )?)?;
+      Some((base.as_url().clone(), contents))
+    }
+    None => None,
+  };
+  let count = tools::vendor::vendor(&graph, &output_dir, maybe_import_map)?;
+
+  info!(
+    "{} {} modules to {}",
+    colors::green("Vendored"),
+    count,
+    output_dir.display()
+  );
+  info!(
+    "To use the vendored modules, run with --unstable --import-map={}",
+    output_dir.join(tools::vendor::IMPORT_MAP_NAME).display()
+  );
+  Ok(())
+}
+
+async fn outdated_command(
+  flags: Flags,
+  files: Vec<PathBuf>,
+  update: bool,
+  registry_api: Option<String>,
+) -> Result<(), AnyError> {
+  let maybe_import_map = match flags.import_map_path.as_ref() {
+    Some(path) => {
+      if !flags.unstable {
+        exit_unstable("--import-map");
+      }
+      Some(fs_util::resolve_from_cwd(&PathBuf::from(path))?)
+    }
+    None => None,
+  };
+  let registry_api = registry_api
+    .unwrap_or_else(|| tools::outdated::DEFAULT_REGISTRY_API.to_string());
+  let ca_file = flags.ca_file.or_else(|| env::var("DENO_CERT").ok());
+
+  tools::outdated::outdated(
+    files,
+    maybe_import_map,
+    registry_api,
+    ca_file.as_deref(),
+    update,
+  )
+  .await
+}
+
 async fn eval_command(
   flags: Flags,
   code: String,
@@ -495,6 +789,7 @@ async fn eval_command(
 async fn create_module_graph_and_maybe_check(
   module_specifier: ModuleSpecifier,
   program_state: Arc<ProgramState>,
+  lib: module_graph::TypeLib,
   debug: bool,
 ) -> Result<module_graph::Graph, AnyError> {
   let handler = Arc::new(Mutex::new(FetchHandler::new(
@@ -510,14 +805,13 @@ async fn create_module_graph_and_maybe_check(
   );
   builder.add(&module_specifier, false).await?;
   let module_graph = builder.get_graph();
+  // every module has been checked against the lock file when the graph was
+  // built, so the lock file can be written for `--lock-write`
+  if let Some(lockfile) = program_state.lockfile.as_ref() {
+    lockfile.lock().unwrap().write()?;
+  }
 
   if !program_state.flags.no_check {
-    // TODO(@kitsonk) support bundling for workers
-    let lib = if program_state.flags.unstable {
-      module_graph::TypeLib::UnstableDenoWindow
-    } else {
-      module_graph::TypeLib::DenoWindow
-    };
     let result_info =
       module_graph.clone().check(module_graph::CheckOptions {
         debug,
@@ -539,6 +833,22 @@ async fn create_module_graph_and_maybe_check(
   Ok(module_graph)
 }
 
+fn window_type_lib(flags: &Flags) -> module_graph::TypeLib {
+  if flags.unstable {
+    module_graph::TypeLib::UnstableDenoWindow
+  } else {
+    module_graph::TypeLib::DenoWindow
+  }
+}
+
+fn worker_type_lib(flags: &Flags) -> module_graph::TypeLib {
+  if flags.unstable {
+    module_graph::TypeLib::UnstableDenoWorker
+  } else {
+    module_graph::TypeLib::DenoWorker
+  }
+}
+
 fn bundle_module_graph(
   module_graph: module_graph::Graph,
   flags: Flags,
@@ -586,6 +896,7 @@ async fn bundle_command(
       let module_graph = create_module_graph_and_maybe_check(
         module_specifier,
         program_state.clone(),
+        window_type_lib(&flags),
         debug,
       )
       .await?;
@@ -958,6 +1269,7 @@ async fn run_command(flags: Flags, script: String) -> Result<(), AnyError> {
   Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn test_command(
   flags: Flags,
   include: Option<Vec<String>>,
@@ -966,6 +1278,8 @@ async fn test_command(
   quiet: bool,
   allow_none: bool,
   filter: Option<String>,
+  coverage_reporter: CoverageReporterKind,
+  coverage_output: Option<PathBuf>,
 ) -> Result<(), AnyError> {
   let program_state = ProgramState::new(flags.clone())?;
   let permissions = Permissions::from_options(&flags.clone().into());
@@ -1055,9 +1369,12 @@ async fn test_command(
       let main_module_url = main_module.as_url().to_owned();
       exclude.push(main_module_url);
       tools::coverage::report_coverages(
+        &program_state,
         &coverage_collector.dir,
         quiet,
         exclude,
+        coverage_reporter,
+        coverage_output,
       )?;
     }
   }
@@ -1065,6 +1382,29 @@ async fn test_command(
   Ok(())
 }
 
+async fn coverage_command(
+  flags: Flags,
+  files: Vec<PathBuf>,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  reporter: CoverageReporterKind,
+  output: Option<PathBuf>,
+) -> Result<(), AnyError> {
+  if !flags.unstable {
+    exit_unstable("coverage");
+  }
+
+  let program_state = ProgramState::new(flags)?;
+  tools::coverage::cover_dirs(
+    &program_state,
+    files,
+    include,
+    exclude,
+    reporter,
+    output,
+  )
+}
+
 fn init_v8_flags(v8_flags: &[String]) {
   let v8_flags_includes_help = v8_flags
     .iter()
@@ -1088,6 +1428,10 @@ fn init_v8_flags(v8_flags: &[String]) {
   }
 }
 
+/// Set while a progress line is shown on stderr, which the logger clears before
+/// writing a message over it.
+static PROGRESS_SHOWN: AtomicBool = AtomicBool::new(false);
+
 fn init_logger(maybe_level: Option<Level>) {
   let log_level = match maybe_level {
     Some(level) => level,
@@ -1105,6 +1449,9 @@ fn init_logger(maybe_level: Option<Level>) {
       target.push(':');
       target.push_str(&line_no.to_string());
     }
+    if PROGRESS_SHOWN.load(Ordering::Relaxed) {
+      write!(buf, "\x1b[K")?;
+    }
     if record.level() <= Level::Info {
       // Print ERROR, WARN, INFO logs as they are
       writeln!(buf, "{}", record.args())
@@ -1147,14 +1494,46 @@ fn get_subcommand(
     DenoSubcommand::Compile {
       source_file,
       output,
-    } => compile_command(flags, source_file, output).boxed_local(),
+      workers,
+      include,
+      target,
+      manifest,
+      runtime_unstable,
+    } => compile_command(
+      flags,
+      source_file,
+      output,
+      workers,
+      include,
+      target,
+      manifest,
+      runtime_unstable,
+    )
+    .boxed_local(),
+    DenoSubcommand::Coverage {
+      files,
+      include,
+      exclude,
+      reporter,
+      output,
+    } => coverage_command(flags, files, include, exclude, reporter, output)
+      .boxed_local(),
     DenoSubcommand::Fmt {
       check,
       files,
       ignore,
     } => format_command(flags, files, ignore, check).boxed_local(),
-    DenoSubcommand::Info { file, json } => {
-      info_command(flags, file, json).boxed_local()
+    DenoSubcommand::Gc {
+      files,
+      list,
+      json,
+      older_than,
+      dry_run,
+    } => {
+      gc_command(flags, files, list, json, older_than, dry_run).boxed_local()
+    }
+    DenoSubcommand::Info { file, json, why } => {
+      info_command(flags, file, json, why).boxed_local()
     }
     DenoSubcommand::Install {
       module_url,
@@ -1172,6 +1551,11 @@ fn get_subcommand(
       ignore,
       json,
     } => lint_command(flags, files, rules, ignore, json).boxed_local(),
+    DenoSubcommand::Outdated {
+      files,
+      update,
+      registry_api,
+    } => outdated_command(flags, files, update, registry_api).boxed_local(),
     DenoSubcommand::Repl => run_repl(flags).boxed_local(),
     DenoSubcommand::Run { script } => run_command(flags, script).boxed_local(),
     DenoSubcommand::Test {
@@ -1181,10 +1565,25 @@ fn get_subcommand(
       include,
       allow_none,
       filter,
-    } => {
-      test_command(flags, include, no_run, fail_fast, quiet, allow_none, filter)
-        .boxed_local()
-    }
+      coverage_reporter,
+      coverage_output,
+    } => test_command(
+      flags,
+      include,
+      no_run,
+      fail_fast,
+      quiet,
+      allow_none,
+      filter,
+      coverage_reporter,
+      coverage_output,
+    )
+    .boxed_local(),
+    DenoSubcommand::Vendor {
+      files,
+      output,
+      force,
+    } => vendor_command(flags, files, output, force).boxed_local(),
     DenoSubcommand::Completions { buf } => {
       if let Err(e) = write_to_stdout_ignore_sigpipe(&buf) {
         eprintln!("{}", e);
@@ -9,6 +9,9 @@ use crate::ast::ParsedModule;
 use crate::colors;
 use crate::diagnostics::Diagnostics;
 use crate::import_map::ImportMap;
+use crate::import_map::ImportMapMatch;
+use crate::info::DependencyInfo;
+use crate::info::ImportChainsInfo;
 use crate::info::ModuleGraphInfo;
 use crate::info::ModuleInfo;
 use crate::info::ModuleInfoMap;
@@ -44,6 +47,7 @@ use deno_core::ModuleSource;
 use deno_core::ModuleSpecifier;
 use regex::Regex;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
 use std::fmt;
@@ -81,9 +85,14 @@ pub enum GraphError {
   InvalidDowngrade(ModuleSpecifier, Location),
   /// A remote module is trying to import a local module.
   InvalidLocalImport(ModuleSpecifier, Location),
+  /// A redirect does not match the redirect recorded in the lockfile.
+  InvalidRedirect(ModuleSpecifier, ModuleSpecifier, PathBuf),
   /// The source code is invalid, as it does not match the expected hash in the
   /// lockfile.
   InvalidSource(ModuleSpecifier, PathBuf),
+  /// The type definitions of a module do not match the type definitions
+  /// recorded in the lockfile.
+  InvalidTypes(ModuleSpecifier, ModuleSpecifier, PathBuf),
   /// An unexpected dependency was requested for a module.
   MissingDependency(ModuleSpecifier, String),
   /// An unexpected specifier was requested.
@@ -99,7 +108,9 @@ impl fmt::Display for GraphError {
     match self {
       GraphError::InvalidDowngrade(ref specifier, ref location) => write!(f, "Modules imported via https are not allowed to import http modules.\n  Importing: {}\n    at {}", specifier, location),
       GraphError::InvalidLocalImport(ref specifier, ref location) => write!(f, "Remote modules are not allowed to import local modules.  Consider using a dynamic import instead.\n  Importing: {}\n    at {}", specifier, location),
+      GraphError::InvalidRedirect(ref from, ref to, ref lockfile) => write!(f, "The redirect is invalid, as it does not match the redirect in the lock file.\n  Specifier: {}\n  Redirected to: {}\n  Lock file: {}", from, to, lockfile.to_str().unwrap()),
       GraphError::InvalidSource(ref specifier, ref lockfile) => write!(f, "The source code is invalid, as it does not match the expected hash in the lock file.\n  Specifier: {}\n  Lock file: {}", specifier, lockfile.to_str().unwrap()),
+      GraphError::InvalidTypes(ref specifier, ref types, ref lockfile) => write!(f, "The type definitions are invalid, as they do not match the type definitions in the lock file.\n  Specifier: {}\n  Types: {}\n  Lock file: {}", specifier, types, lockfile.to_str().unwrap()),
       GraphError::MissingDependency(ref referrer, specifier) => write!(
         f,
         "The graph is missing a dependency.\n  Specifier: {} from {}",
@@ -325,6 +336,25 @@ impl Module {
     }
   }
 
+  /// Return the type definition files of the module and of its dependencies,
+  /// as pairs of the code specifier and the types specifier.
+  fn get_types(&self) -> Vec<(ModuleSpecifier, ModuleSpecifier)> {
+    let mut types = Vec::new();
+    if let Some((_, specifier)) = self.maybe_types.as_ref() {
+      types.push((self.specifier.clone(), specifier.clone()));
+    }
+    for dep in self.dependencies.values() {
+      if let (Some(code), Some(type_)) =
+        (dep.maybe_code.as_ref(), dep.maybe_type.as_ref())
+      {
+        if code != type_ {
+          types.push((code.clone(), type_.clone()));
+        }
+      }
+    }
+    types
+  }
+
   /// Parse a module, populating the structure with data retrieved from the
   /// source of the module.
   pub fn parse(&mut self) -> Result<ParsedModule, AnyError> {
@@ -468,9 +498,12 @@ impl Module {
     }
 
     // Disallow a remote URL from trying to import a local URL, unless it is a
-    // remapped import via the import map
-    if (referrer_scheme == "https" || referrer_scheme == "http")
-      && !(specifier_scheme == "https" || specifier_scheme == "http")
+    // remapped import via the import map. Data and blob URLs are treated like
+    // remote modules, as their source can be generated from untrusted input.
+    let is_remote =
+      |scheme: &str| matches!(scheme, "https" | "http" | "data" | "blob");
+    if is_remote(referrer_scheme)
+      && !is_remote(specifier_scheme)
       && !remapped_import
     {
       return Err(
@@ -481,6 +514,16 @@ impl Module {
     Ok(specifier)
   }
 
+  /// Return the entry of the import map which a dependency of the module was
+  /// resolved with, if any.
+  fn get_import_map_match(&self, specifier: &str) -> Option<ImportMapMatch> {
+    let import_map = self.maybe_import_map.as_ref()?;
+    import_map
+      .lock()
+      .unwrap()
+      .resolve_match(specifier, self.specifier.as_str())
+  }
+
   pub fn set_emit(&mut self, code: String, maybe_map: Option<String>) {
     self.maybe_emit = Some(Emit::Cli((code, maybe_map)));
   }
@@ -656,6 +699,20 @@ pub struct TranspileOptions {
   pub reload: bool,
 }
 
+/// The maximum number of import chains which are collected for `info --why`,
+/// as the number of chains can grow exponentially with the size of the graph.
+const MAX_IMPORT_CHAINS: usize = 100;
+
+/// A module of the graph which failed to be fetched or analyzed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FailedModule {
+  pub specifier: ModuleSpecifier,
+  pub error: String,
+  /// The shortest chain of imports which leads to the module, from a root
+  /// module of the graph to the module itself.
+  pub chain: Vec<ModuleSpecifier>,
+}
+
 #[derive(Debug, Clone)]
 enum ModuleSlot {
   /// The module fetch resulted in a non-recoverable error.
@@ -1240,6 +1297,24 @@ impl Graph {
       .iter()
       .filter_map(|(specifier, module_slot)| {
         if let ModuleSlot::Module(module) = module_slot {
+          let mut dependencies: Vec<DependencyInfo> = module
+            .dependencies
+            .iter()
+            .map(|(specifier, dep)| DependencyInfo {
+              specifier: specifier.clone(),
+              is_dynamic: dep.is_dynamic,
+              code: dep.maybe_code.clone(),
+              maybe_type: dep.maybe_type.clone(),
+              import_map: module.get_import_map_match(specifier),
+              redirects: dep
+                .maybe_code
+                .as_ref()
+                .or_else(|| dep.maybe_type.as_ref())
+                .map(|s| self.get_redirect_chain(s))
+                .unwrap_or_default(),
+            })
+            .collect();
+          dependencies.sort_by(|a, b| a.specifier.cmp(&b.specifier));
           let mut deps = BTreeSet::new();
           for (_, dep) in module.dependencies.iter() {
             if let Some(code_dep) = &dep.maybe_code {
@@ -1253,6 +1328,7 @@ impl Graph {
             deps.insert(types_dep.clone());
           }
           let item = ModuleInfoMapItem {
+            dependencies,
             deps: deps.into_iter().collect(),
             size: module.size(),
           };
@@ -1321,6 +1397,110 @@ impl Graph {
     self.modules.get_mut(s)
   }
 
+  /// Return the redirects which were followed when fetching the modules of
+  /// the graph.
+  pub fn get_redirects(&self) -> HashMap<ModuleSpecifier, ModuleSpecifier> {
+    self.redirects.clone()
+  }
+
+  /// Return the chain of redirects which were followed from a specifier, in
+  /// the order they were followed, where the last one is the specifier of the
+  /// module in the graph.
+  fn get_redirect_chain(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Vec<ModuleSpecifier> {
+    let mut chain = Vec::new();
+    let mut s = specifier;
+    while let Some(redirect) = self.redirects.get(s) {
+      chain.push(redirect.clone());
+      s = redirect;
+    }
+    chain
+  }
+
+  /// Return the modules of the graph which failed to be fetched or analyzed,
+  /// sorted by specifier, with the chain of imports which leads to each of
+  /// them.
+  pub fn get_errors(&self) -> Vec<FailedModule> {
+    let mut errors = Vec::new();
+    let mut importers: HashMap<ModuleSpecifier, ModuleSpecifier> =
+      HashMap::new();
+    let mut seen: HashSet<ModuleSpecifier> =
+      self.roots.iter().cloned().collect();
+    let mut queue: VecDeque<ModuleSpecifier> =
+      self.roots.iter().cloned().collect();
+    while let Some(specifier) = queue.pop_front() {
+      match self.get_module(&specifier) {
+        ModuleSlot::Err(err) => {
+          let mut chain = vec![specifier.clone()];
+          while let Some(importer) = importers.get(chain.last().unwrap()) {
+            chain.push(importer.clone());
+          }
+          chain.reverse();
+          errors.push(FailedModule {
+            specifier,
+            error: err.to_string(),
+            chain,
+          });
+        }
+        ModuleSlot::Module(module) => {
+          let deps = module
+            .dependencies
+            .values()
+            .flat_map(|dep| dep.maybe_code.iter().chain(dep.maybe_type.iter()))
+            .chain(module.maybe_types.iter().map(|(_, types)| types));
+          for dep in deps {
+            if seen.insert(dep.clone()) {
+              importers.insert(dep.clone(), specifier.clone());
+              queue.push_back(dep.clone());
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+    errors.sort_by(|a, b| a.specifier.as_str().cmp(b.specifier.as_str()));
+    errors
+  }
+
+  /// Return the resolved code dependencies of a module, keyed by the
+  /// specifier as it is written in the source of the module.
+  pub fn get_dependencies(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<HashMap<String, ModuleSpecifier>> {
+    if let ModuleSlot::Module(module) = self.get_module(specifier) {
+      Some(
+        module
+          .dependencies
+          .iter()
+          .filter_map(|(k, dep)| {
+            dep.maybe_code.clone().map(|code| (k.clone(), code))
+          })
+          .collect(),
+      )
+    } else {
+      None
+    }
+  }
+
+  /// Return the specifier of the type definitions of a module which were
+  /// given with a `X-TypeScript-Types` header, if any.
+  pub fn get_types_header(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<ModuleSpecifier> {
+    if let ModuleSlot::Module(module) = self.get_module(specifier) {
+      module
+        .maybe_types
+        .as_ref()
+        .map(|(_, specifier)| specifier.clone())
+    } else {
+      None
+    }
+  }
+
   /// Consume graph and return list of all module specifiers contained in the
   /// graph.
   pub fn get_modules(&self) -> Vec<ModuleSpecifier> {
@@ -1451,6 +1631,117 @@ impl Graph {
     })
   }
 
+  /// Return the import chains from the root module of the graph to the modules
+  /// which match a target, where the specifier of a matching module starts
+  /// with the target, so the target can be a specifier or a URL prefix. A chain
+  /// ends at the first module which matches the target, and a module is only
+  /// part of a chain once.
+  pub fn import_chains(
+    &self,
+    target: &str,
+  ) -> Result<ImportChainsInfo, AnyError> {
+    if self.roots.is_empty() || self.roots.len() > 1 {
+      return Err(GraphError::NotSupported(format!("Info is only supported when there is a single root module in the graph.  Found: {}", self.roots.len())).into());
+    }
+
+    let module = self.roots[0].clone();
+    let root = self.resolve_specifier(&module).clone();
+
+    // only the modules which can lead to the target are followed, so the
+    // branches of the graph which do not import it are not walked
+    let mut importers: HashMap<ModuleSpecifier, Vec<ModuleSpecifier>> =
+      HashMap::new();
+    for (specifier, module_slot) in self.modules.iter() {
+      if let ModuleSlot::Module(module) = module_slot {
+        for dep in self.get_imported_modules(module) {
+          importers.entry(dep).or_default().push(specifier.clone());
+        }
+      }
+    }
+    let mut leads_to_target = HashSet::new();
+    let mut queue: VecDeque<ModuleSpecifier> = self
+      .modules
+      .keys()
+      .filter(|s| s.as_str().starts_with(target))
+      .cloned()
+      .collect();
+    while let Some(specifier) = queue.pop_front() {
+      if leads_to_target.insert(specifier.clone()) {
+        if let Some(importers) = importers.get(&specifier) {
+          queue.extend(importers.iter().cloned());
+        }
+      }
+    }
+
+    let mut chains = Vec::new();
+    let truncated = if leads_to_target.contains(&root) {
+      !self.collect_import_chains(
+        &mut vec![root],
+        target,
+        &leads_to_target,
+        &mut chains,
+      )
+    } else {
+      false
+    };
+
+    Ok(ImportChainsInfo {
+      chains,
+      module,
+      target: target.to_string(),
+      truncated,
+    })
+  }
+
+  /// Walk the imports of the last module of a chain depth first, adding the
+  /// chains which reach the target. Returns `false` when the maximum number of
+  /// chains was reached before all of the chains were collected.
+  fn collect_import_chains(
+    &self,
+    chain: &mut Vec<ModuleSpecifier>,
+    target: &str,
+    leads_to_target: &HashSet<ModuleSpecifier>,
+    chains: &mut Vec<Vec<ModuleSpecifier>>,
+  ) -> bool {
+    let specifier = chain.last().unwrap().clone();
+    if specifier.as_str().starts_with(target) {
+      if chains.len() == MAX_IMPORT_CHAINS {
+        return false;
+      }
+      chains.push(chain.clone());
+      return true;
+    }
+    let module = match self.get_module(&specifier) {
+      ModuleSlot::Module(module) => module,
+      _ => return true,
+    };
+    for dep in self.get_imported_modules(module) {
+      if leads_to_target.contains(&dep) && !chain.contains(&dep) {
+        chain.push(dep);
+        let complete =
+          self.collect_import_chains(chain, target, leads_to_target, chains);
+        chain.pop();
+        if !complete {
+          return false;
+        }
+      }
+    }
+
+    true
+  }
+
+  /// Return the modules which a module imports, for its runtime code and for
+  /// its types, after following any redirects.
+  fn get_imported_modules(&self, module: &Module) -> BTreeSet<ModuleSpecifier> {
+    module
+      .dependencies
+      .values()
+      .flat_map(|dep| dep.maybe_code.iter().chain(dep.maybe_type.iter()))
+      .chain(module.maybe_types.iter().map(|(_, types)| types))
+      .map(|s| self.resolve_specifier(s).clone())
+      .collect()
+  }
+
   /// Determines if all of the modules in the graph that require an emit have
   /// a valid emit.  Returns `true` if all the modules have a valid emit,
   /// otherwise false.
@@ -1492,6 +1783,36 @@ impl Graph {
             );
             std::process::exit(10);
           }
+          for (code, types) in module.get_types() {
+            let valid = lockfile
+              .check_or_insert_types(&code.to_string(), &types.to_string());
+            if !valid {
+              eprintln!(
+                "{}",
+                GraphError::InvalidTypes(
+                  code,
+                  types,
+                  lockfile.filename.clone()
+                )
+              );
+              std::process::exit(10);
+            }
+          }
+        }
+      }
+      for (from, to) in self.redirects.iter() {
+        let valid =
+          lockfile.check_or_insert_redirect(&from.to_string(), &to.to_string());
+        if !valid {
+          eprintln!(
+            "{}",
+            GraphError::InvalidRedirect(
+              from.clone(),
+              to.clone(),
+              lockfile.filename.clone()
+            )
+          );
+          std::process::exit(10);
         }
       }
     }
@@ -1715,11 +2036,38 @@ impl swc_bundler::Resolve for Graph {
   }
 }
 
+/// The maximum number of modules the graph builder fetches at the same time.
+const MAX_PENDING_FETCHES: usize = 16;
+
+/// The progress of the fetches of a graph builder, which is reported each
+/// time a fetch completes.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FetchProgress {
+  /// The number of fetches which completed, including the failed ones.
+  pub completed: usize,
+  pub failed: usize,
+  /// The number of fetches which have been requested so far, which grows as
+  /// the dependencies of the fetched modules are discovered.
+  pub total: usize,
+  /// The total size of the sources of the fetched modules.
+  pub size: usize,
+}
+
+/// A callback which is called with the progress of the fetches of a graph
+/// builder.
+pub type FetchProgressCallback = dyn FnMut(&FetchProgress) + Send;
+
 /// A structure for building a dependency graph of modules.
 pub struct GraphBuilder {
   graph: Graph,
   maybe_import_map: Option<Arc<Mutex<ImportMap>>>,
   pending: FuturesUnordered<FetchFuture>,
+  /// The fetches which wait for the number of pending fetches to go below
+  /// `MAX_PENDING_FETCHES`.
+  queued: VecDeque<FetchFuture>,
+  keep_going: bool,
+  progress: FetchProgress,
+  maybe_progress_callback: Option<Box<FetchProgressCallback>>,
 }
 
 impl GraphBuilder {
@@ -1737,9 +2085,28 @@ impl GraphBuilder {
       graph: Graph::new(handler, maybe_lockfile),
       maybe_import_map: internal_import_map,
       pending: FuturesUnordered::new(),
+      queued: VecDeque::new(),
+      keep_going: false,
+      progress: FetchProgress::default(),
+      maybe_progress_callback: None,
     }
   }
 
+  /// When set, a module which fails to be analyzed is recorded as an error in
+  /// the graph, like a module which fails to be fetched, instead of stopping
+  /// the build, so all the errors can be reported with `Graph::get_errors()`.
+  pub fn set_keep_going(&mut self, keep_going: bool) {
+    self.keep_going = keep_going;
+  }
+
+  /// Set a callback which is called each time a fetch completes.
+  pub fn set_progress_callback(
+    &mut self,
+    callback: Box<FetchProgressCallback>,
+  ) {
+    self.maybe_progress_callback = Some(callback);
+  }
+
   /// Add a module into the graph based on a module specifier.  The module
   /// and any dependencies will be fetched from the handler.  The module will
   /// also be treated as a _root_ module in the graph.
@@ -1751,20 +2118,47 @@ impl GraphBuilder {
     self.fetch(specifier, &None, is_dynamic);
 
     loop {
-      match self.pending.next().await {
+      while self.pending.len() < MAX_PENDING_FETCHES {
+        match self.queued.pop_front() {
+          Some(future) => self.pending.push(future),
+          None => break,
+        }
+      }
+      let maybe_result = self.pending.next().await;
+      let completed = maybe_result.is_some();
+      match maybe_result {
         Some(Err((specifier, err))) => {
+          self.progress.completed += 1;
+          self.progress.failed += 1;
           self
             .graph
             .modules
             .insert(specifier, ModuleSlot::Err(Arc::new(err)));
         }
         Some(Ok(cached_module)) => {
+          self.progress.completed += 1;
+          self.progress.size += cached_module.source.len();
           let is_root = &cached_module.specifier == specifier;
-          self.visit(cached_module, is_root)?;
+          let requested_specifier = cached_module.requested_specifier.clone();
+          if let Err(err) = self.visit(cached_module, is_root) {
+            if !self.keep_going {
+              return Err(err);
+            }
+            self.progress.failed += 1;
+            self
+              .graph
+              .modules
+              .insert(requested_specifier, ModuleSlot::Err(Arc::new(err)));
+          }
+        }
+        None => {}
+      }
+      if completed {
+        if let Some(callback) = self.maybe_progress_callback.as_mut() {
+          callback(&self.progress);
         }
-        _ => {}
       }
-      if self.pending.is_empty() {
+      if self.pending.is_empty() && self.queued.is_empty() {
         break;
       }
     }
@@ -1797,7 +2191,8 @@ impl GraphBuilder {
       let mut handler = self.graph.handler.lock().unwrap();
       let future =
         handler.fetch(specifier.clone(), maybe_referrer.clone(), is_dynamic);
-      self.pending.push(future);
+      self.queued.push_back(future);
+      self.progress.total += 1;
     }
   }
 
@@ -1811,6 +2206,7 @@ impl GraphBuilder {
   ) -> Result<(), AnyError> {
     let specifier = cached_module.specifier.clone();
     let requested_specifier = cached_module.requested_specifier.clone();
+    let redirects = cached_module.redirects.clone();
     let mut module =
       Module::new(cached_module, is_root, self.maybe_import_map.clone());
     match module.media_type {
@@ -1853,7 +2249,10 @@ impl GraphBuilder {
     if let Some((_, specifier)) = module.maybe_types.as_ref() {
       self.fetch(specifier, &None, false);
     }
-    if specifier != requested_specifier {
+    if redirects.last().map(|(_, to)| to) == Some(&specifier) {
+      // record each hop of the redirect chain
+      self.graph.redirects.extend(redirects);
+    } else if specifier != requested_specifier {
       self
         .graph
         .redirects
@@ -2469,6 +2868,50 @@ pub mod tests {
     assert_eq!(info.total_size, 344);
   }
 
+  #[tokio::test]
+  async fn test_graph_import_chains() {
+    let specifier =
+      ModuleSpecifier::resolve_url_or_path("file:///tests/main.ts")
+        .expect("could not resolve module");
+    let (graph, _) = setup(specifier.clone()).await;
+    let s = |s: &str| ModuleSpecifier::resolve_url_or_path(s).unwrap();
+
+    let info = graph
+      .import_chains("https://deno.land/x/lib/c")
+      .expect("could not get import chains");
+    assert_eq!(info.module, specifier);
+    assert_eq!(
+      info.chains,
+      vec![
+        vec![
+          specifier.clone(),
+          s("https://deno.land/x/lib/mod.d.ts"),
+          s("https://deno.land/x/lib/c.js"),
+        ],
+        vec![
+          specifier.clone(),
+          s("https://deno.land/x/lib/mod.js"),
+          s("https://deno.land/x/lib/c.js"),
+        ],
+      ]
+    );
+    assert!(!info.truncated);
+
+    let info = graph
+      .import_chains("https://deno.land/x/lib/c.d.ts")
+      .expect("could not get import chains");
+    assert_eq!(info.chains.len(), 2);
+    assert_eq!(
+      info.chains[0].last(),
+      Some(&s("https://deno.land/x/lib/c.d.ts"))
+    );
+
+    let info = graph
+      .import_chains("https://deno.land/std/")
+      .expect("could not get import chains");
+    assert!(info.chains.is_empty());
+  }
+
   #[tokio::test]
   async fn test_graph_import_json() {
     let specifier =
@@ -2628,4 +3071,74 @@ pub mod tests {
       .expect("module not inserted");
     builder.get_graph();
   }
+
+  #[tokio::test]
+  async fn test_graph_keep_going() {
+    let sources: HashMap<String, String> = map!(
+      "/a.ts" => r#"
+      import * as b from "./b.ts";
+      import * as c from "./c.ts";
+      import * as d from "./d.ts";
+      "#,
+      "/b.ts" => r#"
+      import * as c from "./c.ts";
+      import * as e from "./e.ts";
+      "#,
+      "/c.ts" => r#"
+      export const c = "c";
+      "#,
+      "/d.ts" => r#"
+      export const d = ;
+      "#
+    )
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    let handler = Arc::new(Mutex::new(MemoryHandler::new(sources)));
+    let specifier = ModuleSpecifier::resolve_url_or_path("file:///a.ts")
+      .expect("could not resolve module");
+
+    let mut builder = GraphBuilder::new(handler.clone(), None, None);
+    let result = builder.add(&specifier, false).await;
+    assert!(result.is_err());
+
+    let mut builder = GraphBuilder::new(handler, None, None);
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let p = progress.clone();
+    builder.set_keep_going(true);
+    builder.set_progress_callback(Box::new(move |progress| {
+      p.lock().unwrap().push(progress.clone());
+    }));
+    builder
+      .add(&specifier, false)
+      .await
+      .expect("module not inserted");
+    let graph = builder.get_graph();
+
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 5);
+    assert_eq!(
+      progress.last().unwrap(),
+      &FetchProgress {
+        completed: 5,
+        failed: 2,
+        total: 5,
+        size: progress.last().unwrap().size,
+      }
+    );
+    let errors = graph.get_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].specifier.as_str(), "file:///d.ts");
+    assert_eq!(errors[0].chain.len(), 2);
+    assert_eq!(errors[1].specifier.as_str(), "file:///e.ts");
+    assert!(errors[1].error.contains("Unable to find specifier"));
+    assert_eq!(
+      errors[1]
+        .chain
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>(),
+      vec!["file:///a.ts", "file:///b.ts", "file:///e.ts"]
+    );
+  }
 }
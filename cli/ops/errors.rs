@@ -4,6 +4,7 @@ use crate::diagnostics::Diagnostics;
 use crate::program_state::ProgramState;
 use crate::source_maps::get_orig_position;
 use crate::source_maps::CachedMaps;
+use crate::source_maps::SourceMapGetter;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
@@ -15,10 +16,19 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 pub fn init(rt: &mut deno_core::JsRuntime) {
-  super::reg_json_sync(rt, "op_apply_source_map", op_apply_source_map);
+  init_source_maps::<ProgramState>(rt);
   super::reg_json_sync(rt, "op_format_diagnostic", op_format_diagnostic);
 }
 
+/// Register the op which maps the locations of stack traces to the original
+/// sources with the source maps of `G`, which has to be put into the op state
+/// as an `Arc<G>`.
+pub fn init_source_maps<G: SourceMapGetter + 'static>(
+  rt: &mut deno_core::JsRuntime,
+) {
+  super::reg_json_sync(rt, "op_apply_source_map", op_apply_source_map::<G>);
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ApplySourceMap {
@@ -27,7 +37,7 @@ struct ApplySourceMap {
   column_number: i32,
 }
 
-fn op_apply_source_map(
+fn op_apply_source_map<G: SourceMapGetter + 'static>(
   state: &mut OpState,
   args: Value,
   _zero_copy: &mut [ZeroCopyBuf],
@@ -35,7 +45,7 @@ fn op_apply_source_map(
   let args: ApplySourceMap = serde_json::from_value(args)?;
 
   let mut mappings_map: CachedMaps = HashMap::new();
-  let program_state = state.borrow::<Arc<ProgramState>>().clone();
+  let getter = state.borrow::<Arc<G>>().clone();
 
   let (orig_file_name, orig_line_number, orig_column_number) =
     get_orig_position(
@@ -43,7 +53,7 @@ fn op_apply_source_map(
       args.line_number.into(),
       args.column_number.into(),
       &mut mappings_map,
-      program_state,
+      getter,
     );
 
   Ok(json!({
@@ -12,8 +12,10 @@ use crate::module_graph::CheckOptions;
 use crate::module_graph::GraphBuilder;
 use crate::module_graph::TranspileOptions;
 use crate::module_graph::TypeLib;
+use crate::source_maps::get_inline_source_map;
 use crate::source_maps::SourceMapGetter;
 use crate::specifier_handler::FetchHandler;
+use deno_runtime::deno_fetch::BlobUrlStore;
 use deno_runtime::inspector::InspectorServer;
 use deno_runtime::permissions::Permissions;
 
@@ -50,6 +52,7 @@ pub struct ProgramState {
   pub lockfile: Option<Arc<Mutex<Lockfile>>>,
   pub maybe_import_map: Option<ImportMap>,
   pub maybe_inspector_server: Option<Arc<InspectorServer>>,
+  pub blob_url_store: BlobUrlStore,
 }
 
 impl ProgramState {
@@ -66,19 +69,28 @@ impl ProgramState {
       CacheSetting::ReloadSome(flags.cache_blocklist.clone())
     } else if flags.reload {
       CacheSetting::ReloadAll
+    } else if flags.revalidate {
+      CacheSetting::RespectHeaders
     } else {
       CacheSetting::Use
     };
 
+    let blob_url_store = BlobUrlStore::default();
+
     let file_fetcher = FileFetcher::new(
       http_cache,
       cache_usage,
       !flags.no_remote,
       ca_file.as_deref(),
+      blob_url_store.clone(),
     )?;
 
     let lockfile = if let Some(filename) = &flags.lock {
-      let lockfile = Lockfile::new(filename.clone(), flags.lock_write)?;
+      let lockfile = if flags.lock_update {
+        Lockfile::new_update(filename.clone())?
+      } else {
+        Lockfile::new(filename.clone(), flags.lock_write)?
+      };
       Some(Arc::new(Mutex::new(lockfile)))
     } else {
       None
@@ -118,6 +130,7 @@ impl ProgramState {
       lockfile,
       maybe_import_map,
       maybe_inspector_server,
+      blob_url_store,
     };
     Ok(Arc::new(program_state))
   }
@@ -184,7 +197,7 @@ impl ProgramState {
     loadable_modules.extend(result_modules);
 
     if let Some(ref lockfile) = self.lockfile {
-      let g = lockfile.lock().unwrap();
+      let mut g = lockfile.lock().unwrap();
       g.write()?;
     }
 
@@ -244,7 +257,18 @@ impl ProgramState {
     match url.scheme() {
       // we should only be looking for emits for schemes that denote external
       // modules, which the disk_cache supports
-      "wasm" | "file" | "http" | "https" => (),
+      "wasm" | "file" | "http" | "https" | "data" => (),
+      // the emits of blob URLs are not written to the disk_cache, but only
+      // kept with the loaded modules
+      "blob" => {
+        let modules = self.modules.lock().unwrap();
+        return match modules.get(&ModuleSpecifier::from(url.clone())) {
+          Some(Ok(module_source)) => {
+            Some((module_source.code.clone().into_bytes(), None))
+          }
+          _ => None,
+        };
+      }
       _ => {
         return None;
       }
@@ -292,23 +316,7 @@ impl SourceMapGetter for ProgramState {
           maybe_map
         } else {
           let code = String::from_utf8(code).unwrap();
-          let lines: Vec<&str> = code.split('\n').collect();
-          if let Some(last_line) = lines.last() {
-            if last_line
-              .starts_with("//# sourceMappingURL=data:application/json;base64,")
-            {
-              let input = last_line.trim_start_matches(
-                "//# sourceMappingURL=data:application/json;base64,",
-              );
-              let decoded_map = base64::decode(input)
-                .expect("Unable to decode source map from emitted file.");
-              Some(decoded_map)
-            } else {
-              None
-            }
-          } else {
-            None
-          }
+          get_inline_source_map(&code)
         }
       } else {
         None
@@ -18,6 +18,16 @@ pub trait SourceMapGetter: Sync + Send {
   ) -> Option<String>;
 }
 
+/// Return the source map inlined as a data URL in the last line of emitted
+/// code, if any. A source map which can't be decoded is ignored, as the code
+/// may come from anywhere.
+pub fn get_inline_source_map(code: &str) -> Option<Vec<u8>> {
+  let last_line = code.trim_end().rsplit('\n').next()?;
+  let encoded = last_line
+    .strip_prefix("//# sourceMappingURL=data:application/json;base64,")?;
+  base64::decode(encoded).ok()
+}
+
 /// Cached filename lookups. The key can be None if a previous lookup failed to
 /// find a SourceMap.
 pub type CachedMaps = HashMap<String, Option<SourceMap>>;
@@ -196,6 +206,23 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_get_inline_source_map() {
+    assert_eq!(
+      get_inline_source_map(
+        "console.log(1);\n//# sourceMappingURL=data:application/json;base64,e30=\n"
+      ),
+      Some(b"{}".to_vec())
+    );
+    assert_eq!(
+      get_inline_source_map(
+        "console.log(1);\n//# sourceMappingURL=data:application/json;base64,!!!"
+      ),
+      None
+    );
+    assert_eq!(get_inline_source_map("console.log(1);"), None);
+  }
+
   #[test]
   fn apply_source_map_line() {
     let e = JsError {
@@ -62,6 +62,9 @@ pub struct CachedModule {
   pub maybe_types: Option<String>,
   pub maybe_version: Option<String>,
   pub media_type: MediaType,
+  /// The redirects which were followed to fetch the module from the
+  /// requested specifier, in order.
+  pub redirects: Vec<(ModuleSpecifier, ModuleSpecifier)>,
   pub requested_specifier: ModuleSpecifier,
   pub source: String,
   pub source_path: PathBuf,
@@ -79,6 +82,7 @@ impl Default for CachedModule {
       maybe_types: None,
       maybe_version: None,
       media_type: MediaType::Unknown,
+      redirects: Vec::new(),
       requested_specifier: specifier.clone(),
       source: "".to_string(),
       source_path: PathBuf::new(),
@@ -306,6 +310,13 @@ impl SpecifierHandler for FetchHandler {
         })?;
       let url = source_file.specifier.as_url();
       let is_remote = url.scheme() != "file";
+      let redirects = if source_file.specifier != requested_specifier {
+        file_fetcher
+          .get_redirects(&requested_specifier)
+          .map_err(|err| (requested_specifier.clone(), err))?
+      } else {
+        Vec::new()
+      };
       let filename = disk_cache.get_cache_filename_with_extension(url, "meta");
       let maybe_version = if let Some(filename) = filename {
         if let Ok(bytes) = disk_cache.get(&filename) {
@@ -356,6 +367,7 @@ impl SpecifierHandler for FetchHandler {
         maybe_types: source_file.maybe_types,
         maybe_version,
         media_type: source_file.media_type,
+        redirects,
         requested_specifier,
         source: source_file.source,
         source_path: source_file.local,
@@ -388,10 +400,14 @@ impl SpecifierHandler for FetchHandler {
     specifier: &ModuleSpecifier,
     tsbuildinfo: String,
   ) -> Result<(), AnyError> {
-    let filename = self
+    let filename = match self
       .disk_cache
       .get_cache_filename_with_extension(specifier.as_url(), "buildinfo")
-      .unwrap();
+    {
+      Some(filename) => filename,
+      // modules which are only kept in memory, like blob URLs
+      None => return Ok(()),
+    };
     debug!("set_tsbuildinfo - filename {:?}", filename);
     self
       .disk_cache
@@ -407,10 +423,13 @@ impl SpecifierHandler for FetchHandler {
     match emit {
       Emit::Cli((code, maybe_map)) => {
         let url = specifier.as_url();
-        let filename = self
-          .disk_cache
-          .get_cache_filename_with_extension(url, "js")
-          .unwrap();
+        let filename =
+          match self.disk_cache.get_cache_filename_with_extension(url, "js") {
+            Some(filename) => filename,
+            // the emit of a blob URL is only kept in memory, as its URL is
+            // never requested again once the program exits
+            None => return Ok(()),
+          };
         self.disk_cache.set(&filename, code.as_bytes())?;
 
         if let Some(map) = maybe_map {
@@ -450,10 +469,14 @@ impl SpecifierHandler for FetchHandler {
     version_hash: String,
   ) -> Result<(), AnyError> {
     let compiled_file_metadata = CompiledFileMetadata { version_hash };
-    let filename = self
+    let filename = match self
       .disk_cache
       .get_cache_filename_with_extension(specifier.as_url(), "meta")
-      .unwrap();
+    {
+      Some(filename) => filename,
+      // modules which are only kept in memory, like blob URLs
+      None => return Ok(()),
+    };
 
     self
       .disk_cache
@@ -568,6 +591,7 @@ pub mod tests {
   use super::*;
   use crate::file_fetcher::CacheSetting;
   use crate::http_cache::HttpCache;
+  use deno_runtime::deno_fetch::BlobUrlStore;
   use tempfile::TempDir;
 
   macro_rules! map (
@@ -592,6 +616,7 @@ pub mod tests {
       CacheSetting::Use,
       true,
       None,
+      BlobUrlStore::default(),
     )
     .expect("could not setup");
     let disk_cache = deno_dir.gen_cache;
@@ -654,6 +679,25 @@ pub mod tests {
     );
   }
 
+  #[test]
+  fn test_fetch_handler_set_cache_blob() {
+    let (temp_dir, mut file_fetcher) = setup();
+    let specifier = ModuleSpecifier::resolve_url(
+      "blob:null/a0a1a2a3-b0b1-c0c1-d0d1-e0e1e2e3e4e5",
+    )
+    .unwrap();
+    file_fetcher
+      .set_cache(&specifier, &Emit::Cli(("some code".to_string(), None)))
+      .expect("could not set cache");
+    file_fetcher
+      .set_version(&specifier, "1".to_string())
+      .expect("could not set version");
+    file_fetcher
+      .set_tsbuildinfo(&specifier, "{}".to_string())
+      .expect("could not set tsbuildinfo");
+    assert!(!temp_dir.path().join("gen").join("blob").exists());
+  }
+
   #[tokio::test]
   async fn test_fetch_handler_is_remote() {
     let _http_server_guard = test_util::http_server();
@@ -1,18 +1,39 @@
+use crate::checksum;
 use crate::colors;
+use crate::deno_dir::DenoDir;
 use crate::flags::Flags;
+use crate::fs_util;
+use crate::media_type::MediaType;
+use crate::module_graph::Graph;
+use crate::ops;
+use crate::source_maps::get_inline_source_map;
+use crate::source_maps::SourceMapGetter;
 use crate::tokio_util;
 use crate::version;
 use deno_core::error::bail;
-use deno_core::error::type_error;
+use deno_core::error::custom_error;
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures::FutureExt;
+use deno_core::serde_json;
 use deno_core::ModuleLoader;
+use deno_core::ModuleSource;
 use deno_core::ModuleSpecifier;
 use deno_core::OpState;
+use deno_runtime::deno_fetch::BlobUrlStore;
+use deno_runtime::ops::worker_host::CreateWebWorkerCb;
 use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsOptions;
+use deno_runtime::virtual_fs::VirtualFs;
+use deno_runtime::web_worker::WebWorker;
+use deno_runtime::web_worker::WebWorkerOptions;
 use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
+use serde::Deserialize;
+use serde::Serialize;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::env::current_exe;
 use std::fs::File;
@@ -20,38 +41,370 @@ use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
+use walkdir::WalkDir;
 
 const MAGIC_TRAILER: &[u8; 8] = b"d3n0l4nd";
+const TRAILER_LEN: usize = 24;
+
+/// The runtime configuration given to `deno compile`, which is embedded in
+/// the standalone binary next to the module archive.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Metadata {
+  pub main_module: String,
+  /// Whether the unstable APIs are enabled in the binary. This is given with
+  /// `--runtime-unstable`, as `--unstable` is always set to run `deno compile`.
+  pub unstable: bool,
+  pub seed: Option<u64>,
+  pub permissions: PermissionsOptions,
+  pub v8_flags: Vec<String>,
+  pub manifest: Option<Manifest>,
+}
+
+impl Metadata {
+  pub fn new(
+    main_module: &ModuleSpecifier,
+    flags: &Flags,
+    unstable: bool,
+  ) -> Self {
+    Metadata {
+      main_module: main_module.to_string(),
+      unstable,
+      seed: flags.seed,
+      permissions: flags.clone().into(),
+      v8_flags: flags.v8_flags.clone(),
+      manifest: None,
+    }
+  }
+}
+
+/// A record of where the modules embedded in a standalone binary came from,
+/// which the binary prints when it is run with `--deno-manifest`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+  /// The checksums of the sources of the modules, as in a lock file.
+  pub modules: BTreeMap<String, String>,
+  pub redirects: BTreeMap<String, String>,
+  pub import_map: Option<String>,
+}
+
+impl Manifest {
+  pub fn new(maybe_import_map: Option<ModuleSpecifier>) -> Self {
+    Manifest {
+      import_map: maybe_import_map.map(|s| s.to_string()),
+      ..Default::default()
+    }
+  }
+
+  pub fn add_graph(&mut self, graph: &Graph) {
+    for specifier in graph.get_modules() {
+      if let Some(source) = graph.get_source(&specifier) {
+        self
+          .modules
+          .insert(specifier.to_string(), checksum::gen(&[source]));
+      }
+    }
+    for (from, to) in graph.get_redirects() {
+      self.redirects.insert(from.to_string(), to.to_string());
+    }
+  }
+}
+
+/// The location of the code of an archived module in the data section of
+/// an `EmbeddedModules` archive.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct ModuleEntry {
+  offset: u64,
+  len: u64,
+}
+
+/// The index of an `EmbeddedModules` archive.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ModuleIndex {
+  modules: HashMap<String, ModuleEntry>,
+  redirects: HashMap<String, String>,
+  /// The resolved specifier of each import of a module, keyed by the
+  /// specifier as it is written in the module.  This allows the runtime to
+  /// resolve imports the same way they were resolved when the module graph
+  /// was built, including any import map.
+  dependencies: HashMap<String, HashMap<String, String>>,
+  /// Static assets included with `--include`, keyed by their path relative
+  /// to `files_root` with `/` as separator.
+  files: HashMap<String, ModuleEntry>,
+  /// The directory `deno compile` was run in.
+  files_root: PathBuf,
+}
+
+/// An indexed archive of the emitted modules of one or more module graphs,
+/// keyed by their original specifiers, and of the static assets embedded in
+/// a standalone binary.
+///
+/// The archive is serialized as a u64 length of the JSON serialized index,
+/// followed by the index and the concatenated code of all modules.
+#[derive(Debug, Default)]
+pub struct EmbeddedModules {
+  index: ModuleIndex,
+  data: Vec<u8>,
+}
+
+impl EmbeddedModules {
+  /// Add the loadable modules of a module graph to the archive.  Modules
+  /// which could not be loaded are skipped, which means that trying to
+  /// import them from the binary will fail at runtime.
+  pub fn add_graph(
+    &mut self,
+    graph: &Graph,
+    loadable_modules: HashMap<ModuleSpecifier, Result<ModuleSource, AnyError>>,
+  ) {
+    for (specifier, result) in loadable_modules {
+      match result {
+        Ok(module_source) => {
+          if module_source.module_url_specified
+            != module_source.module_url_found
+          {
+            self.index.redirects.insert(
+              module_source.module_url_specified,
+              module_source.module_url_found,
+            );
+          } else {
+            self.insert(&module_source.module_url_found, &module_source.code);
+          }
+        }
+        Err(err) => {
+          if graph.get_media_type(&specifier) != Some(MediaType::Dts) {
+            debug!("skipping \"{}\" when embedding: {}", specifier, err);
+          }
+        }
+      }
+    }
+    for specifier in graph.get_modules() {
+      if let Some(dependencies) = graph.get_dependencies(&specifier) {
+        let dependencies = dependencies
+          .into_iter()
+          .map(|(k, v)| (k, v.to_string()))
+          .collect();
+        self
+          .index
+          .dependencies
+          .insert(specifier.to_string(), dependencies);
+      }
+    }
+  }
+
+  /// Add a file, or all files of a directory recursively, to the archive.
+  /// The files are stored with their path relative to `root`, the directory
+  /// `deno compile` is run in. The standalone binary opens them from its
+  /// `VirtualFs` with that relative path, wherever it is run, or with their
+  /// absolute path at compile time, like a path based on `import.meta.url`.
+  pub fn include(&mut self, root: &Path, path: &Path) -> Result<(), AnyError> {
+    let path = fs_util::resolve_from_cwd(path)?;
+    if !path.exists() {
+      bail!("Could not compile: {:?} does not exist.", path);
+    }
+    if !path.starts_with(root) {
+      bail!(
+        "Could not compile: {:?} is not inside the current directory {:?}.",
+        path,
+        root
+      );
+    }
+    self.index.files_root = root.to_path_buf();
+    for entry in WalkDir::new(&path) {
+      let entry = entry?;
+      if entry.file_type().is_file() {
+        let key = entry
+          .path()
+          .strip_prefix(root)?
+          .components()
+          .map(|c| c.as_os_str().to_string_lossy())
+          .collect::<Vec<_>>()
+          .join("/");
+        if !self.index.files.contains_key(&key) {
+          let contents = std::fs::read(entry.path())?;
+          let entry = self.append(&contents);
+          self.index.files.insert(key, entry);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  fn insert(&mut self, specifier: &str, code: &str) {
+    if self.index.modules.contains_key(specifier) {
+      return;
+    }
+    let entry = self.append(code.as_bytes());
+    self.index.modules.insert(specifier.to_string(), entry);
+  }
+
+  fn append(&mut self, bytes: &[u8]) -> ModuleEntry {
+    let entry = ModuleEntry {
+      offset: self.data.len() as u64,
+      len: bytes.len() as u64,
+    };
+    self.data.extend_from_slice(bytes);
+    entry
+  }
+
+  fn get_data(&self, entry: &ModuleEntry) -> Option<&[u8]> {
+    let start = entry.offset as usize;
+    let end = start + entry.len as usize;
+    self.data.get(start..end)
+  }
+
+  /// Create the read-only file system of the included static assets.
+  fn virtual_fs(&self) -> VirtualFs {
+    let files = self
+      .index
+      .files
+      .iter()
+      .filter_map(|(key, entry)| {
+        let path = key.split('/').collect::<PathBuf>();
+        self.get_data(entry).map(|data| (path, data.to_vec()))
+      })
+      .collect();
+    VirtualFs::new(self.index.files_root.clone(), files)
+  }
+
+  /// Resolve an import of a module the way it was resolved when the module
+  /// graph was built.  Imports which were not part of the module graph,
+  /// like computed dynamic imports, are resolved relative to the referrer.
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    if let Some(resolved) = self
+      .index
+      .dependencies
+      .get(self.resolve_redirect(referrer))
+      .and_then(|deps| deps.get(specifier))
+    {
+      Ok(ModuleSpecifier::resolve_url(resolved)?)
+    } else {
+      Ok(ModuleSpecifier::resolve_import(specifier, referrer)?)
+    }
+  }
+
+  fn resolve_redirect<'a>(&'a self, specifier: &'a str) -> &'a str {
+    let mut specifier = specifier;
+    // guard against redirect cycles in a malformed archive
+    for _ in 0..self.index.redirects.len() {
+      if let Some(redirect) = self.index.redirects.get(specifier) {
+        specifier = redirect;
+      } else {
+        break;
+      }
+    }
+    specifier
+  }
+
+  fn contains(&self, specifier: &str) -> bool {
+    let specifier = self.resolve_redirect(specifier);
+    self.index.modules.contains_key(specifier)
+  }
+
+  /// Return the specifier the module was found at and its code.
+  fn get(&self, specifier: &str) -> Option<(String, String)> {
+    let found = self.resolve_redirect(specifier);
+    let entry = self.index.modules.get(found)?;
+    let code = self.get_data(entry)?;
+    Some((
+      found.to_string(),
+      String::from_utf8_lossy(code).into_owned(),
+    ))
+  }
+
+  pub fn to_bytes(&self) -> Result<Vec<u8>, AnyError> {
+    let index = serde_json::to_vec(&self.index)?;
+    let mut bytes = Vec::with_capacity(8 + index.len() + self.data.len());
+    bytes.write_all(&(index.len() as u64).to_be_bytes())?;
+    bytes.write_all(&index)?;
+    bytes.write_all(&self.data)?;
+    Ok(bytes)
+  }
+
+  pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Self, AnyError> {
+    if bytes.len() < 8 {
+      bail!("Unexpected end of the embedded data in the executable.");
+    }
+    let index_len = u64_from_bytes(&bytes[..8])? as usize;
+    if bytes.len() < 8 + index_len {
+      bail!("Unexpected end of the embedded data in the executable.");
+    }
+    let index: ModuleIndex = serde_json::from_slice(&bytes[8..8 + index_len])?;
+    let data = bytes.split_off(8 + index_len);
+    Ok(EmbeddedModules { index, data })
+  }
+}
+
+impl SourceMapGetter for EmbeddedModules {
+  /// The emitted code of the archived modules has an inline source map, which
+  /// maps stack traces to the original sources.
+  fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+    let (_, code) = self.get(file_name)?;
+    get_inline_source_map(&code)
+  }
+
+  /// The original sources are not archived.
+  fn get_source_line(
+    &self,
+    _file_name: &str,
+    _line_number: usize,
+  ) -> Option<String> {
+    None
+  }
+}
 
 /// This function will try to run this binary as a standalone binary
 /// produced by `deno compile`. It determines if this is a stanalone
-/// binary by checking for the magic trailer string `D3N0` at EOF-12.
-/// After the magic trailer is a u64 pointer to the start of the JS
-/// file embedded in the binary. This file is read, and run. If no
-/// magic trailer is present, this function exits with Ok(()).
+/// binary by checking for the magic trailer string `d3n0l4nd` at EOF-24.
+/// After the magic trailer are a u64 pointer to the start of the
+/// `EmbeddedModules` archive embedded in the binary and a u64 pointer to
+/// the start of the JSON serialized `Metadata` following it. These are read,
+/// and run. If no magic trailer is present, this function exits with Ok(()).
 pub fn try_run_standalone_binary(args: Vec<String>) -> Result<(), AnyError> {
   let current_exe_path = current_exe()?;
 
   let mut current_exe = File::open(current_exe_path)?;
-  let trailer_pos = current_exe.seek(SeekFrom::End(-16))?;
-  let mut trailer = [0; 16];
+  let trailer_pos = current_exe.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+  let mut trailer = [0; TRAILER_LEN];
   current_exe.read_exact(&mut trailer)?;
-  let (magic_trailer, bundle_pos_arr) = trailer.split_at(8);
+  let (magic_trailer, rest) = trailer.split_at(8);
   if magic_trailer == MAGIC_TRAILER {
-    let bundle_pos_arr: &[u8; 8] = bundle_pos_arr.try_into()?;
-    let bundle_pos = u64::from_be_bytes(*bundle_pos_arr);
-    current_exe.seek(SeekFrom::Start(bundle_pos))?;
+    let (modules_pos, metadata_pos) = rest.split_at(8);
+    let modules_pos = u64_from_bytes(modules_pos)?;
+    let metadata_pos = u64_from_bytes(metadata_pos)?;
+    let (modules_len, metadata_len) =
+      embedded_data_lengths(modules_pos, metadata_pos, trailer_pos)?;
+
+    current_exe.seek(SeekFrom::Start(modules_pos))?;
+    let mut modules = vec![0; modules_len as usize];
+    current_exe.read_exact(&mut modules).map_err(|_| {
+      generic_error("Unexpected end of the embedded data in the executable.")
+    })?;
+    let modules = EmbeddedModules::from_bytes(modules)?;
+    let metadata = read_string_slice(&mut current_exe, metadata_len)?;
+    let metadata: Metadata = serde_json::from_str(&metadata)?;
 
-    let bundle_len = trailer_pos - bundle_pos;
-    let mut bundle = String::new();
-    current_exe.take(bundle_len).read_to_string(&mut bundle)?;
-    // TODO: check amount of bytes read
+    // without an embedded manifest the flag is an argument of the program
+    if let Some(manifest) = &metadata.manifest {
+      if args.get(1).map(String::as_str) == Some("--deno-manifest") {
+        println!("{}", serde_json::to_string_pretty(manifest)?);
+        std::process::exit(0);
+      }
+    }
+
+    if !metadata.v8_flags.is_empty() {
+      crate::init_v8_flags(&metadata.v8_flags);
+    }
 
-    if let Err(err) = tokio_util::run_basic(run(bundle, args)) {
+    if let Err(err) = tokio_util::run_basic(run(modules, metadata, args)) {
       eprintln!("{}: {}", colors::red_bold("error"), err.to_string());
       std::process::exit(1);
     }
@@ -61,24 +414,59 @@ pub fn try_run_standalone_binary(args: Vec<String>) -> Result<(), AnyError> {
   }
 }
 
-const SPECIFIER: &str = "file://$deno$/bundle.js";
+fn u64_from_bytes(arr: &[u8]) -> Result<u64, AnyError> {
+  let fixed_arr: &[u8; 8] = arr.try_into()?;
+  Ok(u64::from_be_bytes(*fixed_arr))
+}
+
+/// Computes the lengths of the module archive and the metadata from their
+/// positions in the trailer, which are unchecked input of a possibly corrupt
+/// executable.
+fn embedded_data_lengths(
+  modules_pos: u64,
+  metadata_pos: u64,
+  trailer_pos: u64,
+) -> Result<(u64, u64), AnyError> {
+  match (
+    metadata_pos.checked_sub(modules_pos),
+    trailer_pos.checked_sub(metadata_pos),
+  ) {
+    (Some(modules_len), Some(metadata_len)) => Ok((modules_len, metadata_len)),
+    _ => bail!("Unexpected end of the embedded data in the executable."),
+  }
+}
 
-struct EmbeddedModuleLoader(String);
+/// Reads exactly `len` bytes from the current position of `file` as UTF-8.
+fn read_string_slice(file: &mut File, len: u64) -> Result<String, AnyError> {
+  let mut string = String::new();
+  let read = file.take(len).read_to_string(&mut string)?;
+  if read as u64 != len {
+    bail!("Unexpected end of the embedded data in the executable.");
+  }
+  Ok(string)
+}
+
+struct EmbeddedModuleLoader(Arc<EmbeddedModules>);
 
 impl ModuleLoader for EmbeddedModuleLoader {
   fn resolve(
     &self,
     _op_state: Rc<RefCell<OpState>>,
     specifier: &str,
-    _referrer: &str,
+    referrer: &str,
     _is_main: bool,
   ) -> Result<ModuleSpecifier, AnyError> {
-    if specifier != SPECIFIER {
-      return Err(type_error(
-        "Self-contained binaries don't support module loading",
+    let module_specifier = self.0.resolve(specifier, referrer)?;
+    if !self.0.contains(&module_specifier.to_string()) {
+      return Err(custom_error(
+        "NotFound",
+        format!(
+          "Module not found in the self-contained binary \"{}\"",
+          module_specifier
+        ),
       ));
     }
-    Ok(ModuleSpecifier::resolve_url(specifier)?)
+    Ok(module_specifier)
   }
 
   fn load(
@@ -89,45 +477,116 @@ impl ModuleLoader for EmbeddedModuleLoader {
     _is_dynamic: bool,
   ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
     let module_specifier = module_specifier.clone();
-    let code = self.0.to_string();
+    let maybe_module = self.0.get(&module_specifier.to_string());
     async move {
-      if module_specifier.to_string() != SPECIFIER {
-        return Err(type_error(
-          "Self-contained binaries don't support module loading",
-        ));
-      }
-      Ok(deno_core::ModuleSource {
+      let (module_url_found, code) = maybe_module.ok_or_else(|| {
+        custom_error(
+          "NotFound",
+          format!(
+            "Module not found in the self-contained binary \"{}\"",
+            module_specifier
+          ),
+        )
+      })?;
+      Ok(ModuleSource {
         code,
         module_url_specified: module_specifier.to_string(),
-        module_url_found: module_specifier.to_string(),
+        module_url_found,
       })
     }
     .boxed_local()
   }
 }
 
-async fn run(source_code: String, args: Vec<String>) -> Result<(), AnyError> {
+fn create_web_worker_callback(
+  modules: Arc<EmbeddedModules>,
+  virtual_fs: VirtualFs,
+  blob_url_store: BlobUrlStore,
+  flags: Flags,
+) -> Arc<CreateWebWorkerCb> {
+  Arc::new(move |args| {
+    let module_loader = Rc::new(EmbeddedModuleLoader(modules.clone()));
+    let create_web_worker_cb = create_web_worker_callback(
+      modules.clone(),
+      virtual_fs.clone(),
+      blob_url_store.clone(),
+      flags.clone(),
+    );
+
+    let options = WebWorkerOptions {
+      args: flags.argv.clone(),
+      apply_source_maps: true,
+      debug_flag: false,
+      unstable: flags.unstable,
+      ca_filepath: None,
+      user_agent: crate::http_util::get_user_agent(),
+      seed: flags.seed,
+      module_loader,
+      create_web_worker_cb,
+      js_error_create_fn: None,
+      use_deno_namespace: args.use_deno_namespace,
+      attach_inspector: false,
+      maybe_inspector_server: None,
+      runtime_version: version::deno(),
+      ts_version: version::TYPESCRIPT.to_string(),
+      no_color: !colors::use_color(),
+      get_error_class_fn: Some(&crate::errors::get_error_class_name),
+      blob_url_store: blob_url_store.clone(),
+    };
+
+    let mut worker = WebWorker::from_options(
+      args.name,
+      args.permissions,
+      args.main_module,
+      args.worker_id,
+      &options,
+    );
+    {
+      let js_runtime = &mut worker.js_runtime;
+      let op_state = js_runtime.op_state();
+      op_state.borrow_mut().put(virtual_fs.clone());
+      op_state.borrow_mut().put(modules.clone());
+      // Applies the source maps of the archived modules to stack traces
+      ops::errors::init_source_maps::<EmbeddedModules>(js_runtime);
+    }
+    worker.bootstrap(&options);
+
+    worker
+  })
+}
+
+async fn run(
+  modules: EmbeddedModules,
+  metadata: Metadata,
+  args: Vec<String>,
+) -> Result<(), AnyError> {
   let flags = Flags {
     argv: args[1..].to_vec(),
-    // TODO(lucacasonato): remove once you can specify this correctly through embedded metadata
-    unstable: true,
+    unstable: metadata.unstable,
+    seed: metadata.seed,
     ..Default::default()
   };
-  let main_module = ModuleSpecifier::resolve_url(SPECIFIER)?;
-  let permissions = Permissions::allow_all();
-  let module_loader = Rc::new(EmbeddedModuleLoader(source_code));
-  let create_web_worker_cb = Arc::new(|_| {
-    todo!("Worker are currently not supported in standalone binaries");
-  });
+  let main_module = ModuleSpecifier::resolve_url(&metadata.main_module)?;
+  let permissions = Permissions::from_options(&metadata.permissions);
+  let virtual_fs = modules.virtual_fs();
+  let modules = Arc::new(modules);
+  let blob_url_store = BlobUrlStore::default();
+  let module_loader = Rc::new(EmbeddedModuleLoader(modules.clone()));
+  let create_web_worker_cb = create_web_worker_callback(
+    modules.clone(),
+    virtual_fs.clone(),
+    blob_url_store.clone(),
+    flags.clone(),
+  );
 
   let options = WorkerOptions {
-    apply_source_maps: false,
+    apply_source_maps: true,
     args: flags.argv.clone(),
     debug_flag: false,
     user_agent: crate::http_util::get_user_agent(),
-    unstable: true,
+    unstable: flags.unstable,
     ca_filepath: None,
-    seed: None,
+    seed: flags.seed,
     js_error_create_fn: None,
     create_web_worker_cb,
     attach_inspector: false,
@@ -138,9 +597,18 @@ async fn run(source_code: String, args: Vec<String>) -> Result<(), AnyError> {
     ts_version: version::TYPESCRIPT.to_string(),
     no_color: !colors::use_color(),
     get_error_class_fn: Some(&crate::errors::get_error_class_name),
+    blob_url_store,
   };
   let mut worker =
     MainWorker::from_options(main_module.clone(), permissions, &options);
+  {
+    let js_runtime = &mut worker.js_runtime;
+    let op_state = js_runtime.op_state();
+    op_state.borrow_mut().put(virtual_fs);
+    op_state.borrow_mut().put(modules);
+    // Applies the source maps of the archived modules to stack traces
+    ops::errors::init_source_maps::<EmbeddedModules>(js_runtime);
+  }
   worker.bootstrap(&options);
   worker.execute_module(&main_module).await?;
   worker.execute("window.dispatchEvent(new Event('load'))")?;
@@ -149,30 +617,75 @@ async fn run(source_code: String, args: Vec<String>) -> Result<(), AnyError> {
   Ok(())
 }
 
-/// This functions creates a standalone deno binary by appending a bundle
-/// and magic trailer to the currently executing binary.
+/// Return the path a runtime binary for `target` is cached at in the
+/// `DENO_DIR`.
+pub fn get_runtime_binary_path(deno_dir: &DenoDir, target: &str) -> PathBuf {
+  let mut file_name = format!("denort-{}-{}", version::deno(), target);
+  if target.contains("windows") {
+    file_name.push_str(".exe");
+  }
+  deno_dir.root.join("dl").join(file_name)
+}
+
+/// Read the runtime binary an executable for `target` is based on. For the
+/// target of the currently executing binary, this is the binary itself,
+/// otherwise a deno binary of the same version built for `target` is taken
+/// from the `DENO_DIR`.
+pub async fn get_base_binary(
+  deno_dir: &DenoDir,
+  target: &str,
+) -> Result<Vec<u8>, AnyError> {
+  let binary_path = if target == env!("TARGET") {
+    current_exe()?
+  } else {
+    let binary_path = get_runtime_binary_path(deno_dir, target);
+    if !binary_path.is_file() {
+      bail!(
+        "Could not compile: no runtime binary for target \"{}\" found at {:?}. It has to be a deno {} executable built for that target.",
+        target,
+        binary_path,
+        version::deno()
+      );
+    }
+    binary_path
+  };
+  Ok(tokio::fs::read(binary_path).await?)
+}
+
+/// This functions creates a standalone deno binary by appending an archive
+/// of the emitted modules and included files, its metadata and magic trailer
+/// to the runtime binary of the target.
 pub async fn create_standalone_binary(
-  mut source_code: Vec<u8>,
+  mut original_bin: Vec<u8>,
+  modules: EmbeddedModules,
+  metadata: Metadata,
   output: PathBuf,
+  target: &str,
 ) -> Result<(), AnyError> {
-  let original_binary_path = std::env::current_exe()?;
-  let mut original_bin = tokio::fs::read(original_binary_path).await?;
+  let mut source_code = modules.to_bytes()?;
+  let mut metadata = serde_json::to_vec(&metadata)?;
 
+  let modules_pos = original_bin.len();
+  let metadata_pos = modules_pos + source_code.len();
   let mut trailer = MAGIC_TRAILER.to_vec();
-  trailer.write_all(&original_bin.len().to_be_bytes())?;
+  trailer.write_all(&(modules_pos as u64).to_be_bytes())?;
+  trailer.write_all(&(metadata_pos as u64).to_be_bytes())?;
 
-  let mut final_bin =
-    Vec::with_capacity(original_bin.len() + source_code.len() + trailer.len());
+  let mut final_bin = Vec::with_capacity(
+    original_bin.len() + source_code.len() + metadata.len() + trailer.len(),
+  );
   final_bin.append(&mut original_bin);
   final_bin.append(&mut source_code);
+  final_bin.append(&mut metadata);
   final_bin.append(&mut trailer);
 
-  let output =
-    if cfg!(windows) && output.extension().unwrap_or_default() != "exe" {
-      PathBuf::from(output.display().to_string() + ".exe")
-    } else {
-      output
-    };
+  let output = if target.contains("windows")
+    && output.extension().unwrap_or_default() != "exe"
+  {
+    PathBuf::from(output.display().to_string() + ".exe")
+  } else {
+    output
+  };
 
   if output.exists() {
     // If the output is a directory, throw error
@@ -181,10 +694,10 @@ pub async fn create_standalone_binary(
     }
 
     // Make sure we don't overwrite any file not created by Deno compiler.
-    // Check for magic trailer in last 16 bytes
+    // Check for magic trailer in last 24 bytes
     let mut output_file = File::open(&output)?;
-    output_file.seek(SeekFrom::End(-16))?;
-    let mut trailer = [0; 16];
+    output_file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    let mut trailer = [0; TRAILER_LEN];
     output_file.read_exact(&mut trailer)?;
     let (magic_trailer, _) = trailer.split_at(8);
     if magic_trailer != MAGIC_TRAILER {
@@ -201,3 +714,141 @@ pub async fn create_standalone_binary(
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn test_embedded_data_lengths() {
+    assert_eq!(embedded_data_lengths(10, 25, 30).unwrap(), (15, 5));
+    assert_eq!(embedded_data_lengths(10, 10, 10).unwrap(), (0, 0));
+    assert!(embedded_data_lengths(25, 10, 30).is_err());
+    assert!(embedded_data_lengths(10, 35, 30).is_err());
+  }
+
+  #[test]
+  fn test_embedded_modules_round_trip() {
+    let mut modules = EmbeddedModules::default();
+    modules.insert("file:///a.js", "import \"./b.js\";");
+    modules.insert("https://deno.land/x/b.js", "console.log(\"b\");");
+    modules.index.redirects.insert(
+      "https://deno.land/x/c.js".to_string(),
+      "https://deno.land/x/b.js".to_string(),
+    );
+    let mut dependencies = HashMap::new();
+    dependencies
+      .insert("./b.js".to_string(), "https://deno.land/x/b.js".to_string());
+    modules
+      .index
+      .dependencies
+      .insert("file:///a.js".to_string(), dependencies);
+
+    let bytes = modules.to_bytes().unwrap();
+    let modules = EmbeddedModules::from_bytes(bytes).unwrap();
+    assert_eq!(
+      modules.get("file:///a.js"),
+      Some(("file:///a.js".to_string(), "import \"./b.js\";".to_string()))
+    );
+    assert_eq!(
+      modules.get("https://deno.land/x/c.js"),
+      Some((
+        "https://deno.land/x/b.js".to_string(),
+        "console.log(\"b\");".to_string()
+      ))
+    );
+    assert!(modules.get("file:///b.js").is_none());
+    assert_eq!(
+      modules
+        .resolve("./b.js", "file:///a.js")
+        .unwrap()
+        .to_string(),
+      "https://deno.land/x/b.js"
+    );
+    assert_eq!(
+      modules
+        .resolve("./d.js", "file:///a.js")
+        .unwrap()
+        .to_string(),
+      "file:///d.js"
+    );
+    assert!(modules.contains("https://deno.land/x/c.js"));
+    assert!(!modules.contains("file:///d.js"));
+  }
+
+  #[test]
+  fn test_embedded_modules_include() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let assets = temp_dir.path().join("assets");
+    std::fs::create_dir_all(assets.join("nested")).unwrap();
+    std::fs::write(assets.join("a.txt"), b"a").unwrap();
+    std::fs::write(assets.join("nested/b.json"), b"{}").unwrap();
+
+    let root = temp_dir.path();
+    let mut modules = EmbeddedModules::default();
+    modules.include(root, &assets).unwrap();
+    assert!(modules.include(root, &root.join("missing")).is_err());
+    assert!(modules.include(&assets.join("nested"), &assets).is_err());
+    let bytes = modules.to_bytes().unwrap();
+    let modules = EmbeddedModules::from_bytes(bytes).unwrap();
+
+    let virtual_fs = modules.virtual_fs();
+    let file = virtual_fs.open(&assets.join("nested/b.json")).unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(file.unwrap().read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"{}");
+    assert!(virtual_fs.open(&assets.join("a.txt")).unwrap().is_some());
+    assert!(virtual_fs.open(&assets.join("c.txt")).unwrap().is_none());
+    // relative paths are resolved against the root, not the cwd
+    assert!(virtual_fs
+      .open(Path::new("assets/nested/b.json"))
+      .unwrap()
+      .is_some());
+  }
+
+  #[test]
+  fn test_get_runtime_binary_path() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let deno_dir = DenoDir::new(Some(temp_dir.path().to_path_buf())).unwrap();
+    assert_eq!(
+      get_runtime_binary_path(&deno_dir, "aarch64-unknown-linux-gnu"),
+      temp_dir.path().join("dl").join(format!(
+        "denort-{}-aarch64-unknown-linux-gnu",
+        version::deno()
+      ))
+    );
+    assert_eq!(
+      get_runtime_binary_path(&deno_dir, "x86_64-pc-windows-msvc"),
+      temp_dir.path().join("dl").join(format!(
+        "denort-{}-x86_64-pc-windows-msvc.exe",
+        version::deno()
+      ))
+    );
+  }
+
+  #[test]
+  fn test_embedded_modules_source_map() {
+    let mut modules = EmbeddedModules::default();
+    modules.insert(
+      "file:///a.ts",
+      "console.log(\"a\");\n//# sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozfQ==",
+    );
+    modules.insert("file:///b.js", "console.log(\"b\");");
+    assert_eq!(
+      modules.get_source_map("file:///a.ts"),
+      Some(b"{\"version\":3}".to_vec())
+    );
+    assert!(modules.get_source_map("file:///b.js").is_none());
+    assert!(modules.get_source_map("file:///c.js").is_none());
+  }
+
+  #[test]
+  fn test_embedded_modules_truncated() {
+    let mut modules = EmbeddedModules::default();
+    modules.insert("file:///a.js", "console.log(\"a\");");
+    let mut bytes = modules.to_bytes().unwrap();
+    bytes.truncate(10);
+    assert!(EmbeddedModules::from_bytes(bytes).is_err());
+  }
+}
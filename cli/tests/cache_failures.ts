@@ -0,0 +1,2 @@
+import "http://localhost:4545/cli/tests/subdir/does_not_exist.ts";
+import "./subdir/cache_failures_dep.ts";
@@ -0,0 +1,20 @@
+const blob = new Blob(
+  ['export const a: string = "a";\n'],
+  { type: "application/typescript" },
+);
+const url = URL.createObjectURL(blob);
+
+const { a } = await import(url);
+console.log(a);
+
+const workerBlob = new Blob(
+  ['self.postMessage("b");\nself.close();\n'],
+  { type: "application/javascript" },
+);
+const workerUrl = URL.createObjectURL(workerBlob);
+const worker = new Worker(workerUrl, { type: "module" });
+worker.onmessage = (e) => {
+  console.log(e.data);
+  URL.revokeObjectURL(url);
+  URL.revokeObjectURL(workerUrl);
+};
@@ -0,0 +1,5 @@
+const blob = new Blob(
+  ['import "file:///a.ts";\n'],
+  { type: "application/javascript" },
+);
+await import(URL.createObjectURL(blob));
@@ -0,0 +1,9 @@
+import { a } from "data:application/typescript;base64,ZXhwb3J0IGNvbnN0IGE6IHN0cmluZyA9ICJhIjsK";
+
+console.log(a);
+
+const { b } = await import(
+  "data:application/javascript,export%20const%20b%20%3D%20%22b%22%3B"
+);
+
+console.log(b);
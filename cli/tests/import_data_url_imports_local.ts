@@ -0,0 +1 @@
+import "data:application/javascript,import%20%22file%3A%2F%2F%2Fa.ts%22%3B";
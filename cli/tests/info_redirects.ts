@@ -0,0 +1 @@
+import "http://localhost:4546/cli/tests/subdir/redirects/redirect1.ts";
@@ -263,6 +263,48 @@ pub fn test_raw_tty_cbreak() {
   }
 }
 
+#[cfg(unix)]
+#[test]
+pub fn test_cache_progress_cleared_before_logs() {
+  use std::io::Read;
+  use util::pty::fork::*;
+  let _g = util::http_server();
+  let deno_exe = util::deno_exe_path();
+  let root_path = util::root_path();
+  let deno_dir = TempDir::new().expect("tempdir fail");
+  let fork = Fork::from_ptmx().unwrap();
+
+  if let Ok(mut master) = fork.is_parent() {
+    let mut output = Vec::new();
+    let mut buf = [0; 1024];
+    // reading fails once the child exited and the pty is closed
+    while let Ok(nread) = master.read(&mut buf) {
+      if nread == 0 {
+        break;
+      }
+      output.extend_from_slice(&buf[..nread]);
+    }
+    fork.wait().unwrap();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("Progress"));
+    assert!(output.contains("Download"));
+    for (index, _) in output.match_indices("Download") {
+      assert!(output[..index].ends_with("\x1b[K"));
+    }
+  } else {
+    std::env::set_current_dir(root_path).unwrap();
+    std::env::set_var("DENO_DIR", deno_dir.path());
+    std::env::set_var("NO_COLOR", "1");
+    let err = exec::Command::new(deno_exe)
+      .arg("cache")
+      .arg("--reload")
+      .arg("http://localhost:4545/cli/tests/019_media_types.ts")
+      .exec();
+    println!("err {}", err);
+    unreachable!()
+  }
+}
+
 #[test]
 fn test_pattern_match() {
   // foo, bar, baz, qux, quux, quuz, corge, grault, garply, waldo, fred, plugh, xyzzy
@@ -1432,6 +1474,81 @@ fn info_with_compiled_source() {
   assert_eq!(output.stderr, b"");
 }
 
+#[test]
+fn info_json_dependencies() {
+  let _g = util::http_server();
+  let t = TempDir::new().expect("tempdir fail");
+  let deno_info = |args: &[&str]| {
+    let output = util::deno_cmd()
+      .env("DENO_DIR", t.path())
+      .current_dir(util::tests_path())
+      .arg("info")
+      .arg("--quiet")
+      .arg("--json")
+      .arg("--unstable")
+      .args(args)
+      .output()
+      .expect("failed to spawn script");
+    assert!(output.status.success());
+    let info: serde_json::Value =
+      serde_json::from_slice(&output.stdout).unwrap();
+    info
+  };
+  let get_dependency = |info: &serde_json::Value, file: &str, dep: &str| {
+    let (_, item) = info["files"]
+      .as_object()
+      .unwrap()
+      .iter()
+      .find(|(specifier, _)| specifier.ends_with(file))
+      .unwrap();
+    item["dependencies"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .find(|d| d["specifier"] == dep)
+      .unwrap()
+      .clone()
+  };
+
+  let info = deno_info(&[
+    "--import-map=import_maps/import_map.json",
+    "import_maps/test.ts",
+  ]);
+  let dep =
+    get_dependency(&info, "import_maps/test.ts", "moment/other_file.ts");
+  assert_eq!(dep["importMap"]["key"], "moment/");
+  assert!(dep["importMap"]["address"]
+    .as_str()
+    .unwrap()
+    .ends_with("import_maps/moment/"));
+  assert_eq!(dep["importMap"]["scope"], serde_json::Value::Null);
+  let dep = get_dependency(&info, "import_maps/scope/scoped.ts", "moment");
+  assert_eq!(dep["importMap"]["key"], "moment");
+  assert!(dep["code"]
+    .as_str()
+    .unwrap()
+    .ends_with("import_maps/scoped_moment.ts"));
+  assert!(dep["importMap"]["scope"]
+    .as_str()
+    .unwrap()
+    .ends_with("import_maps/scope/"));
+  let dep = get_dependency(&info, "import_maps/test.ts", "./scope/scoped.ts");
+  assert_eq!(dep["importMap"], serde_json::Value::Null);
+
+  let info = deno_info(&["info_redirects.ts"]);
+  let dep = get_dependency(
+    &info,
+    "info_redirects.ts",
+    "http://localhost:4546/cli/tests/subdir/redirects/redirect1.ts",
+  );
+  assert_eq!(
+    dep["redirects"],
+    serde_json::json!([
+      "http://localhost:4545/cli/tests/subdir/redirects/redirect1.ts"
+    ])
+  );
+}
+
 /// Helper function to skip watcher output that doesn't contain
 /// "{job_name} finished" phrase.
 fn wait_for_process_finished(
@@ -2566,6 +2683,16 @@ itest!(_076_info_json_deps_order {
   output: "076_info_json_deps_order.out",
 });
 
+itest!(_076_info_why {
+  args: "info --why recursive_imports/common.ts 076_info_json_deps_order.ts",
+  output: "076_info_why.out",
+});
+
+itest!(_076_info_why_json {
+  args: "info --unstable --json --why recursive_imports/C.ts 076_info_json_deps_order.ts",
+  output: "076_info_why_json.out",
+});
+
 itest!(js_import_detect {
   args: "run --quiet --reload js_import_detect.ts",
   output: "js_import_detect.ts.out",
@@ -2913,6 +3040,28 @@ itest!(if_main {
   output: "if_main.ts.out",
 });
 
+itest!(import_blob_url {
+  args: "run --quiet --reload import_blob_url.ts",
+  output: "import_blob_url.ts.out",
+});
+
+itest!(import_blob_url_imports_local {
+  args: "run --quiet --reload import_blob_url_imports_local.ts",
+  output: "import_blob_url_imports_local.ts.out",
+  exit_code: 1,
+});
+
+itest!(import_data_url {
+  args: "run --quiet --reload import_data_url.ts",
+  output: "import_data_url.ts.out",
+});
+
+itest!(import_data_url_imports_local {
+  args: "run --quiet --reload import_data_url_imports_local.ts",
+  output: "import_data_url_imports_local.ts.out",
+  exit_code: 1,
+});
+
 itest!(import_meta {
   args: "run --quiet --reload import_meta.ts",
   output: "import_meta.ts.out",
@@ -3184,6 +3333,13 @@ itest!(cache_random_extension {
   http_server: true,
 });
 
+itest!(cache_failures {
+  args: "cache --reload cache_failures.ts",
+  output: "cache_failures.out",
+  exit_code: 1,
+  http_server: true,
+});
+
 itest!(cafile_url_imports {
   args: "run --quiet --reload --cert tls/RootCA.pem cafile_url_imports.ts",
   output: "cafile_url_imports.ts.out",
@@ -3330,6 +3486,124 @@ itest!(deno_test_run_run_coverage {
   exit_code: 0,
 });
 
+#[test]
+fn deno_test_coverage_lcov_and_html() {
+  let tempdir = TempDir::new().expect("tempdir fail");
+  let lcov_path = tempdir.path().join("cov.lcov");
+  let status = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg("--unstable")
+    .arg(format!("--coverage={}", tempdir.path().display()))
+    .arg("--coverage-reporter=lcov")
+    .arg(format!("--coverage-output={}", lcov_path.display()))
+    .arg("test_coverage.ts")
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let lcov = std::fs::read_to_string(&lcov_path).unwrap();
+  assert!(lcov.contains("mod1.ts\n"));
+  // Line numbers refer to the TypeScript source, `returnsFoo2` is declared on
+  // line 7 of mod1.ts.
+  assert!(lcov.contains("FN:7,returnsFoo2\n"));
+  assert!(lcov.contains("FNDA:1,returnsFoo2\n"));
+  assert!(lcov.contains("FNDA:0,throwsError\n"));
+  assert!(lcov.contains("end_of_record\n"));
+
+  let html_dir = tempdir.path().join("html");
+  let status = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg("--unstable")
+    .arg(format!("--coverage={}", tempdir.path().display()))
+    .arg("--coverage-reporter=html")
+    .arg("test_coverage.ts")
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let index = std::fs::read_to_string(html_dir.join("index.html")).unwrap();
+  assert!(index.contains("mod1.ts"));
+}
+
+#[test]
+fn deno_coverage_merges_runs() {
+  let tempdir = TempDir::new().expect("tempdir fail");
+  for _ in 0..2 {
+    let status = util::deno_cmd()
+      .current_dir(util::tests_path())
+      .arg("test")
+      .arg("--quiet")
+      .arg("--unstable")
+      .arg(format!("--coverage={}", tempdir.path().display()))
+      .arg("test_coverage.ts")
+      .stdout(std::process::Stdio::null())
+      .spawn()
+      .unwrap()
+      .wait()
+      .unwrap();
+    assert!(status.success());
+  }
+
+  let output = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .env("NO_COLOR", "1")
+    .arg("coverage")
+    .arg("--unstable")
+    .arg(tempdir.path())
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  assert!(stdout.contains("subdir/mod1.ts ... lines "));
+  assert!(stdout.contains("functions 25.000% (1/4)"));
+  assert!(!stdout.contains("test_coverage.ts"));
+  assert!(stdout.contains(" files ... lines "));
+}
+
+#[test]
+fn deno_coverage_lcov_to_stdout() {
+  let tempdir = TempDir::new().expect("tempdir fail");
+  let status = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg("--unstable")
+    .arg(format!("--coverage={}", tempdir.path().display()))
+    .arg("test_coverage.ts")
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+
+  let output = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("coverage")
+    .arg("--unstable")
+    .arg("--reporter=lcov")
+    .arg(tempdir.path())
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout = String::from_utf8(output.stdout).unwrap();
+  assert!(stdout.contains("subdir/mod1.ts"));
+  assert!(stdout.contains("end_of_record"));
+  assert!(!tempdir.path().join("lcov.info").exists());
+}
+
 itest!(deno_lint {
   args: "lint --unstable lint/file1.js lint/file2.ts lint/ignored_file.ts",
   output: "lint/expected.out",
@@ -4686,7 +4960,7 @@ fn standalone_args() {
     .wait_with_output()
     .unwrap();
   assert!(output.status.success());
-  let output = Command::new(exe)
+  let output = Command::new(&exe)
     .arg("foo")
     .arg("--bar")
     .arg("--unstable")
@@ -4697,23 +4971,36 @@ fn standalone_args() {
     .unwrap();
   assert!(output.status.success());
   assert_eq!(output.stdout, b"foo\n--bar\n--unstable\n");
+
+  // only an executable compiled with --manifest handles --deno-manifest
+  let output = Command::new(exe)
+    .arg("--deno-manifest")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"--deno-manifest\n");
 }
 
 #[test]
-fn standalone_error() {
+fn standalone_runtime_flags() {
   let dir = TempDir::new().expect("tempdir fail");
   let exe = if cfg!(windows) {
-    dir.path().join("error.exe")
+    dir.path().join("flags.exe")
   } else {
-    dir.path().join("error")
+    dir.path().join("flags")
   };
   let output = util::deno_cmd()
     .current_dir(util::root_path())
     .arg("compile")
     .arg("--unstable")
+    .arg("--allow-read")
+    .arg("--seed=100")
     .arg("--output")
     .arg(&exe)
-    .arg("./cli/tests/standalone_error.ts")
+    .arg("./cli/tests/standalone_runtime_flags.ts")
     .stdout(std::process::Stdio::piped())
     .spawn()
     .unwrap()
@@ -4721,35 +5008,34 @@ fn standalone_error() {
     .unwrap();
   assert!(output.status.success());
   let output = Command::new(exe)
-    .env("NO_COLOR", "1")
+    .current_dir(dir.path())
     .stdout(std::process::Stdio::piped())
-    .stderr(std::process::Stdio::piped())
     .spawn()
     .unwrap()
     .wait_with_output()
     .unwrap();
-  assert!(!output.status.success());
-  assert_eq!(output.stdout, b"");
-  let expected_stderr = "error: Error: boom!\n    at boom (file://$deno$/bundle.js:2:11)\n    at foo (file://$deno$/bundle.js:5:5)\n    at file://$deno$/bundle.js:7:1\n";
-  let stderr = String::from_utf8(output.stderr).unwrap();
-  assert_eq!(stderr, expected_stderr);
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"0.858562739044346\ntrue\nfalse\n");
 }
 
 #[test]
-fn standalone_no_module_load() {
+fn standalone_runtime_unstable() {
   let dir = TempDir::new().expect("tempdir fail");
   let exe = if cfg!(windows) {
-    dir.path().join("hello.exe")
+    dir.path().join("unstable.exe")
   } else {
-    dir.path().join("hello")
+    dir.path().join("unstable")
   };
   let output = util::deno_cmd()
     .current_dir(util::root_path())
     .arg("compile")
     .arg("--unstable")
+    .arg("--runtime-unstable")
+    .arg("--allow-read")
+    .arg("--seed=100")
     .arg("--output")
     .arg(&exe)
-    .arg("./cli/tests/standalone_import.ts")
+    .arg("./cli/tests/standalone_runtime_flags.ts")
     .stdout(std::process::Stdio::piped())
     .spawn()
     .unwrap()
@@ -4757,17 +5043,705 @@ fn standalone_no_module_load() {
     .unwrap();
   assert!(output.status.success());
   let output = Command::new(exe)
+    .current_dir(dir.path())
     .stdout(std::process::Stdio::piped())
-    .stderr(std::process::Stdio::piped())
     .spawn()
     .unwrap()
     .wait_with_output()
     .unwrap();
-  assert!(!output.status.success());
-  assert_eq!(output.stdout, b"start\n");
-  let stderr_str = String::from_utf8(output.stderr).unwrap();
-  assert!(util::strip_ansi_codes(&stderr_str)
-    .contains("Self-contained binaries don't support module loading"));
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"0.858562739044346\ntrue\ntrue\n");
+}
+
+#[test]
+fn standalone_worker() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = if cfg!(windows) {
+    dir.path().join("worker.exe")
+  } else {
+    dir.path().join("worker")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--worker")
+    .arg("./cli/tests/standalone_worker_child.ts")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./cli/tests/standalone_worker.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"ping pong\n");
+}
+
+#[test]
+fn standalone_include() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let assets = dir.path().join("assets");
+  std::fs::create_dir(&assets).unwrap();
+  std::fs::write(assets.join("hello.txt"), "Hello from an asset").unwrap();
+  let main = dir.path().join("main.ts");
+  std::fs::write(
+    &main,
+    r#"console.log(await Deno.readTextFile(new URL("./assets/hello.txt", import.meta.url)));"#,
+  )
+  .unwrap();
+  let exe = if cfg!(windows) {
+    dir.path().join("include.exe")
+  } else {
+    dir.path().join("include")
+  };
+  let output = util::deno_cmd()
+    .current_dir(dir.path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--include")
+    .arg("assets")
+    .arg("--output")
+    .arg(&exe)
+    .arg("main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  // the asset is read from the binary, not from disk
+  std::fs::remove_dir_all(&assets).unwrap();
+  let output = Command::new(exe)
+    .current_dir(dir.path())
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"Hello from an asset\n");
+}
+
+#[test]
+fn standalone_include_other_cwd() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let project = dir.path().join("project");
+  let assets = project.join("templates");
+  std::fs::create_dir_all(&assets).unwrap();
+  std::fs::write(assets.join("x.json"), r#"{"hello":"world"}"#).unwrap();
+  std::fs::write(
+    project.join("main.ts"),
+    r#"console.log(await Deno.readTextFile("templates/x.json"));
+console.log(await Deno.readTextFile(new URL("./templates/x.json", import.meta.url)));"#,
+  )
+  .unwrap();
+  let exe = if cfg!(windows) {
+    dir.path().join("include.exe")
+  } else {
+    dir.path().join("include")
+  };
+  let output = util::deno_cmd()
+    .current_dir(&project)
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--include")
+    .arg("templates")
+    .arg("--output")
+    .arg(&exe)
+    .arg("main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  // the binary runs without the project, from another directory
+  std::fs::remove_dir_all(&project).unwrap();
+  let other_cwd = dir.path().join("elsewhere");
+  std::fs::create_dir(&other_cwd).unwrap();
+  let output = Command::new(exe)
+    .current_dir(&other_cwd)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(
+    output.stdout,
+    b"{\"hello\":\"world\"}\n{\"hello\":\"world\"}\n"
+  );
+}
+
+#[test]
+fn standalone_error() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = if cfg!(windows) {
+    dir.path().join("error.exe")
+  } else {
+    dir.path().join("error")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./cli/tests/standalone_error.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  assert_eq!(output.stdout, b"");
+  let specifier =
+    url::Url::from_file_path(util::tests_path().join("standalone_error.ts"))
+      .unwrap();
+  let expected_stderr = format!("error: Error: boom!\n    at boom ({0}:2:9)\n    at foo ({0}:6:3)\n    at {0}:9:1\n", specifier);
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert_eq!(stderr, expected_stderr);
+}
+
+#[test]
+fn standalone_dynamic_import() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = if cfg!(windows) {
+    dir.path().join("hello.exe")
+  } else {
+    dir.path().join("hello")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./cli/tests/standalone_import.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"start\nHello World\n");
+}
+
+#[test]
+fn standalone_no_module_load() {
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = if cfg!(windows) {
+    dir.path().join("hello.exe")
+  } else {
+    dir.path().join("hello")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./cli/tests/standalone_import_computed.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  assert_eq!(output.stdout, b"start\n");
+  let stderr_str = String::from_utf8(output.stderr).unwrap();
+  assert!(util::strip_ansi_codes(&stderr_str)
+    .contains("Module not found in the self-contained binary"));
+}
+
+// The runtime binaries of the targets are only named after Unix targets.
+#[cfg(unix)]
+#[test]
+fn compile_with_target() {
+  let deno_dir = TempDir::new().expect("tempdir fail");
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = dir.path().join("welcome");
+  let target = if env!("TARGET") == "x86_64-unknown-linux-musl" {
+    "x86_64-unknown-linux-gnu"
+  } else {
+    "x86_64-unknown-linux-musl"
+  };
+
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .env("DENO_DIR", deno_dir.path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--target")
+    .arg(target)
+    .arg("--output")
+    .arg(&exe)
+    .arg("./std/examples/welcome.ts")
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert!(stderr.contains(&format!(
+    "Could not compile: no runtime binary for target \"{}\" found",
+    target
+  )));
+
+  // Any binary of the same version can be used as the runtime of a target,
+  // so the runtime cache is populated with the binary under test.
+  let output = util::deno_cmd()
+    .arg("eval")
+    .arg("console.log(Deno.version.deno)")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  let version = String::from_utf8(output.stdout).unwrap();
+  let runtime_dir = deno_dir.path().join("dl");
+  std::fs::create_dir_all(&runtime_dir).unwrap();
+  std::fs::copy(
+    util::deno_exe_path(),
+    runtime_dir.join(format!("denort-{}-{}", version.trim(), target)),
+  )
+  .unwrap();
+
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .env("DENO_DIR", deno_dir.path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--target")
+    .arg(target)
+    .arg("--output")
+    .arg(&exe)
+    .arg("./std/examples/welcome.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, "Welcome to Deno 🦕\n".as_bytes());
+}
+
+#[test]
+fn standalone_manifest() {
+  let _g = util::http_server();
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = if cfg!(windows) {
+    dir.path().join("manifest.exe")
+  } else {
+    dir.path().join("manifest")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--manifest")
+    .arg("--lock=cli/tests/lock_check_ok.json")
+    .arg("--output")
+    .arg(&exe)
+    .arg("http://127.0.0.1:4545/cli/tests/003_relative_import.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(&exe)
+    .arg("--deno-manifest")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let manifest: serde_json::Value =
+    serde_json::from_slice(&output.stdout).unwrap();
+  let lockfile: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(util::tests_path().join("lock_check_ok.json"))
+      .unwrap(),
+  )
+  .unwrap();
+  assert_eq!(manifest["modules"], lockfile);
+  assert_eq!(manifest["importMap"], serde_json::Value::Null);
+
+  // without the flag the program runs as usual
+  let output = Command::new(&exe)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"Hello\n");
+}
+
+#[test]
+fn compile_lock_check_err() {
+  let _g = util::http_server();
+  let dir = TempDir::new().expect("tempdir fail");
+  let exe = dir.path().join("lock_check_err");
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--unstable")
+    .arg("--lock=cli/tests/lock_check_err.json")
+    .arg("--output")
+    .arg(&exe)
+    .arg("http://127.0.0.1:4545/cli/tests/003_relative_import.ts")
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert_eq!(output.status.code(), Some(10));
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert!(stderr.contains(
+    "The source code is invalid, as it does not match the expected hash in the lock file."
+  ));
+  assert!(!exe.exists());
+}
+
+#[test]
+fn bundle_lock_write() {
+  let _g = util::http_server();
+  let dir = TempDir::new().expect("tempdir fail");
+  let lockfile = dir.path().join("lock.json");
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("bundle")
+    .arg("--lock-write")
+    .arg("--lock")
+    .arg(&lockfile)
+    .arg("http://127.0.0.1:4545/cli/tests/003_relative_import.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let actual: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&lockfile).unwrap()).unwrap();
+  let expected: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(util::tests_path().join("lock_check_ok.json"))
+      .unwrap(),
+  )
+  .unwrap();
+  assert_eq!(actual["version"], "2");
+  assert_eq!(actual["remote"], expected);
+}
+
+#[test]
+fn cache_lock_update_and_prune() {
+  let _g = util::http_server();
+  let dir = TempDir::new().expect("tempdir fail");
+  let lockfile = dir.path().join("lock.json");
+  let mut lock: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(util::tests_path().join("lock_check_ok.json"))
+      .unwrap(),
+  )
+  .unwrap();
+  lock["http://127.0.0.1:4545/cli/tests/unused.ts"] = serde_json::json!(
+    "fe7bbccaedb6579200a8b582f905139296402d06b1b91109d6e12c41a23125da"
+  );
+  std::fs::write(&lockfile, lock.to_string()).unwrap();
+
+  let cache = |prune: bool| {
+    let mut cmd = util::deno_cmd();
+    cmd
+      .current_dir(util::root_path())
+      .env("NO_COLOR", "1")
+      .arg("cache")
+      .arg("--lock")
+      .arg(&lockfile)
+      .arg("--lock-update");
+    if prune {
+      cmd.arg("--lock-prune");
+    }
+    let output = cmd
+      .arg("http://127.0.0.1:4545/cli/tests/003_relative_import.ts")
+      .stderr(std::process::Stdio::piped())
+      .spawn()
+      .unwrap()
+      .wait_with_output()
+      .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let actual: serde_json::Value =
+      serde_json::from_str(&std::fs::read_to_string(&lockfile).unwrap())
+        .unwrap();
+    (stderr, actual)
+  };
+
+  let (stderr, actual) = cache(false);
+  assert!(!stderr.contains("Remove"));
+  assert_eq!(actual["version"], "2");
+  assert_eq!(actual["remote"], lock);
+
+  let (stderr, actual) = cache(true);
+  assert!(stderr.contains("Remove http://127.0.0.1:4545/cli/tests/unused.ts"));
+  lock
+    .as_object_mut()
+    .unwrap()
+    .remove("http://127.0.0.1:4545/cli/tests/unused.ts");
+  assert_eq!(actual["remote"], lock);
+}
+
+#[test]
+fn run_with_auth_tokens() {
+  let _g = util::http_server();
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .env("DENO_AUTH_TOKENS", "abcdef123456789@localhost:4545")
+    .arg("run")
+    .arg("--reload")
+    .arg("http://localhost:4545/cli/tests/subdir/auth_required.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout = std::str::from_utf8(&output.stdout).unwrap().trim();
+  assert_eq!(stdout, "Hello from a private host");
+}
+
+#[test]
+fn outdated_check_and_update() {
+  let _g = util::http_server();
+  let t = TempDir::new().expect("tempdir fail");
+  let fixtures = util::tests_path().join("outdated");
+  for file in &["mod.ts", "deps.ts", "import_map.json"] {
+    std::fs::copy(fixtures.join(file), t.path().join(file)).unwrap();
+  }
+  let outdated = |update: bool| {
+    let mut cmd = util::deno_cmd();
+    cmd
+      .current_dir(t.path())
+      .env("NO_COLOR", "1")
+      .arg("outdated")
+      .arg("--unstable")
+      .arg("--import-map=import_map.json")
+      .arg("--registry-api=http://localhost:4545/cli/tests/outdated/registry");
+    if update {
+      cmd.arg("--update");
+    }
+    let output = cmd.output().expect("failed to spawn script");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+  };
+
+  let stdout = outdated(false);
+  assert!(stdout.contains("std 0.50.0 → 0.84.0"));
+  assert!(stdout.contains("std 0.83.0 → 0.84.0"));
+  assert!(!stdout.contains("oak"));
+  assert!(stdout.contains("Run with --update to update them."));
+  let mod_ts = std::fs::read_to_string(t.path().join("mod.ts")).unwrap();
+  assert!(mod_ts.contains("https://deno.land/std@0.50.0/fs/mod.ts"));
+
+  let stdout = outdated(true);
+  assert!(stdout.contains("Updated 3 files"));
+  let mod_ts = std::fs::read_to_string(t.path().join("mod.ts")).unwrap();
+  assert!(mod_ts.contains("\"https://deno.land/std@0.84.0/fs/mod.ts\""));
+  assert!(mod_ts.contains("\"https://deno.land/x/oak@v6.4.1/mod.ts\""));
+  let deps_ts = std::fs::read_to_string(t.path().join("deps.ts")).unwrap();
+  assert!(deps_ts.contains("\"https://deno.land/std@0.84.0/http/server.ts\""));
+  let import_map =
+    std::fs::read_to_string(t.path().join("import_map.json")).unwrap();
+  assert!(import_map.contains("\"https://deno.land/std@0.84.0/\""));
+
+  let stdout = outdated(false);
+  assert!(stdout.contains("All versioned dependencies are up to date."));
+}
+
+#[test]
+fn gc_list_and_collect() {
+  let _g = util::http_server();
+  let deno_dir = TempDir::new().expect("tempdir fail");
+  let deno = |args: &[&str]| {
+    let output = util::deno_cmd()
+      .current_dir(util::root_path())
+      .env("DENO_DIR", deno_dir.path())
+      .env("NO_COLOR", "1")
+      .args(args)
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::piped())
+      .spawn()
+      .unwrap()
+      .wait_with_output()
+      .unwrap();
+    assert!(output.status.success());
+    (
+      String::from_utf8(output.stdout).unwrap(),
+      String::from_utf8(output.stderr).unwrap(),
+    )
+  };
+  let list = || {
+    let (stdout, _) = deno(&["gc", "--list", "--json"]);
+    let entries: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    entries
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|e| e["url"].as_str().unwrap().to_string())
+      .collect::<Vec<_>>()
+  };
+
+  deno(&[
+    "cache",
+    "http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+  ]);
+  deno(&[
+    "cache",
+    "http://127.0.0.1:4545/cli/tests/subdir/mt_text_ecmascript.j3.js",
+  ]);
+  assert_eq!(
+    list(),
+    vec![
+      "http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+      "http://127.0.0.1:4545/cli/tests/subdir/mt_text_ecmascript.j3.js",
+      "http://127.0.0.1:4545/cli/tests/subdir/print_hello.ts",
+    ]
+  );
+
+  let (_, stderr) = deno(&[
+    "gc",
+    "--dry-run",
+    "http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+  ]);
+  assert!(stderr.contains(
+    "Would remove http://127.0.0.1:4545/cli/tests/subdir/mt_text_ecmascript.j3.js"
+  ));
+  assert_eq!(list().len(), 3);
+
+  let (_, stderr) = deno(&[
+    "gc",
+    "http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+  ]);
+  assert!(stderr.contains(
+    "Remove http://127.0.0.1:4545/cli/tests/subdir/mt_text_ecmascript.j3.js"
+  ));
+  assert_eq!(
+    list(),
+    vec![
+      "http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+      "http://127.0.0.1:4545/cli/tests/subdir/print_hello.ts",
+    ]
+  );
+}
+
+#[test]
+fn vendor_and_run_offline() {
+  let _g = util::http_server();
+  let dir = TempDir::new().expect("tempdir fail");
+  let output_dir = dir.path().join("vendor");
+  let vendor = || {
+    util::deno_cmd()
+      .current_dir(util::root_path())
+      .arg("vendor")
+      .arg("--output")
+      .arg(&output_dir)
+      .arg("./cli/tests/vendor/main.ts")
+      .stderr(std::process::Stdio::piped())
+      .spawn()
+      .unwrap()
+      .wait_with_output()
+      .unwrap()
+  };
+  let output = vendor();
+  assert!(output.status.success());
+  assert!(output_dir
+    .join("localhost_4545/cli/tests/subdir/no_js_ext@1.0.0.js")
+    .exists());
+  assert!(output_dir
+    .join("localhost_4545/cli/tests/subdir/print_hello.ts")
+    .exists());
+  let import_map: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(output_dir.join("import_map.json")).unwrap(),
+  )
+  .unwrap();
+  assert_eq!(
+    import_map["imports"]["http://localhost:4545/"],
+    "./localhost_4545/"
+  );
+  assert_eq!(
+    import_map["imports"]
+      ["http://localhost:4545/cli/tests/subdir/no_js_ext@1.0.0"],
+    "./localhost_4545/cli/tests/subdir/no_js_ext@1.0.0.js"
+  );
+  assert_eq!(
+    import_map["imports"]
+      ["http://localhost:4546/cli/tests/subdir/redirects/redirect1.js"],
+    "./localhost_4545/cli/tests/subdir/redirects/redirect1.js"
+  );
+
+  // the vendored modules are loaded without a cache or network access
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .env("DENO_DIR", dir.path().join("deno_dir"))
+    .arg("run")
+    .arg("--unstable")
+    .arg("--no-remote")
+    .arg("--import-map")
+    .arg(output_dir.join("import_map.json"))
+    .arg("./cli/tests/vendor/main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "Hello\n1\n");
+
+  let output = vendor();
+  assert!(!output.status.success());
+  let stderr = std::str::from_utf8(&output.stderr).unwrap();
+  assert!(stderr.contains("is not empty"));
 }
 
 #[test]
@@ -0,0 +1 @@
+export { serve } from "https://deno.land/std@0.83.0/http/server.ts";
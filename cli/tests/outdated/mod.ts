@@ -0,0 +1,5 @@
+import { ensureDir } from "https://deno.land/std@0.50.0/fs/mod.ts";
+import { Application } from "https://deno.land/x/oak@v6.4.1/mod.ts";
+import { serve } from "./deps.ts";
+
+console.log(ensureDir, Application, serve);
@@ -0,0 +1,3 @@
+console.log("start");
+const specifier = ["./001_hello", "js"].join(".");
+await import(specifier);
@@ -0,0 +1,8 @@
+console.log(Math.random());
+await Deno.stat(".");
+try {
+  await Deno.writeTextFile("./hello.txt", "");
+} catch (error) {
+  console.log(error instanceof Deno.errors.PermissionDenied);
+}
+console.log("permissions" in Deno);
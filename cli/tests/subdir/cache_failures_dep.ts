@@ -0,0 +1 @@
+import "http://localhost:4545/cli/tests/subdir/also_does_not_exist.ts";
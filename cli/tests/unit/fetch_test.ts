@@ -1106,3 +1106,70 @@ unitTest(
     assertEquals(actual, expected);
   },
 );
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function fetchCustomHttpClientHttp2(): Promise<void> {
+    const client = Deno.createHttpClient(
+      { caFile: "./cli/tests/tls/RootCA.crt" },
+    );
+    // The server selects HTTP/2 with ALPN.
+    const response = await fetch("https://localhost:5546/http_version", {
+      client,
+    });
+    assertEquals(await response.text(), "HTTP/2.0");
+    client.close();
+  },
+);
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function fetchCustomHttpClientHttp1Only(): Promise<void> {
+    const client = Deno.createHttpClient(
+      { caFile: "./cli/tests/tls/RootCA.crt", http2: false },
+    );
+    const response = await fetch("https://localhost:5546/http_version", {
+      client,
+    });
+    assertEquals(await response.text(), "HTTP/1.1");
+    client.close();
+  },
+);
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function fetchCustomHttpClientClientCertificate(): Promise<void> {
+    const promise = (async () => {
+      const listener = Deno.listenTls({
+        port: 4503,
+        certFile: "cli/tests/tls/localhost.crt",
+        keyFile: "cli/tests/tls/localhost.key",
+        clientAuth: "required",
+        clientCaFile: "cli/tests/tls/RootCA.pem",
+      });
+      const conn = await listener.accept();
+      listener.close();
+      assertEquals(conn.peerCertificates?.length, 1);
+      const httpConn = Deno.serveHttp(conn);
+      const requestEvent = await httpConn.nextRequest();
+      assert(requestEvent);
+      await requestEvent.respondWith(new Response("Hello World"));
+      assertEquals(await httpConn.nextRequest(), null);
+      httpConn.close();
+    })();
+
+    const client = Deno.createHttpClient({
+      caFile: "cli/tests/tls/RootCA.pem",
+      clientCertFile: "cli/tests/tls/localhost.crt",
+      clientKeyFile: "cli/tests/tls/localhost.key",
+      http2: false,
+    });
+    const response = await fetch("https://localhost:4503/", {
+      client,
+      headers: { "connection": "close" },
+    });
+    assertEquals(await response.text(), "Hello World");
+    client.close();
+    await promise;
+  },
+);
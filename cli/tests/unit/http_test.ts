@@ -0,0 +1,133 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, unitTest } from "./test_util.ts";
+
+unitTest({ perms: { net: true } }, async function httpServerBasic() {
+  const promise = (async () => {
+    const listener = Deno.listen({ port: 4501 });
+    const conn = await listener.accept();
+    listener.close();
+    const httpConn = Deno.serveHttp(conn);
+    const requestEvent = await httpConn.nextRequest();
+    assert(requestEvent);
+    const { request, respondWith } = requestEvent;
+    assertEquals(request.url, "http://127.0.0.1:4501/foo?bar=baz");
+    assertEquals(request.method, "GET");
+    assertEquals(request.headers.get("x-foo"), "bar");
+    assertEquals(await request.text(), "");
+    await respondWith(
+      new Response("Hello World", { headers: { "x-bar": "foo" } }),
+    );
+    // The client asked to close the connection after the response.
+    assertEquals(await httpConn.nextRequest(), null);
+    httpConn.close();
+  })();
+
+  const resp = await fetch("http://127.0.0.1:4501/foo?bar=baz", {
+    headers: { "connection": "close", "x-foo": "bar" },
+  });
+  assertEquals(resp.status, 200);
+  assertEquals(resp.headers.get("x-bar"), "foo");
+  assertEquals(await resp.text(), "Hello World");
+  await promise;
+});
+
+unitTest({ perms: { net: true } }, async function httpServerStreamResponse() {
+  const encoder = new TextEncoder();
+  const stream = new ReadableStream({
+    start(controller) {
+      controller.enqueue(encoder.encode("hello "));
+      controller.enqueue(encoder.encode("world"));
+      controller.close();
+    },
+  });
+
+  const promise = (async () => {
+    const listener = Deno.listen({ port: 4501 });
+    const conn = await listener.accept();
+    listener.close();
+    const httpConn = Deno.serveHttp(conn);
+    const requestEvent = await httpConn.nextRequest();
+    assert(requestEvent);
+    await requestEvent.respondWith(new Response(stream));
+    assertEquals(await httpConn.nextRequest(), null);
+    httpConn.close();
+  })();
+
+  const resp = await fetch("http://127.0.0.1:4501/", {
+    headers: { "connection": "close" },
+  });
+  assertEquals(resp.headers.get("transfer-encoding"), "chunked");
+  assertEquals(await resp.text(), "hello world");
+  await promise;
+});
+
+unitTest({ perms: { net: true } }, async function httpServerStreamRequest() {
+  const promise = (async () => {
+    const listener = Deno.listen({ port: 4501 });
+    const conn = await listener.accept();
+    listener.close();
+    const httpConn = Deno.serveHttp(conn);
+    const requestEvent = await httpConn.nextRequest();
+    assert(requestEvent);
+    const { request, respondWith } = requestEvent;
+    assertEquals(request.method, "POST");
+    assertEquals(await request.text(), "Hello World");
+    await respondWith(new Response(""));
+    assertEquals(await httpConn.nextRequest(), null);
+    httpConn.close();
+  })();
+
+  const resp = await fetch("http://127.0.0.1:4501/", {
+    method: "POST",
+    headers: { "connection": "close" },
+    body: "Hello World",
+  });
+  assertEquals(resp.status, 200);
+  assertEquals(await resp.text(), "");
+  await promise;
+});
+
+unitTest({ perms: { net: true } }, async function httpServerClose() {
+  const listener = Deno.listen({ port: 4501 });
+  const client = await Deno.connect({ port: 4501 });
+  const conn = await listener.accept();
+  const httpConn = Deno.serveHttp(conn);
+  const promise = httpConn.nextRequest();
+  httpConn.close();
+  assertEquals(await promise, null);
+  client.close();
+  listener.close();
+});
+
+unitTest(
+  { perms: { net: true, read: true } },
+  async function httpServerH2() {
+    const promise = (async () => {
+      const listener = Deno.listenTls({
+        port: 4503,
+        certFile: "cli/tests/tls/localhost.crt",
+        keyFile: "cli/tests/tls/localhost.key",
+        alpnProtocols: ["h2", "http/1.1"],
+      });
+      const conn = await listener.accept();
+      listener.close();
+      assertEquals(conn.alpnProtocol, "h2");
+      const httpConn = Deno.serveHttp(conn);
+      const requestEvent = await httpConn.nextRequest();
+      assert(requestEvent);
+      assertEquals(requestEvent.request.url, "https://localhost:4503/");
+      await requestEvent.respondWith(new Response("Hello World"));
+      // The connection ends once the client is closed.
+      assertEquals(await httpConn.nextRequest(), null);
+      httpConn.close();
+    })();
+
+    const client = Deno.createHttpClient({
+      caFile: "cli/tests/tls/RootCA.pem",
+    });
+    const resp = await fetch("https://localhost:4503/", { client });
+    assertEquals(await resp.text(), "Hello World");
+    client.close();
+    await promise;
+  },
+);
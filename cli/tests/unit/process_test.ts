@@ -4,6 +4,7 @@ import {
   assertEquals,
   assertStringIncludes,
   assertThrows,
+  assertThrowsAsync,
   unitTest,
 } from "./test_util.ts";
 
@@ -506,3 +507,134 @@ unitTest({ perms: { run: true, read: true } }, function killFailed(): void {
 
   p.close();
 });
+
+unitTest(
+  {
+    // No `env` command on windows.
+    ignore: Deno.build.os === "windows",
+    perms: { run: true },
+  },
+  async function runClearEnv(): Promise<void> {
+    const p = Deno.run({
+      cmd: ["env"],
+      clearEnv: true,
+      env: { FOO: "bar" },
+      stdout: "piped",
+    });
+    const output = await p.output();
+    assertEquals(new TextDecoder().decode(output), "FOO=bar\n");
+    p.close();
+  },
+);
+
+unitTest(
+  {
+    // No signals on windows.
+    ignore: Deno.build.os === "windows",
+    perms: { run: true, read: true },
+  },
+  async function runNewProcessGroup(): Promise<void> {
+    const p = Deno.run({
+      cmd: [Deno.execPath(), "eval", "setTimeout(() => {}, 10000)"],
+      newProcessGroup: true,
+    });
+
+    // The child is the leader of its own process group.
+    Deno.kill(-p.pid, Deno.Signal.SIGTERM);
+    const status = await p.status();
+    assertEquals(status.success, false);
+    assertEquals(status.signal, Deno.Signal.SIGTERM);
+    p.close();
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  function runNewSessionAndProcessGroup(): void {
+    assertThrows(() => {
+      Deno.run({
+        cmd: [Deno.execPath(), "eval", "console.log('hello world')"],
+        newProcessGroup: true,
+        newSession: true,
+      });
+    });
+  },
+);
+
+unitTest(
+  {
+    // Sockets can't be passed as stdio on windows.
+    ignore: Deno.build.os === "windows",
+    perms: { run: true, read: true, net: true },
+  },
+  async function runStdoutSocket(): Promise<void> {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 4502 });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connect({ hostname: "127.0.0.1", port: 4502 }),
+    ]);
+
+    const p = Deno.run({
+      cmd: [
+        Deno.execPath(),
+        "eval",
+        "Deno.stdout.writeSync(new TextEncoder().encode('hello'))",
+      ],
+      stdout: clientConn.rid,
+    });
+    const status = await p.status();
+    assertEquals(status.code, 0);
+    p.close();
+
+    // The child wrote to a duplicate of the socket, which is still open here.
+    clientConn.close();
+    const output = await Deno.readAll(serverConn);
+    assertEquals(new TextDecoder().decode(output), "hello");
+    serverConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function outputConcurrent(): Promise<void> {
+    // More than fits into the buffer of a pipe, which would block the child
+    // if the outputs were read one after the other.
+    const { status, stdout, stderr } = await Deno.output({
+      cmd: [
+        Deno.execPath(),
+        "eval",
+        `
+        const buf = new Uint8Array(256 * 1024);
+        Deno.writeAllSync(Deno.stderr, buf);
+        Deno.writeAllSync(Deno.stdout, buf);
+        `,
+      ],
+    });
+    assert(status.success);
+    assertEquals(stdout?.length, 256 * 1024);
+    assertEquals(stderr?.length, 256 * 1024);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function outputNotPiped(): Promise<void> {
+    const { status, stdout, stderr } = await Deno.output({
+      cmd: [Deno.execPath(), "eval", "console.log('hello world')"],
+      stdout: "null",
+    });
+    assert(status.success);
+    assertEquals(stdout, null);
+    assertEquals(stderr?.length, 0);
+  },
+);
+
+unitTest(
+  { perms: { run: true, read: true } },
+  async function outputNotFound(): Promise<void> {
+    await assertThrowsAsync(async () => {
+      await Deno.output({ cmd: ["this file hopefully doesn't exist"] });
+    }, Deno.errors.NotFound);
+  },
+);
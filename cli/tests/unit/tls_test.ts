@@ -232,3 +232,307 @@ unitTest(
     conn.close();
   },
 );
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsAlpnNegotiation(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2", "http/1.1"],
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        certFile: "cli/tests/tls/RootCA.pem",
+        alpnProtocols: ["foo", "http/1.1"],
+      }),
+    ]);
+    assertEquals(serverConn.alpnProtocol, "http/1.1");
+    assertEquals(clientConn.alpnProtocol, "http/1.1");
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsAlpnNoProtocols(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      alpnProtocols: ["h2"],
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        certFile: "cli/tests/tls/RootCA.pem",
+      }),
+    ]);
+    assertEquals(serverConn.alpnProtocol, null);
+    assertEquals(clientConn.alpnProtocol, null);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientAuthRequired(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientAuth: "required",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        certFile: "cli/tests/tls/RootCA.pem",
+        clientCertFile: "cli/tests/tls/localhost.crt",
+        clientKeyFile: "cli/tests/tls/localhost.key",
+      }),
+    ]);
+    // Both peers present the same certificate.
+    assert(serverConn.peerCertificates);
+    assertEquals(serverConn.peerCertificates.length, 1);
+    assertEquals(clientConn.peerCertificates, serverConn.peerCertificates);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientAuthRequiredNoCertificate(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientAuth: "required",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+    });
+    const acceptPromise = listener.accept();
+    const clientConn = await Deno.connectTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/RootCA.pem",
+    }).catch(() => null);
+    await assertThrowsAsync(() => acceptPromise);
+
+    clientConn?.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientAuthOptional(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientAuth: "optional",
+      clientCaFile: "cli/tests/tls/RootCA.pem",
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        certFile: "cli/tests/tls/RootCA.pem",
+      }),
+    ]);
+    assertEquals(serverConn.peerCertificates, null);
+    assertEquals(clientConn.peerCertificates?.length, 1);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  function tlsClientAuthNoCaFile(): void {
+    assertThrows(() => {
+      Deno.listenTls({
+        hostname: "localhost",
+        port: 3500,
+        certFile: "cli/tests/tls/localhost.crt",
+        keyFile: "cli/tests/tls/localhost.key",
+        clientAuth: "required",
+      });
+    }, TypeError);
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function connectTlsClientCertWithoutKey(): Promise<void> {
+    await assertThrowsAsync(async () => {
+      await Deno.connectTls({
+        hostname: "localhost",
+        port: 3500,
+        clientCertFile: "cli/tests/tls/localhost.crt",
+      });
+    }, TypeError);
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function listenTlsCertChain(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certChain: await Deno.readTextFile("cli/tests/tls/localhost.crt"),
+      privateKey: await Deno.readTextFile("cli/tests/tls/localhost.key"),
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        caCerts: [await Deno.readTextFile("cli/tests/tls/RootCA.pem")],
+      }),
+    ]);
+    assertEquals(clientConn.peerCertificates?.length, 1);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsClientAuthCertChain(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+      clientAuth: "required",
+      clientCaCerts: [await Deno.readTextFile("cli/tests/tls/RootCA.pem")],
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({
+        hostname,
+        port,
+        certFile: "cli/tests/tls/RootCA.pem",
+        certChain: await Deno.readTextFile("cli/tests/tls/localhost.crt"),
+        privateKey: await Deno.readTextFile("cli/tests/tls/localhost.key"),
+      }),
+    ]);
+    assertEquals(serverConn.peerCertificates?.length, 1);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
+
+unitTest(
+  { perms: { net: true } },
+  function listenTlsInvalidCertChain(): void {
+    assertThrows(() => {
+      Deno.listenTls({
+        hostname: "localhost",
+        port: 3500,
+        certChain: "not a certificate",
+        privateKey: "not a key",
+      });
+    }, Deno.errors.InvalidData);
+  },
+);
+
+unitTest(
+  { perms: { read: true, net: true } },
+  async function tlsListenerReloadCertificates(): Promise<void> {
+    const hostname = "localhost";
+    const port = 3500;
+    const certFile = "cli/tests/tls/RootCA.pem";
+
+    const listener = Deno.listenTls({
+      hostname,
+      port,
+      certFile: "cli/tests/tls/localhost.crt",
+      keyFile: "cli/tests/tls/localhost.key",
+    });
+
+    // A CA certificate isn't accepted as the certificate of a server.
+    listener.reloadCertificates({
+      certFile: "cli/tests/tls/RootCA.crt",
+      keyFile: "cli/tests/tls/RootCA.key",
+    });
+    const acceptPromise = listener.accept().catch(() => null);
+    await assertThrowsAsync(async () => {
+      await Deno.connectTls({ hostname, port, certFile });
+    });
+    (await acceptPromise)?.close();
+
+    assertThrows(() => {
+      listener.reloadCertificates({ certChain: "", privateKey: "" });
+    }, Deno.errors.InvalidData);
+    assertThrows(() => {
+      listener.reloadCertificates({ certFile: "cli/tests/tls/localhost.crt" });
+    }, TypeError);
+
+    listener.reloadCertificates({
+      certChain: await Deno.readTextFile("cli/tests/tls/localhost.crt"),
+      privateKey: await Deno.readTextFile("cli/tests/tls/localhost.key"),
+    });
+    const [serverConn, clientConn] = await Promise.all([
+      listener.accept(),
+      Deno.connectTls({ hostname, port, certFile }),
+    ]);
+    assertEquals(clientConn.peerCertificates?.length, 1);
+
+    serverConn.close();
+    clientConn.close();
+    listener.close();
+  },
+);
@@ -1,5 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { assert, assertEquals, assertThrows, unitTest } from "./test_util.ts";
+import {
+  assert,
+  assertEquals,
+  assertMatch,
+  assertThrows,
+  assertThrowsAsync,
+  unitTest,
+} from "./test_util.ts";
 
 unitTest(function urlParsing(): void {
   const url = new URL(
@@ -500,3 +507,39 @@ unitTest(function emptyPortForSchemeDefaultPort(): void {
     assertEquals(url.port, "");
   }
 });
+
+unitTest(async function urlCreateObjectURL(): Promise<void> {
+  const blob = new Blob(["export const a = 1;"], {
+    type: "application/javascript",
+  });
+  const url = URL.createObjectURL(blob);
+  assertMatch(
+    url,
+    /^blob:null\/[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/,
+  );
+  assert(url !== URL.createObjectURL(blob));
+  const { a } = await import(url);
+  assertEquals(a, 1);
+  URL.revokeObjectURL(url);
+});
+
+unitTest(async function urlRevokeObjectURL(): Promise<void> {
+  const blob = new Blob(["export const b = 2;"], {
+    type: "application/javascript",
+  });
+  const url = URL.createObjectURL(blob);
+  URL.revokeObjectURL(url);
+  await assertThrowsAsync(async () => {
+    await import(url);
+  }, TypeError, "Blob URL not found");
+  // revoking an unknown or invalid URL is a no-op
+  URL.revokeObjectURL(url);
+  URL.revokeObjectURL("not a url");
+});
+
+unitTest(function urlCreateObjectURLNotBlob(): void {
+  assertThrows(() => {
+    // @ts-expect-error
+    URL.createObjectURL("export const c = 3;");
+  }, TypeError);
+});
@@ -1,14 +1,23 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
+use crate::checksum;
 use crate::colors;
+use crate::flags::CoverageReporterKind;
+use crate::program_state::ProgramState;
+use crate::source_maps::get_inline_source_map;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
 use deno_runtime::inspector::InspectorSession;
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
+use sourcemap::SourceMap;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -93,7 +102,7 @@ impl CoverageCollector {
 
 // TODO(caspervonb) all of these structs can and should be made private, possibly moved to
 // inspector::protocol.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoverageRange {
   pub start_offset: usize,
@@ -101,7 +110,7 @@ pub struct CoverageRange {
   pub count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FunctionCoverage {
   pub function_name: String,
@@ -109,7 +118,7 @@ pub struct FunctionCoverage {
   pub is_block_coverage: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ScriptCoverage {
   pub script_id: String,
@@ -117,7 +126,7 @@ pub struct ScriptCoverage {
   pub functions: Vec<FunctionCoverage>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Coverage {
   pub script_coverage: ScriptCoverage,
@@ -137,21 +146,275 @@ pub struct GetScriptSourceResult {
   pub bytecode: Option<String>,
 }
 
+/// Execution counts of a single module, expressed in terms of the original
+/// source when the emitted code carries an inline source map.
+pub struct FileCoverage {
+  pub url: String,
+  pub lines: Vec<String>,
+  /// The execution count of each line, `None` for lines which do not contain
+  /// any executable code.
+  pub line_counts: Vec<Option<usize>>,
+  pub functions: Vec<FunctionCount>,
+  pub branches: Vec<BranchCount>,
+}
+
+pub struct FunctionCount {
+  pub name: String,
+  /// Zero based line of the function declaration.
+  pub line: usize,
+  pub count: usize,
+}
+
+/// A block range reported by V8 within a function, which is treated as a
+/// branch of that function.
+pub struct BranchCount {
+  /// Zero based line at which the block starts.
+  pub line: usize,
+  /// Index of the function the block belongs to.
+  pub block: usize,
+  /// Index of the block within its function.
+  pub branch: usize,
+  pub count: usize,
+}
+
+impl FileCoverage {
+  pub fn new(
+    coverage: &Coverage,
+    maybe_original_source: Option<String>,
+  ) -> Self {
+    let script_source = &coverage.script_source;
+    let line_offsets = get_line_offsets(script_source);
+    let generated_lines = script_source.split('\n').collect::<Vec<_>>();
+
+    let mut generated_counts = Vec::with_capacity(generated_lines.len());
+    for (index, line) in generated_lines.iter().enumerate() {
+      let trimmed = line.trim();
+      if trimmed.is_empty() || trimmed.starts_with("//# sourceMappingURL=") {
+        generated_counts.push(None);
+        continue;
+      }
+
+      let (line_start_offset, line_end_offset) = line_offsets[index];
+      generated_counts.push(Some(get_count_for_range(
+        coverage
+          .script_coverage
+          .functions
+          .iter()
+          .flat_map(|function| function.ranges.iter()),
+        line_start_offset,
+        line_end_offset,
+      )));
+    }
+
+    let mut functions = Vec::new();
+    let mut branches = Vec::new();
+    for (index, function) in
+      coverage.script_coverage.functions.iter().enumerate()
+    {
+      let range = match function.ranges.first() {
+        Some(range) => range,
+        None => continue,
+      };
+      // The first function is the module itself, which is not interesting to
+      // report on.
+      if index == 0 && range.start_offset == 0 {
+        continue;
+      }
+      let name = if function.function_name.is_empty() {
+        format!("(anonymous_{})", index)
+      } else {
+        function.function_name.clone()
+      };
+      functions.push(FunctionCount {
+        name,
+        line: get_line_for_offset(&line_offsets, range.start_offset),
+        count: range.count,
+      });
+
+      if function.is_block_coverage {
+        for (range_index, range) in function.ranges.iter().enumerate().skip(1) {
+          branches.push(BranchCount {
+            line: get_line_for_offset(&line_offsets, range.start_offset),
+            block: index,
+            branch: range_index - 1,
+            count: range.count,
+          });
+        }
+      }
+    }
+
+    let maybe_source_map = get_inline_source_map(script_source)
+      .and_then(|source_map| SourceMap::from_slice(&source_map).ok());
+    let maybe_original_source = maybe_original_source.or_else(|| {
+      maybe_source_map
+        .as_ref()
+        .and_then(|source_map| source_map.get_source_contents(0))
+        .map(String::from)
+    });
+
+    if let (Some(source_map), Some(original_source)) =
+      (maybe_source_map, maybe_original_source)
+    {
+      let lines = original_source
+        .split('\n')
+        .map(String::from)
+        .collect::<Vec<_>>();
+      let mut line_counts: Vec<Option<usize>> = vec![None; lines.len()];
+      for token in source_map.tokens() {
+        if token.get_src_id() != 0 {
+          continue;
+        }
+        let dst_line = token.get_dst_line() as usize;
+        let src_line = token.get_src_line() as usize;
+        if src_line >= line_counts.len() {
+          continue;
+        }
+        if let Some(Some(count)) = generated_counts.get(dst_line) {
+          // A line is only as covered as its least executed part.
+          line_counts[src_line] = Some(match line_counts[src_line] {
+            Some(existing) => existing.min(*count),
+            None => *count,
+          });
+        }
+      }
+
+      let original_line = |line: usize| {
+        source_map
+          .lookup_token(line as u32, u32::MAX)
+          .map(|token| token.get_src_line() as usize)
+          .unwrap_or(line)
+      };
+      for function in functions.iter_mut() {
+        function.line = original_line(function.line);
+      }
+      for branch in branches.iter_mut() {
+        branch.line = original_line(branch.line);
+      }
+
+      FileCoverage {
+        url: coverage.script_coverage.url.clone(),
+        lines,
+        line_counts,
+        functions,
+        branches,
+      }
+    } else {
+      FileCoverage {
+        url: coverage.script_coverage.url.clone(),
+        lines: generated_lines.into_iter().map(String::from).collect(),
+        line_counts: generated_counts,
+        functions,
+        branches,
+      }
+    }
+  }
+
+  pub fn lines_found(&self) -> usize {
+    self.line_counts.iter().filter(|c| c.is_some()).count()
+  }
+
+  pub fn lines_hit(&self) -> usize {
+    self
+      .line_counts
+      .iter()
+      .filter(|c| matches!(c, Some(count) if *count > 0))
+      .count()
+  }
+
+  pub fn functions_hit(&self) -> usize {
+    self.functions.iter().filter(|f| f.count > 0).count()
+  }
+
+  pub fn branches_hit(&self) -> usize {
+    self.branches.iter().filter(|b| b.count > 0).count()
+  }
+
+  /// Returns the path of the module if it is a local file, otherwise the
+  /// specifier.
+  pub fn display_name(&self) -> String {
+    Url::parse(&self.url)
+      .ok()
+      .and_then(|url| url.to_file_path().ok())
+      .map(|path| path.to_string_lossy().to_string())
+      .unwrap_or_else(|| self.url.clone())
+  }
+}
+
+/// Returns the start and end offset of every line in the source, measured in
+/// UTF-16 code units like the offsets reported by V8.
+fn get_line_offsets(source: &str) -> Vec<(usize, usize)> {
+  let mut offsets = Vec::new();
+  let mut line_start_offset = 0;
+  for line in source.split('\n') {
+    let line_end_offset = line_start_offset + line.encode_utf16().count();
+    offsets.push((line_start_offset, line_end_offset));
+    line_start_offset = line_end_offset + 1;
+  }
+  offsets
+}
+
+fn get_line_for_offset(
+  line_offsets: &[(usize, usize)],
+  offset: usize,
+) -> usize {
+  line_offsets
+    .iter()
+    .position(|(_, line_end_offset)| offset <= *line_end_offset)
+    .unwrap_or_else(|| line_offsets.len().saturating_sub(1))
+}
+
+/// Returns the count of the innermost range which spans the given range.
+fn get_count_for_range<'a>(
+  ranges: impl Iterator<Item = &'a CoverageRange>,
+  start_offset: usize,
+  end_offset: usize,
+) -> usize {
+  let mut maybe_innermost: Option<&CoverageRange> = None;
+  for range in ranges {
+    if range.start_offset <= start_offset && range.end_offset >= end_offset {
+      let is_inner = maybe_innermost
+        .map(|innermost| {
+          range.end_offset - range.start_offset
+            <= innermost.end_offset - innermost.start_offset
+        })
+        .unwrap_or(true);
+      if is_inner {
+        maybe_innermost = Some(range);
+      }
+    }
+  }
+
+  maybe_innermost.map(|range| range.count).unwrap_or(0)
+}
+
+pub trait CoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    coverage: &Coverage,
+    maybe_original_source: Option<String>,
+  ) -> Result<(), AnyError>;
+
+  fn done(&mut self) -> Result<(), AnyError>;
+}
+
 pub struct PrettyCoverageReporter {
   quiet: bool,
 }
 
-// TODO(caspervonb) add support for lcov output (see geninfo(1) for format spec).
 impl PrettyCoverageReporter {
   pub fn new(quiet: bool) -> PrettyCoverageReporter {
     PrettyCoverageReporter { quiet }
   }
+}
 
-  pub fn visit_coverage(
+impl CoverageReporter for PrettyCoverageReporter {
+  fn visit_coverage(
     &mut self,
-    script_coverage: &ScriptCoverage,
-    script_source: &str,
-  ) {
+    coverage: &Coverage,
+    _maybe_original_source: Option<String>,
+  ) -> Result<(), AnyError> {
+    let script_coverage = &coverage.script_coverage;
+    let script_source = &coverage.script_source;
     let lines = script_source.lines().collect::<Vec<_>>();
 
     let mut covered_lines: Vec<usize> = Vec::new();
@@ -228,39 +491,445 @@ impl PrettyCoverageReporter {
         last_line = Some(line_index);
       }
     }
+
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
   }
 }
 
-fn collect_coverages(dir: &PathBuf) -> Result<Vec<Coverage>, AnyError> {
-  let mut coverages: Vec<Coverage> = Vec::new();
+/// Writes a tracefile in the LCOV format, see geninfo(1) for the format
+/// specification.
+pub struct LcovCoverageReporter {
+  maybe_output: Option<PathBuf>,
+  report: String,
+}
+
+impl LcovCoverageReporter {
+  pub fn new(maybe_output: Option<PathBuf>) -> LcovCoverageReporter {
+    LcovCoverageReporter {
+      maybe_output,
+      report: String::new(),
+    }
+  }
+}
+
+impl CoverageReporter for LcovCoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    coverage: &Coverage,
+    maybe_original_source: Option<String>,
+  ) -> Result<(), AnyError> {
+    let file_coverage = FileCoverage::new(coverage, maybe_original_source);
+    self.report.push_str(&render_lcov_record(&file_coverage));
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    if let Some(output) = &self.maybe_output {
+      if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      fs::write(output, &self.report)?;
+    } else {
+      let stdout = std::io::stdout();
+      let mut handle = stdout.lock();
+      handle.write_all(self.report.as_bytes())?;
+    }
+    Ok(())
+  }
+}
+
+fn render_lcov_record(file_coverage: &FileCoverage) -> String {
+  let mut record = format!("SF:{}\n", file_coverage.display_name());
+
+  for function in &file_coverage.functions {
+    record.push_str(&format!("FN:{},{}\n", function.line + 1, function.name));
+  }
+  for function in &file_coverage.functions {
+    record.push_str(&format!("FNDA:{},{}\n", function.count, function.name));
+  }
+  let functions_hit = file_coverage.functions_hit();
+  record.push_str(&format!("FNF:{}\n", file_coverage.functions.len()));
+  record.push_str(&format!("FNH:{}\n", functions_hit));
 
-  let entries = fs::read_dir(dir)?;
-  for entry in entries {
-    let json = fs::read_to_string(entry.unwrap().path())?;
-    let coverage: Coverage = serde_json::from_str(&json)?;
+  for branch in &file_coverage.branches {
+    let taken = if branch.count > 0 {
+      branch.count.to_string()
+    } else {
+      "-".to_string()
+    };
+    record.push_str(&format!(
+      "BRDA:{},{},{},{}\n",
+      branch.line + 1,
+      branch.block,
+      branch.branch,
+      taken
+    ));
+  }
+  record.push_str(&format!("BRF:{}\n", file_coverage.branches.len()));
+  record.push_str(&format!("BRH:{}\n", file_coverage.branches_hit()));
 
-    coverages.push(coverage);
+  for (index, maybe_count) in file_coverage.line_counts.iter().enumerate() {
+    if let Some(count) = maybe_count {
+      record.push_str(&format!("DA:{},{}\n", index + 1, count));
+    }
   }
+  record.push_str(&format!("LF:{}\n", file_coverage.lines_found()));
+  record.push_str(&format!("LH:{}\n", file_coverage.lines_hit()));
+  record.push_str("end_of_record\n");
 
-  // TODO(caspervonb) drain_filter would make this cleaner, its nightly at the moment.
-  if coverages.len() > 1 {
-    coverages.sort_by_key(|k| k.script_coverage.url.clone());
+  record
+}
 
-    for i in (1..coverages.len() - 1).rev() {
-      if coverages[i].script_coverage.url
-        == coverages[i - 1].script_coverage.url
-      {
-        let current = coverages.remove(i);
-        let previous = &mut coverages[i - 1];
+/// Writes a self-contained static HTML report, with an index page listing
+/// every module and a page per module with its source annotated by
+/// execution counts.
+pub struct HtmlCoverageReporter {
+  output: PathBuf,
+  entries: Vec<(String, String, usize, usize)>,
+}
 
-        for function in current.script_coverage.functions {
-          previous.script_coverage.functions.push(function);
-        }
+impl HtmlCoverageReporter {
+  pub fn new(output: PathBuf) -> HtmlCoverageReporter {
+    HtmlCoverageReporter {
+      output,
+      entries: Vec::new(),
+    }
+  }
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse}\
+td,th{padding:2px 8px;text-align:left}\
+pre{margin:0}\
+.hit{background:#dfd}\
+.miss{background:#fdd}\
+.count{color:#888;text-align:right}\
+.line{color:#888;text-align:right}";
+
+impl CoverageReporter for HtmlCoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    coverage: &Coverage,
+    maybe_original_source: Option<String>,
+  ) -> Result<(), AnyError> {
+    let file_coverage = FileCoverage::new(coverage, maybe_original_source);
+    let name = file_coverage.display_name();
+    let page = page_file_name(&name, &file_coverage.url);
+
+    let mut rows = String::new();
+    for (index, line) in file_coverage.lines.iter().enumerate() {
+      let (class, count) = match file_coverage.line_counts[index] {
+        Some(0) => ("miss", "0".to_string()),
+        Some(count) => ("hit", count.to_string()),
+        None => ("", "".to_string()),
+      };
+      rows.push_str(&format!(
+        "<tr class=\"{}\"><td class=\"line\">{}</td><td class=\"count\">{}</td><td><pre>{}</pre></td></tr>\n",
+        class,
+        index + 1,
+        count,
+        escape_html(line)
+      ));
+    }
+
+    let html = format!(
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name}</title>\n<style>{style}</style>\n</head>\n<body>\n<p><a href=\"index.html\">All files</a></p>\n<h1>{name}</h1>\n<p>{percent}</p>\n<table>\n{rows}</table>\n</body>\n</html>\n",
+      name = escape_html(&name),
+      style = HTML_STYLE,
+      percent = format_percent(
+        file_coverage.lines_hit(),
+        file_coverage.lines_found()
+      ),
+      rows = rows,
+    );
+    fs::create_dir_all(&self.output)?;
+    fs::write(self.output.join(&page), html)?;
+
+    self.entries.push((
+      name,
+      page,
+      file_coverage.lines_hit(),
+      file_coverage.lines_found(),
+    ));
+
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut rows = String::new();
+    for (name, page, hit, found) in &self.entries {
+      rows.push_str(&format!(
+        "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}/{}</td></tr>\n",
+        escape_html(page),
+        escape_html(name),
+        format_percent(*hit, *found),
+        hit,
+        found
+      ));
+    }
+
+    let html = format!(
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Coverage report</title>\n<style>{style}</style>\n</head>\n<body>\n<h1>Coverage report</h1>\n<table>\n<tr><th>File</th><th>Lines</th><th></th></tr>\n{rows}</table>\n</body>\n</html>\n",
+      style = HTML_STYLE,
+      rows = rows,
+    );
+    fs::create_dir_all(&self.output)?;
+    fs::write(self.output.join("index.html"), html)?;
+
+    Ok(())
+  }
+}
+
+fn format_percent(hit: usize, found: usize) -> String {
+  if found == 0 {
+    return "100.000%".to_string();
+  }
+  format!("{:.3}%", hit as f32 / found as f32 * 100.0)
+}
+
+/// Returns the name of the page of a module in the HTML report. Sanitizing
+/// the name can map different modules to the same string, so a short hash of
+/// the specifier keeps the pages apart.
+fn page_file_name(name: &str, url: &str) -> String {
+  let sanitized: String = name
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '.' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  let hash = checksum::gen(&[url.as_bytes()]);
+  format!("{}.{}.html", sanitized, &hash[..8])
+}
+
+fn escape_html(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// Prints a table of line, branch and function coverage per module, followed
+/// by the totals of all modules.
+#[derive(Default)]
+pub struct SummaryCoverageReporter {
+  rows: Vec<(String, (usize, usize), (usize, usize), (usize, usize))>,
+}
+
+impl CoverageReporter for SummaryCoverageReporter {
+  fn visit_coverage(
+    &mut self,
+    coverage: &Coverage,
+    maybe_original_source: Option<String>,
+  ) -> Result<(), AnyError> {
+    let file_coverage = FileCoverage::new(coverage, maybe_original_source);
+    self.rows.push((
+      file_coverage.url.clone(),
+      (file_coverage.lines_hit(), file_coverage.lines_found()),
+      (file_coverage.branches_hit(), file_coverage.branches.len()),
+      (file_coverage.functions_hit(), file_coverage.functions.len()),
+    ));
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut total_lines = (0, 0);
+    let mut total_branches = (0, 0);
+    let mut total_functions = (0, 0);
+    for (url, lines, branches, functions) in &self.rows {
+      println!(
+        "cover {} ... {}",
+        url,
+        format_summary(*lines, *branches, *functions)
+      );
+      total_lines = (total_lines.0 + lines.0, total_lines.1 + lines.1);
+      total_branches =
+        (total_branches.0 + branches.0, total_branches.1 + branches.1);
+      total_functions = (
+        total_functions.0 + functions.0,
+        total_functions.1 + functions.1,
+      );
+    }
+    println!(
+      "cover {} files ... {}",
+      self.rows.len(),
+      format_summary(total_lines, total_branches, total_functions)
+    );
+    Ok(())
+  }
+}
+
+fn format_summary(
+  lines: (usize, usize),
+  branches: (usize, usize),
+  functions: (usize, usize),
+) -> String {
+  format!(
+    "lines {} branches {} functions {}",
+    colorize_ratio(lines.0, lines.1),
+    colorize_ratio(branches.0, branches.1),
+    colorize_ratio(functions.0, functions.1)
+  )
+}
+
+fn colorize_ratio(hit: usize, found: usize) -> String {
+  let text = format!("{} ({}/{})", format_percent(hit, found), hit, found);
+  let ratio = if found == 0 {
+    1.0
+  } else {
+    hit as f32 / found as f32
+  };
+  if ratio >= 0.9 {
+    colors::green(&text).to_string()
+  } else if ratio >= 0.75 {
+    colors::yellow(&text).to_string()
+  } else {
+    colors::red(&text).to_string()
+  }
+}
+
+pub fn create_reporter(
+  kind: CoverageReporterKind,
+  quiet: bool,
+  dir: &PathBuf,
+  maybe_output: Option<PathBuf>,
+) -> Box<dyn CoverageReporter> {
+  match kind {
+    CoverageReporterKind::Pretty => {
+      Box::new(PrettyCoverageReporter::new(quiet))
+    }
+    CoverageReporterKind::Summary => {
+      Box::new(SummaryCoverageReporter::default())
+    }
+    CoverageReporterKind::Lcov => {
+      Box::new(LcovCoverageReporter::new(maybe_output))
+    }
+    CoverageReporterKind::Html => Box::new(HtmlCoverageReporter::new(
+      maybe_output.unwrap_or_else(|| dir.join("html")),
+    )),
+  }
+}
+
+/// Reads the raw profiles written by `CoverageCollector` from the given
+/// directories and merges the profiles of the same module.
+fn collect_coverages(dirs: &[PathBuf]) -> Result<Vec<Coverage>, AnyError> {
+  let mut coverages: Vec<Coverage> = Vec::new();
+
+  for dir in dirs {
+    let entries = fs::read_dir(dir)?;
+    for entry in entries {
+      let path = entry?.path();
+      // Reports can be written into the coverage directory, only the raw
+      // profiles are of interest here.
+      if path.extension().map_or(true, |ext| ext != "json") {
+        continue;
+      }
+      let json = fs::read_to_string(path)?;
+      let coverage: Coverage = serde_json::from_str(&json)?;
+
+      coverages.push(coverage);
+    }
+  }
+
+  Ok(merge_coverages(coverages))
+}
+
+/// Merges the coverages of the same module, summing up the counts of each
+/// function and range, the result is sorted by url.
+fn merge_coverages(coverages: Vec<Coverage>) -> Vec<Coverage> {
+  let mut merged: BTreeMap<String, Coverage> = BTreeMap::new();
+  for coverage in coverages {
+    let url = coverage.script_coverage.url.clone();
+    if let Some(existing) = merged.get_mut(&url) {
+      // Offsets are meaningless when the module changed between runs.
+      if existing.script_source != coverage.script_source {
+        warn!("Ignoring coverage of modified module \"{}\".", url);
+        continue;
       }
+      merge_script_coverage(
+        &mut existing.script_coverage,
+        coverage.script_coverage,
+      );
+    } else {
+      merged.insert(url, coverage);
+    }
+  }
+
+  merged.into_iter().map(|(_, coverage)| coverage).collect()
+}
+
+fn merge_script_coverage(target: &mut ScriptCoverage, source: ScriptCoverage) {
+  for function in source.functions {
+    let key = get_function_key(&function);
+    let maybe_existing = target
+      .functions
+      .iter_mut()
+      .find(|existing| get_function_key(existing) == key);
+    if let Some(existing) = maybe_existing {
+      *existing = merge_function_coverage(existing, &function);
+    } else {
+      target.functions.push(function);
     }
   }
+}
 
-  Ok(coverages)
+fn get_function_key(function: &FunctionCoverage) -> Option<(usize, usize)> {
+  function
+    .ranges
+    .first()
+    .map(|range| (range.start_offset, range.end_offset))
+}
+
+/// V8 only reports a block range when its count differs from the enclosing
+/// range, so a range missing from one of the profiles takes the count of its
+/// innermost enclosing range in that profile.
+fn merge_function_coverage(
+  a: &FunctionCoverage,
+  b: &FunctionCoverage,
+) -> FunctionCoverage {
+  let mut offsets = a
+    .ranges
+    .iter()
+    .chain(b.ranges.iter())
+    .map(|range| (range.start_offset, range.end_offset))
+    .collect::<Vec<_>>();
+  // Enclosing ranges come before the ranges they contain, like V8 reports
+  // them.
+  offsets.sort_by(|x, y| x.0.cmp(&y.0).then(y.1.cmp(&x.1)));
+  offsets.dedup();
+
+  let ranges = offsets
+    .into_iter()
+    .map(|(start_offset, end_offset)| CoverageRange {
+      start_offset,
+      end_offset,
+      count: get_count_for_range(a.ranges.iter(), start_offset, end_offset)
+        + get_count_for_range(b.ranges.iter(), start_offset, end_offset),
+    })
+    .collect();
+
+  FunctionCoverage {
+    function_name: a.function_name.clone(),
+    ranges,
+    is_block_coverage: a.is_block_coverage || b.is_block_coverage,
+  }
+}
+
+fn get_original_source(
+  program_state: &ProgramState,
+  coverage: &Coverage,
+) -> Option<String> {
+  ModuleSpecifier::resolve_url(&coverage.script_coverage.url)
+    .ok()
+    .and_then(|specifier| program_state.file_fetcher.get_source(&specifier))
+    .map(|file| file.source)
 }
 
 fn filter_coverages(
@@ -298,19 +967,241 @@ fn filter_coverages(
 }
 
 pub fn report_coverages(
+  program_state: &ProgramState,
   dir: &PathBuf,
   quiet: bool,
   exclude: Vec<Url>,
+  reporter_kind: CoverageReporterKind,
+  maybe_output: Option<PathBuf>,
 ) -> Result<(), AnyError> {
-  let coverages = collect_coverages(dir)?;
+  let coverages = collect_coverages(&[dir.clone()])?;
   let coverages = filter_coverages(coverages, exclude);
 
-  let mut coverage_reporter = PrettyCoverageReporter::new(quiet);
+  // Unlike `deno coverage`, the lcov report of `deno test` doesn't go to
+  // stdout where it would be mixed with the output of the tests.
+  let maybe_output = match reporter_kind {
+    CoverageReporterKind::Lcov => {
+      maybe_output.or_else(|| Some(dir.join("lcov.info")))
+    }
+    _ => maybe_output,
+  };
+
+  let mut coverage_reporter =
+    create_reporter(reporter_kind, quiet, dir, maybe_output);
+  for coverage in coverages {
+    let maybe_original_source = get_original_source(program_state, &coverage);
+    coverage_reporter.visit_coverage(&coverage, maybe_original_source)?;
+  }
+  coverage_reporter.done()?;
+
+  Ok(())
+}
+
+/// Reports the merged coverage found in one or more directories of raw
+/// profiles, keeping only the modules whose url matches one of the `include`
+/// patterns and none of the `exclude` patterns.
+pub fn cover_dirs(
+  program_state: &ProgramState,
+  dirs: Vec<PathBuf>,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  reporter_kind: CoverageReporterKind,
+  maybe_output: Option<PathBuf>,
+) -> Result<(), AnyError> {
+  let include = include
+    .iter()
+    .map(|pattern| Regex::new(pattern))
+    .collect::<Result<Vec<_>, _>>()?;
+  let exclude = exclude
+    .iter()
+    .map(|pattern| Regex::new(pattern))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let coverages = collect_coverages(&dirs)?
+    .into_iter()
+    .filter(|coverage| {
+      let url = &coverage.script_coverage.url;
+      include.iter().any(|pattern| pattern.is_match(url))
+        && !exclude.iter().any(|pattern| pattern.is_match(url))
+    })
+    .collect::<Vec<_>>();
+
+  let mut coverage_reporter =
+    create_reporter(reporter_kind, false, &dirs[0], maybe_output);
   for coverage in coverages {
-    let script_coverage = coverage.script_coverage;
-    let script_source = coverage.script_source;
-    coverage_reporter.visit_coverage(&script_coverage, &script_source);
+    let maybe_original_source = get_original_source(program_state, &coverage);
+    coverage_reporter.visit_coverage(&coverage, maybe_original_source)?;
   }
+  coverage_reporter.done()?;
 
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn coverage(source: &str, functions: Vec<FunctionCoverage>) -> Coverage {
+    Coverage {
+      script_coverage: ScriptCoverage {
+        script_id: "1".to_string(),
+        url: "file:///a/mod.js".to_string(),
+        functions,
+      },
+      script_source: source.to_string(),
+    }
+  }
+
+  fn function(
+    name: &str,
+    ranges: Vec<(usize, usize, usize)>,
+  ) -> FunctionCoverage {
+    FunctionCoverage {
+      function_name: name.to_string(),
+      ranges: ranges
+        .into_iter()
+        .map(|(start_offset, end_offset, count)| CoverageRange {
+          start_offset,
+          end_offset,
+          count,
+        })
+        .collect(),
+      is_block_coverage: true,
+    }
+  }
+
+  #[test]
+  fn test_file_coverage_without_source_map() {
+    let source = "function a() {\n  return 1;\n}\n\nfunction b() {\n  return 2;\n}\na();\n";
+    let coverage = coverage(
+      source,
+      vec![
+        function("", vec![(0, source.len(), 1)]),
+        function("a", vec![(0, 28, 1)]),
+        function("b", vec![(30, 58, 0)]),
+      ],
+    );
+    let file_coverage = FileCoverage::new(&coverage, None);
+    assert_eq!(
+      file_coverage.line_counts,
+      vec![
+        Some(1),
+        Some(1),
+        Some(1),
+        None,
+        Some(0),
+        Some(0),
+        Some(0),
+        Some(1),
+        None
+      ]
+    );
+    assert_eq!(file_coverage.lines_found(), 7);
+    assert_eq!(file_coverage.lines_hit(), 4);
+    assert_eq!(file_coverage.functions.len(), 2);
+    assert_eq!(file_coverage.functions[1].name, "b");
+    assert_eq!(file_coverage.functions[1].line, 4);
+    assert_eq!(file_coverage.functions[1].count, 0);
+  }
+
+  #[test]
+  fn test_render_lcov_record() {
+    let source = "function a() {\n  return 1;\n}\na();\n";
+    let coverage = coverage(
+      source,
+      vec![
+        function("", vec![(0, source.len(), 1)]),
+        function("a", vec![(0, 28, 2)]),
+      ],
+    );
+    let file_coverage = FileCoverage::new(&coverage, None);
+    let record = render_lcov_record(&file_coverage);
+    let expected_path = Url::parse("file:///a/mod.js")
+      .unwrap()
+      .to_file_path()
+      .unwrap();
+    assert_eq!(
+      record,
+      format!(
+        "SF:{}\nFN:1,a\nFNDA:2,a\nFNF:1\nFNH:1\nBRF:0\nBRH:0\nDA:1,2\nDA:2,2\nDA:3,2\nDA:4,1\nLF:4\nLH:4\nend_of_record\n",
+        expected_path.to_string_lossy()
+      )
+    );
+  }
+
+  #[test]
+  fn test_merge_function_coverage() {
+    let a = function("a", vec![(0, 100, 1), (10, 20, 0)]);
+    let b = function("a", vec![(0, 100, 2)]);
+    let merged = merge_function_coverage(&a, &b);
+    assert_eq!(merged.function_name, "a");
+    let ranges = merged
+      .ranges
+      .iter()
+      .map(|r| (r.start_offset, r.end_offset, r.count))
+      .collect::<Vec<_>>();
+    assert_eq!(ranges, vec![(0, 100, 3), (10, 20, 2)]);
+  }
+
+  #[test]
+  fn test_merge_coverages() {
+    let source = "function a() {\n  return 1;\n}\na();\n";
+    let merged = merge_coverages(vec![
+      coverage(
+        source,
+        vec![
+          function("", vec![(0, source.len(), 1)]),
+          function("a", vec![(0, 28, 0)]),
+        ],
+      ),
+      coverage(
+        source,
+        vec![
+          function("", vec![(0, source.len(), 1)]),
+          function("a", vec![(0, 28, 1)]),
+        ],
+      ),
+    ]);
+    assert_eq!(merged.len(), 1);
+    let functions = &merged[0].script_coverage.functions;
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[0].ranges[0].count, 2);
+    assert_eq!(functions[1].ranges[0].count, 1);
+  }
+
+  #[test]
+  fn test_html_reporter_colliding_names() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let output = temp_dir.path().join("html");
+    let mut reporter = HtmlCoverageReporter::new(output.clone());
+    for url in &["file:///a/b_c.ts", "file:///a/b/c.ts", "file:///a/x-y.ts"] {
+      let mut coverage =
+        coverage("a();\n", vec![function("", vec![(0, 5, 1)])]);
+      coverage.script_coverage.url = url.to_string();
+      reporter.visit_coverage(&coverage, None).unwrap();
+    }
+    reporter.done().unwrap();
+
+    let pages: Vec<&String> = reporter
+      .entries
+      .iter()
+      .map(|(_, page, _, _)| page)
+      .collect();
+    assert_eq!(pages.len(), 3);
+    assert_ne!(pages[0], pages[1]);
+    let index = fs::read_to_string(output.join("index.html")).unwrap();
+    for page in pages {
+      assert!(output.join(page).is_file());
+      assert!(index.contains(&format!("href=\"{}\"", page)));
+    }
+    assert_eq!(fs::read_dir(&output).unwrap().count(), 4);
+  }
+
+  #[test]
+  fn test_escape_html() {
+    assert_eq!(
+      escape_html("if (a < b && c > \"d\")"),
+      "if (a &lt; b &amp;&amp; c &gt; &quot;d&quot;)"
+    );
+  }
+}
@@ -0,0 +1,395 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use crate::colors;
+use crate::disk_cache::DiskCache;
+use crate::http_cache::Metadata;
+use crate::info::human_size;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// The extensions of the files which are written to the compiler cache for a
+/// module, with the longest first so they can be matched as suffixes.
+const EMIT_EXTENSIONS: &[&str] = &["js.map", "buildinfo", "meta", "js"];
+
+/// A remote module in the cache.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+  pub url: String,
+  /// The path of the cached source of the module.
+  pub filename: PathBuf,
+  pub size: u64,
+  /// The total size of the compiled code of the module and its metadata.
+  pub emit_size: u64,
+  /// The time the module was cached, in seconds since the Unix epoch.
+  pub modified: Option<u64>,
+}
+
+impl CacheEntry {
+  /// All the files of the entry, in the remote modules cache and in the
+  /// compiler cache.
+  fn files(&self, gen_cache: &DiskCache) -> Vec<PathBuf> {
+    let mut files =
+      vec![self.filename.clone(), Metadata::filename(&self.filename)];
+    if let Ok(url) = Url::parse(&self.url) {
+      files.extend(emit_filenames(gen_cache, &url));
+    }
+    files
+  }
+}
+
+fn file_size(path: &Path) -> u64 {
+  fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Return the files of the compiler cache for a module which exist.
+fn emit_filenames(gen_cache: &DiskCache, url: &Url) -> Vec<PathBuf> {
+  EMIT_EXTENSIONS
+    .iter()
+    .filter_map(|ext| gen_cache.get_cache_filename_with_extension(url, ext))
+    .map(|filename| gen_cache.location.join(filename))
+    .filter(|path| path.is_file())
+    .collect()
+}
+
+/// Strip the extension of a file of the compiler cache, which returns the
+/// path the file was cached for.
+fn strip_emit_extension(path: &Path) -> Option<PathBuf> {
+  let name = path.file_name()?.to_str()?;
+  EMIT_EXTENSIONS.iter().find_map(|ext| {
+    name
+      .strip_suffix(ext)
+      .and_then(|stem| stem.strip_suffix('.'))
+      .map(|stem| path.with_file_name(stem))
+  })
+}
+
+/// Return the path of a local module from the path of its compiled code,
+/// relative to the `file` directory of the compiler cache.
+fn local_source_path(relative: &Path) -> Option<PathBuf> {
+  if cfg!(windows) {
+    let mut components = relative.components();
+    let disk = components.next()?.as_os_str().to_str()?.to_string();
+    if disk == "UNC" {
+      return None;
+    }
+    Some(PathBuf::from(format!("{}:\\", disk)).join(components.as_path()))
+  } else {
+    Some(Path::new("/").join(relative))
+  }
+}
+
+/// Scan the remote modules cache, which returns the cached modules and the
+/// files which do not belong to a module: metadata without a source, sources
+/// without metadata and left over temporary files.
+fn scan_deps(
+  deps_location: &Path,
+  gen_cache: &DiskCache,
+) -> (Vec<CacheEntry>, Vec<PathBuf>) {
+  let mut entries = Vec::new();
+  let mut orphans = Vec::new();
+  let files = WalkDir::new(deps_location)
+    .into_iter()
+    .filter_map(Result::ok)
+    .filter(|e| e.file_type().is_file());
+  for file in files {
+    let path = file.path();
+    let name = file.file_name().to_string_lossy();
+    if let Some(stem) = name.strip_suffix(".metadata.json") {
+      let filename = path.with_file_name(stem);
+      let url = fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<Metadata>(&s).ok())
+        .and_then(|metadata| Url::parse(&metadata.url).ok());
+      match url {
+        Some(url) if filename.is_file() => {
+          let modified = fs::metadata(&filename)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+          entries.push(CacheEntry {
+            size: file_size(&filename),
+            emit_size: emit_filenames(gen_cache, &url)
+              .iter()
+              .map(|p| file_size(p))
+              .sum(),
+            url: url.to_string(),
+            filename,
+            modified,
+          });
+        }
+        _ => orphans.push(path.to_path_buf()),
+      }
+    } else if !Metadata::filename(path).is_file() {
+      orphans.push(path.to_path_buf());
+    }
+  }
+  entries.sort_by(|a, b| a.url.cmp(&b.url));
+  (entries, orphans)
+}
+
+/// Scan the compiler cache for the compiled code of modules which do not exist
+/// anymore: remote modules which are not in the remote modules cache and local
+/// files which were removed.
+fn scan_gen(deps_location: &Path, gen_cache: &DiskCache) -> Vec<PathBuf> {
+  let mut orphans = Vec::new();
+  for scheme in &["http", "https", "file"] {
+    let dir = gen_cache.location.join(scheme);
+    let files = WalkDir::new(&dir)
+      .into_iter()
+      .filter_map(Result::ok)
+      .filter(|e| e.file_type().is_file());
+    for file in files {
+      let path = file.path();
+      let source = match strip_emit_extension(path) {
+        Some(source) => source,
+        None => continue,
+      };
+      let exists = if *scheme == "file" {
+        match source.strip_prefix(&dir).ok().and_then(local_source_path) {
+          Some(source) => source.exists(),
+          None => true,
+        }
+      } else {
+        match source.strip_prefix(&gen_cache.location) {
+          Ok(relative) => deps_location.join(relative).is_file(),
+          Err(_) => true,
+        }
+      };
+      if !exists {
+        orphans.push(path.to_path_buf());
+      }
+    }
+  }
+  orphans
+}
+
+/// Return the remote modules in the cache, sorted by URL.
+pub fn get_entries(
+  deps_location: &Path,
+  gen_cache: &DiskCache,
+) -> Vec<CacheEntry> {
+  scan_deps(deps_location, gen_cache).0
+}
+
+/// Format the remote modules in the cache for the `--list` output.
+pub fn format_entries(entries: &[CacheEntry]) -> String {
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0);
+  let mut output = String::new();
+  let mut total = 0;
+  for entry in entries {
+    let cached = entry
+      .modified
+      .map(|secs| {
+        let days = now.saturating_sub(secs) / (24 * 60 * 60);
+        format!("{} days ago", days)
+      })
+      .unwrap_or_else(|| "unknown".to_string());
+    writeln!(
+      output,
+      "{} {}",
+      entry.url,
+      colors::gray(&format!(
+        "({}, compiled {}, cached {})",
+        human_size(entry.size as f64),
+        human_size(entry.emit_size as f64),
+        cached
+      ))
+    )
+    .unwrap();
+    total += entry.size + entry.emit_size;
+  }
+  writeln!(
+    output,
+    "{} {} modules, {}",
+    colors::bold("total:"),
+    entries.len(),
+    human_size(total as f64)
+  )
+  .unwrap();
+  output
+}
+
+/// Remove the remote modules which are not reachable and which are older than
+/// the given age, when either is given, along with their compiled code, and
+/// all the files of the caches which do not belong to a module. The reachable
+/// modules are given by their specifiers, including redirected specifiers.
+pub fn collect_garbage(
+  deps_location: &Path,
+  gen_cache: &DiskCache,
+  maybe_reachable: Option<HashSet<String>>,
+  maybe_older_than: Option<Duration>,
+  dry_run: bool,
+) -> Result<(), AnyError> {
+  let (entries, mut orphans) = scan_deps(deps_location, gen_cache);
+  orphans.extend(scan_gen(deps_location, gen_cache));
+
+  let maybe_threshold = maybe_older_than
+    .and_then(|age| SystemTime::now().checked_sub(age))
+    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_secs());
+  let collect_entries = maybe_reachable.is_some() || maybe_threshold.is_some();
+  let label = if dry_run {
+    colors::yellow("Would remove").to_string()
+  } else {
+    colors::red("Remove").to_string()
+  };
+
+  let mut count = 0;
+  let mut size = 0;
+  let mut remove = |path: &Path| -> Result<(), AnyError> {
+    count += 1;
+    size += file_size(path);
+    if !dry_run {
+      fs::remove_file(path)?;
+    }
+    Ok(())
+  };
+
+  if collect_entries {
+    for entry in entries.iter() {
+      let unreachable = maybe_reachable
+        .as_ref()
+        .map(|reachable| !reachable.contains(&entry.url))
+        .unwrap_or(true);
+      let old = match (maybe_threshold, entry.modified) {
+        (Some(threshold), Some(modified)) => modified < threshold,
+        (Some(_), None) => false,
+        (None, _) => true,
+      };
+      if unreachable && old {
+        info!("{} {}", label, entry.url);
+        for file in entry.files(gen_cache) {
+          if file.is_file() {
+            remove(&file)?;
+          }
+        }
+      }
+    }
+  }
+  for orphan in orphans.iter() {
+    info!("{} {}", label, orphan.display());
+    remove(orphan)?;
+  }
+
+  info!(
+    "{} {} files ({})",
+    if dry_run { "Would remove" } else { "Removed" },
+    count,
+    human_size(size as f64)
+  );
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::http_cache::HttpCache;
+  use std::collections::HashMap;
+  use tempfile::TempDir;
+
+  fn setup() -> (TempDir, PathBuf, DiskCache) {
+    let temp_dir = TempDir::new().unwrap();
+    let deps_location = temp_dir.path().join("deps");
+    let gen_cache = DiskCache::new(&temp_dir.path().join("gen"));
+    let http_cache = HttpCache::new(&deps_location);
+    for url in &["https://deno.land/a.ts", "https://deno.land/b.ts"] {
+      let url = Url::parse(url).unwrap();
+      http_cache
+        .set(&url, HashMap::new(), b"export const a = 1;")
+        .unwrap();
+      let filename = gen_cache
+        .get_cache_filename_with_extension(&url, "js")
+        .unwrap();
+      gen_cache.set(&filename, b"export const a = 1;").unwrap();
+    }
+    (temp_dir, deps_location, gen_cache)
+  }
+
+  #[test]
+  fn test_get_entries() {
+    let (_temp_dir, deps_location, gen_cache) = setup();
+    let entries = get_entries(&deps_location, &gen_cache);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].url, "https://deno.land/a.ts");
+    assert_eq!(entries[0].size, 19);
+    assert_eq!(entries[0].emit_size, 19);
+    assert!(entries[0].modified.is_some());
+    assert!(format_entries(&entries).contains("https://deno.land/b.ts"));
+  }
+
+  #[test]
+  fn test_collect_unreachable() {
+    let (_temp_dir, deps_location, gen_cache) = setup();
+    let mut reachable = HashSet::new();
+    reachable.insert("https://deno.land/a.ts".to_string());
+
+    collect_garbage(
+      &deps_location,
+      &gen_cache,
+      Some(reachable.clone()),
+      None,
+      true,
+    )
+    .unwrap();
+    assert_eq!(get_entries(&deps_location, &gen_cache).len(), 2);
+
+    collect_garbage(&deps_location, &gen_cache, Some(reachable), None, false)
+      .unwrap();
+    let entries = get_entries(&deps_location, &gen_cache);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].url, "https://deno.land/a.ts");
+    let url = Url::parse("https://deno.land/b.ts").unwrap();
+    assert!(emit_filenames(&gen_cache, &url).is_empty());
+  }
+
+  #[test]
+  fn test_collect_older_than() {
+    let (_temp_dir, deps_location, gen_cache) = setup();
+    collect_garbage(
+      &deps_location,
+      &gen_cache,
+      None,
+      Some(Duration::from_secs(60 * 60)),
+      false,
+    )
+    .unwrap();
+    assert_eq!(get_entries(&deps_location, &gen_cache).len(), 2);
+  }
+
+  #[test]
+  fn test_collect_orphans() {
+    let (temp_dir, deps_location, gen_cache) = setup();
+    let url = Url::parse("https://deno.land/a.ts").unwrap();
+    let http_cache = HttpCache::new(&deps_location);
+    let filename = http_cache.get_cache_filename(&url);
+    fs::remove_file(&filename).unwrap();
+    let local_source = temp_dir.path().join("removed.ts");
+    let local_url = Url::from_file_path(&local_source).unwrap();
+    let local_emit = gen_cache
+      .get_cache_filename_with_extension(&local_url, "js")
+      .unwrap();
+    gen_cache.set(&local_emit, b"").unwrap();
+
+    collect_garbage(&deps_location, &gen_cache, None, None, false).unwrap();
+    assert!(!Metadata::filename(&filename).exists());
+    assert!(emit_filenames(&gen_cache, &url).is_empty());
+    assert!(!gen_cache.location.join(local_emit).exists());
+    assert_eq!(get_entries(&deps_location, &gen_cache).len(), 1);
+  }
+}
@@ -223,6 +223,14 @@ pub fn install(
     executable_args.push("--lock-write".to_string());
   }
 
+  if flags.lock_update {
+    executable_args.push("--lock-update".to_string());
+  }
+
+  if flags.revalidate {
+    executable_args.push("--revalidate".to_string());
+  }
+
   if flags.cached_only {
     executable_args.push("--cached_only".to_string());
   }
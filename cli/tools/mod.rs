@@ -2,8 +2,11 @@
 
 pub mod coverage;
 pub mod fmt;
+pub mod gc;
 pub mod installer;
 pub mod lint;
+pub mod outdated;
 pub mod repl;
 pub mod test_runner;
 pub mod upgrade;
+pub mod vendor;
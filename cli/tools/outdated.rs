@@ -0,0 +1,449 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! This module provides the `outdated` subcommand, which checks the specifiers
+//! of the `deno.land` registry which are pinned to a version, like
+//! `https://deno.land/std@0.83.0/fs/mod.ts`, for newer versions of their
+//! modules, and updates them in the source files and the import map.
+
+use crate::ast;
+use crate::colors;
+use crate::fs_util::collect_files;
+use crate::fs_util::is_supported_ext;
+use crate::http_util::create_http_client;
+use crate::http_util::get_user_agent;
+use crate::media_type::MediaType;
+use deno_core::error::anyhow;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+use deno_runtime::deno_fetch::reqwest::Client;
+use semver_parser::version::parse as semver_parse;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The default API of the registry, which lists the versions of a module at
+/// `<api>/<module>/meta/versions.json`.
+pub const DEFAULT_REGISTRY_API: &str = "https://cdn.deno.land";
+
+const REGISTRY_URL: &str = "https://deno.land/";
+
+/// A specifier of a module of the registry which is pinned to a version.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct VersionedSpecifier {
+  /// The name of the module in the registry, like `std` or `oak`.
+  name: String,
+  /// The part of the specifier before the version, like
+  /// `https://deno.land/x/oak@`.
+  prefix: String,
+  version: String,
+  /// The part of the specifier after the version, like `/mod.ts`.
+  suffix: String,
+}
+
+impl VersionedSpecifier {
+  /// Parse a specifier of the form `https://deno.land/std@<version>/...` or
+  /// `https://deno.land/x/<module>@<version>/...`.
+  fn parse(specifier: &str) -> Option<Self> {
+    let path = specifier.strip_prefix(REGISTRY_URL)?;
+    let module = if let Some(path) = path.strip_prefix("x/") {
+      path
+    } else if path.starts_with("std@") {
+      path
+    } else {
+      return None;
+    };
+    let module_end = module.find('/').unwrap_or_else(|| module.len());
+    let (name, version) =
+      module[..module_end].split_at(module[..module_end].find('@')?);
+    let version = &version[1..];
+    if name.is_empty() || version.is_empty() {
+      return None;
+    }
+    let prefix_len = specifier.len() - module.len() + name.len() + 1;
+
+    Some(Self {
+      name: name.to_string(),
+      prefix: specifier[..prefix_len].to_string(),
+      version: version.to_string(),
+      suffix: module[module_end..].to_string(),
+    })
+  }
+
+  fn with_version(&self, version: &str) -> String {
+    format!("{}{}{}", self.prefix, version, self.suffix)
+  }
+}
+
+/// The versions of a module, as listed by the registry API.
+#[derive(Debug, Deserialize)]
+struct ModuleVersions {
+  latest: String,
+}
+
+/// Return true if the latest version of a module is newer than the current
+/// one. Versions which are not semver, like branch names, are only compared
+/// for equality.
+fn is_newer(latest: &str, current: &str) -> bool {
+  let parse = |v: &str| semver_parse(v.strip_prefix('v').unwrap_or(v)).ok();
+  match (parse(latest), parse(current)) {
+    (Some(latest), Some(current)) => latest > current,
+    _ => latest != current,
+  }
+}
+
+/// Return the specifiers of the registry which are pinned to a version and
+/// which are imported by a source file.
+fn get_file_specifiers(path: &Path) -> Result<BTreeSet<String>, AnyError> {
+  let source = fs::read_to_string(path)?;
+  let media_type = MediaType::from(path);
+  let parsed_module =
+    ast::parse(&path.to_string_lossy(), &source, &media_type)?;
+
+  Ok(
+    parsed_module
+      .analyze_dependencies()
+      .into_iter()
+      .map(|desc| desc.specifier.to_string())
+      .filter(|specifier| VersionedSpecifier::parse(specifier).is_some())
+      .collect(),
+  )
+}
+
+/// Return the addresses of an import map which are specifiers of the registry
+/// pinned to a version, from both the `imports` and the `scopes`.
+fn get_import_map_specifiers(import_map: &Value) -> BTreeSet<String> {
+  let mut specifiers = BTreeSet::new();
+  let mut maps: Vec<&Value> = vec![&import_map["imports"]];
+  if let Some(scopes) = import_map["scopes"].as_object() {
+    maps.extend(scopes.values());
+  }
+  for map in maps {
+    if let Some(map) = map.as_object() {
+      for address in map.values().filter_map(Value::as_str) {
+        if VersionedSpecifier::parse(address).is_some() {
+          specifiers.insert(address.to_string());
+        }
+      }
+    }
+  }
+  specifiers
+}
+
+/// Replace the quoted specifiers of a source file which are outdated.
+fn update_source(source: &str, updates: &HashMap<String, String>) -> String {
+  let mut source = source.to_string();
+  for (from, to) in updates {
+    for quote in &['"', '\''] {
+      source = source.replace(
+        &format!("{}{}{}", quote, from, quote),
+        &format!("{}{}{}", quote, to, quote),
+      );
+    }
+  }
+  source
+}
+
+/// Replace the addresses of an import map which are outdated, in both the
+/// `imports` and the `scopes`.
+fn update_import_map(
+  import_map: &mut Value,
+  updates: &HashMap<String, String>,
+) {
+  fn update_map(map: &mut Value, updates: &HashMap<String, String>) {
+    if let Some(map) = map.as_object_mut() {
+      for address in map.values_mut() {
+        if let Some(to) = address.as_str().and_then(|a| updates.get(a)) {
+          *address = Value::String(to.clone());
+        }
+      }
+    }
+  }
+
+  if let Some(imports) = import_map.get_mut("imports") {
+    update_map(imports, updates);
+  }
+  if let Some(scopes) =
+    import_map.get_mut("scopes").and_then(Value::as_object_mut)
+  {
+    for map in scopes.values_mut() {
+      update_map(map, updates);
+    }
+  }
+}
+
+async fn get_latest_version(
+  client: &Client,
+  registry_api: &str,
+  name: &str,
+) -> Result<String, AnyError> {
+  let url = format!(
+    "{}/{}/meta/versions.json",
+    registry_api.trim_end_matches('/'),
+    name
+  );
+  let res = client.get(&url).send().await?;
+  if !res.status().is_success() {
+    return Err(anyhow!("Request to \"{}\" failed: {}", url, res.status()));
+  }
+  let versions: ModuleVersions = serde_json::from_str(&res.text().await?)?;
+  Ok(versions.latest)
+}
+
+/// Check the versioned specifiers of the registry in the source files and the
+/// import map for newer versions, and update them when `update` is set.
+pub async fn outdated(
+  files: Vec<PathBuf>,
+  maybe_import_map: Option<PathBuf>,
+  registry_api: String,
+  ca_file: Option<&str>,
+  update: bool,
+) -> Result<(), AnyError> {
+  // the specifiers of each file, where the import map is one of the files
+  let mut file_specifiers: BTreeMap<PathBuf, BTreeSet<String>> =
+    BTreeMap::new();
+  for path in collect_files(&files, &[], is_supported_ext)? {
+    let specifiers = get_file_specifiers(&path)
+      .map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+    if !specifiers.is_empty() {
+      file_specifiers.insert(path, specifiers);
+    }
+  }
+  let maybe_import_map = match maybe_import_map {
+    Some(path) => {
+      let import_map: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+        .map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+      file_specifiers
+        .insert(path.clone(), get_import_map_specifiers(&import_map));
+      Some((path, import_map))
+    }
+    None => None,
+  };
+
+  let names: BTreeSet<String> = file_specifiers
+    .values()
+    .flatten()
+    .filter_map(|s| VersionedSpecifier::parse(s))
+    .map(|s| s.name)
+    .collect();
+  let client = create_http_client(get_user_agent(), ca_file)?;
+  let mut latest_versions = HashMap::new();
+  for name in names {
+    match get_latest_version(&client, &registry_api, &name).await {
+      Ok(latest) => {
+        latest_versions.insert(name, latest);
+      }
+      Err(err) => {
+        eprintln!(
+          "{} Could not get the versions of \"{}\": {}",
+          colors::yellow("Warning"),
+          name,
+          err
+        );
+      }
+    }
+  }
+
+  // the outdated modules, by name and current version, with the files which
+  // import them
+  let mut outdated: BTreeMap<(String, String), BTreeSet<PathBuf>> =
+    BTreeMap::new();
+  let mut updates: HashMap<String, String> = HashMap::new();
+  for (path, specifiers) in file_specifiers.iter() {
+    for specifier in specifiers {
+      let versioned = VersionedSpecifier::parse(specifier).unwrap();
+      let latest = match latest_versions.get(&versioned.name) {
+        Some(latest) if is_newer(latest, &versioned.version) => latest,
+        _ => continue,
+      };
+      updates.insert(specifier.clone(), versioned.with_version(latest));
+      outdated
+        .entry((versioned.name, versioned.version))
+        .or_default()
+        .insert(path.clone());
+    }
+  }
+
+  if outdated.is_empty() {
+    println!("All versioned dependencies are up to date.");
+    return Ok(());
+  }
+  for ((name, current), paths) in outdated.iter() {
+    println!(
+      "{} {} → {}",
+      colors::bold(name),
+      colors::red(current),
+      colors::green(&latest_versions[name])
+    );
+    for path in paths {
+      println!("  {}", colors::gray(&path.display().to_string()));
+    }
+  }
+  if !update {
+    println!("Run with --update to update them.");
+    return Ok(());
+  }
+
+  let mut updated_count = 0;
+  for path in file_specifiers.keys() {
+    if let Some((import_map_path, import_map)) = maybe_import_map.as_ref() {
+      if path == import_map_path {
+        let mut updated_import_map = import_map.clone();
+        update_import_map(&mut updated_import_map, &updates);
+        if &updated_import_map != import_map {
+          let contents = serde_json::to_string_pretty(&updated_import_map)?;
+          fs::write(path, format!("{}\n", contents))?;
+          updated_count += 1;
+        }
+        continue;
+      }
+    }
+    let source = fs::read_to_string(path)?;
+    let updated_source = update_source(&source, &updates);
+    if updated_source != source {
+      fs::write(path, updated_source)?;
+      updated_count += 1;
+    }
+  }
+  println!("{} {} files", colors::green("Updated"), updated_count);
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::serde_json::json;
+
+  #[test]
+  fn test_parse_versioned_specifier() {
+    let fixture =
+      VersionedSpecifier::parse("https://deno.land/std@0.83.0/fs/mod.ts")
+        .unwrap();
+    assert_eq!(fixture.name, "std");
+    assert_eq!(fixture.version, "0.83.0");
+    assert_eq!(
+      fixture.with_version("0.84.0"),
+      "https://deno.land/std@0.84.0/fs/mod.ts"
+    );
+    let fixture =
+      VersionedSpecifier::parse("https://deno.land/x/oak@v6.4.1/mod.ts")
+        .unwrap();
+    assert_eq!(fixture.name, "oak");
+    assert_eq!(fixture.version, "v6.4.1");
+    assert_eq!(fixture.prefix, "https://deno.land/x/oak@");
+    let fixture =
+      VersionedSpecifier::parse("https://deno.land/x/oak@v6.4.1/").unwrap();
+    assert_eq!(fixture.suffix, "/");
+    let fixture =
+      VersionedSpecifier::parse("https://deno.land/x/oak@v6.4.1").unwrap();
+    assert_eq!(fixture.suffix, "");
+
+    assert_eq!(
+      VersionedSpecifier::parse("https://deno.land/std/mod.ts"),
+      None
+    );
+    assert_eq!(
+      VersionedSpecifier::parse("https://deno.land/x/oak/mod.ts"),
+      None
+    );
+    assert_eq!(
+      VersionedSpecifier::parse("https://deno.land/x/@1.0.0/"),
+      None
+    );
+    assert_eq!(VersionedSpecifier::parse("https://deno.land/x/oak@/"), None);
+    assert_eq!(
+      VersionedSpecifier::parse("https://example.com/std@0.83.0/mod.ts"),
+      None
+    );
+    assert_eq!(VersionedSpecifier::parse("./std@0.83.0/mod.ts"), None);
+  }
+
+  #[test]
+  fn test_is_newer() {
+    assert!(is_newer("0.84.0", "0.83.0"));
+    assert!(is_newer("v6.4.1", "v6.4.0"));
+    assert!(is_newer("v1.10.0", "v1.9.0"));
+    assert!(!is_newer("0.83.0", "0.84.0"));
+    assert!(!is_newer("0.83.0", "0.83.0"));
+    assert!(is_newer("main", "v1.0.0"));
+    assert!(!is_newer("main", "main"));
+  }
+
+  #[test]
+  fn test_update_source() {
+    let mut updates = HashMap::new();
+    updates.insert(
+      "https://deno.land/std@0.83.0/fs/mod.ts".to_string(),
+      "https://deno.land/std@0.84.0/fs/mod.ts".to_string(),
+    );
+    let source = r#"import { a } from "https://deno.land/std@0.83.0/fs/mod.ts";
+import { b } from 'https://deno.land/std@0.83.0/fs/mod.ts';
+import { c } from "https://deno.land/std@0.83.0/fs/mod.tsx";
+// https://deno.land/std@0.83.0/fs/mod.ts
+"#;
+    assert_eq!(
+      update_source(source, &updates),
+      r#"import { a } from "https://deno.land/std@0.84.0/fs/mod.ts";
+import { b } from 'https://deno.land/std@0.84.0/fs/mod.ts';
+import { c } from "https://deno.land/std@0.83.0/fs/mod.tsx";
+// https://deno.land/std@0.83.0/fs/mod.ts
+"#
+    );
+  }
+
+  #[test]
+  fn test_import_map_specifiers() {
+    let mut import_map = json!({
+      "imports": {
+        "std/": "https://deno.land/std@0.83.0/",
+        "oak": "https://deno.land/x/oak@v6.4.0/mod.ts",
+        "local": "./local.ts"
+      },
+      "scopes": {
+        "legacy/": {
+          "std/": "https://deno.land/std@0.50.0/"
+        }
+      }
+    });
+    let specifiers = get_import_map_specifiers(&import_map);
+    assert_eq!(
+      specifiers.into_iter().collect::<Vec<_>>(),
+      vec![
+        "https://deno.land/std@0.50.0/",
+        "https://deno.land/std@0.83.0/",
+        "https://deno.land/x/oak@v6.4.0/mod.ts",
+      ]
+    );
+
+    let mut updates = HashMap::new();
+    updates.insert(
+      "https://deno.land/std@0.50.0/".to_string(),
+      "https://deno.land/std@0.84.0/".to_string(),
+    );
+    updates.insert(
+      "https://deno.land/x/oak@v6.4.0/mod.ts".to_string(),
+      "https://deno.land/x/oak@v6.4.1/mod.ts".to_string(),
+    );
+    update_import_map(&mut import_map, &updates);
+    assert_eq!(
+      import_map,
+      json!({
+        "imports": {
+          "std/": "https://deno.land/std@0.83.0/",
+          "oak": "https://deno.land/x/oak@v6.4.1/mod.ts",
+          "local": "./local.ts"
+        },
+        "scopes": {
+          "legacy/": {
+            "std/": "https://deno.land/std@0.84.0/"
+          }
+        }
+      })
+    );
+  }
+}
@@ -0,0 +1,546 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use crate::checksum;
+use crate::media_type::MediaType;
+use crate::module_graph::Graph;
+use deno_core::error::anyhow;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Map;
+use deno_core::serde_json::Value;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Component;
+use std::path::Path;
+
+/// The name of the import map which is generated in the vendor directory.
+pub const IMPORT_MAP_NAME: &str = "import_map.json";
+
+/// Check that the output directory can be vendored into, which requires it
+/// to be empty unless `force` is set.
+pub fn ensure_output_dir(
+  output_dir: &Path,
+  force: bool,
+) -> Result<(), AnyError> {
+  if force || !output_dir.exists() {
+    return Ok(());
+  }
+  if fs::read_dir(output_dir)?.next().is_some() {
+    return Err(anyhow!(
+      "Output directory \"{}\" is not empty. Use --force to vendor into it anyway.",
+      output_dir.display()
+    ));
+  }
+  Ok(())
+}
+
+/// Return true if the specifier is relative or absolute to the host of the
+/// referrer, as opposed to a full URL or a bare specifier.
+fn is_relative_specifier(specifier: &str) -> bool {
+  specifier.starts_with("./")
+    || specifier.starts_with("../")
+    || specifier.starts_with('/')
+}
+
+fn is_remote(specifier: &ModuleSpecifier) -> bool {
+  let scheme = specifier.as_url().scheme();
+  scheme == "http" || scheme == "https"
+}
+
+/// Return the name of the directory of a host in the vendor directory. The
+/// port is separated with `_`, as `:` is not allowed in paths on Windows.
+fn host_dir(url: &Url) -> String {
+  let host: String = url
+    .host_str()
+    .unwrap_or("")
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  match url.port() {
+    Some(port) => format!("{}_{}", host, port),
+    None => host,
+  }
+}
+
+/// Return the path in the vendor directory which mirrors the URL of a module,
+/// so that relative imports between the copies resolve like the imports
+/// between the remote modules.
+fn mirrored_path(url: &Url) -> String {
+  format!("{}{}", host_dir(url), url.path())
+}
+
+fn is_valid_segment(segment: &str) -> bool {
+  !segment.is_empty()
+    && segment != "."
+    && segment != ".."
+    && segment
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "-_.~@+".contains(c))
+}
+
+/// Return true if a module can be copied to its mirrored path, which requires
+/// the path to be valid on all platforms and the extension of the path to
+/// match the media type of the module.
+fn can_mirror(url: &Url, media_type: &MediaType) -> bool {
+  if url.query().is_some() || url.path().ends_with('/') {
+    return false;
+  }
+  let valid_segments = url
+    .path_segments()
+    .map(|mut segments| segments.all(is_valid_segment))
+    .unwrap_or(false);
+  valid_segments && MediaType::from(Path::new(url.path())) == *media_type
+}
+
+/// Return a path for a module which can't be mirrored, made of the sanitized
+/// segments of its URL path and the extension of its media type.
+fn sanitized_path(url: &Url, media_type: &MediaType) -> String {
+  let mut segments: Vec<String> = url
+    .path_segments()
+    .map(|segments| {
+      segments
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+          let segment: String = segment
+            .chars()
+            .map(|c| {
+              if c.is_ascii_alphanumeric() || "-_.~@+".contains(c) {
+                c
+              } else {
+                '_'
+              }
+            })
+            .collect();
+          if segment == "." || segment == ".." {
+            "_".to_string()
+          } else {
+            segment
+          }
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  if url.path().ends_with('/') || segments.is_empty() {
+    segments.push("index".to_string());
+  }
+  let mut path = host_dir(url);
+  for segment in segments {
+    path.push('/');
+    path.push_str(&segment);
+  }
+  path
+}
+
+/// Return a specifier for `path` which is relative to `base_dir`, as it is
+/// written in an import map located in `base_dir`.
+fn relative_specifier(base_dir: &Path, path: &Path) -> String {
+  let base: Vec<Component> = base_dir.components().collect();
+  let target: Vec<Component> = path.components().collect();
+  let common = base
+    .iter()
+    .zip(target.iter())
+    .take_while(|(a, b)| a == b)
+    .count();
+  if common == 0 {
+    // paths on different drives can't be relative to each other
+    return Url::from_file_path(path)
+      .map(|url| url.to_string())
+      .unwrap_or_else(|_| path.to_string_lossy().to_string());
+  }
+  let mut parts: Vec<String> =
+    base[common..].iter().map(|_| "..".to_string()).collect();
+  if parts.is_empty() {
+    parts.push(".".to_string());
+  }
+  parts.extend(
+    target[common..]
+      .iter()
+      .map(|c| c.as_os_str().to_string_lossy().to_string()),
+  );
+  parts.join("/")
+}
+
+/// The paths of the copies of the remote modules of a graph, relative to the
+/// vendor directory and separated with `/`.
+struct VendoredModules {
+  paths: HashMap<ModuleSpecifier, String>,
+  redirects: HashMap<ModuleSpecifier, ModuleSpecifier>,
+}
+
+impl VendoredModules {
+  fn new(graph: &Graph) -> Self {
+    let mut modules: Vec<(ModuleSpecifier, MediaType)> = graph
+      .get_modules()
+      .into_iter()
+      .filter(|specifier| is_remote(specifier))
+      .filter(|specifier| graph.get_source(specifier).is_some())
+      .map(|specifier| {
+        let media_type = graph
+          .get_media_type(&specifier)
+          .unwrap_or(MediaType::Unknown);
+        (specifier, media_type)
+      })
+      .collect();
+    modules.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+    let mut paths = HashMap::new();
+    // the paths are compared in lower case, as the file system may be case
+    // insensitive
+    let mut used = HashSet::new();
+    for (specifier, media_type) in modules.iter() {
+      let url = specifier.as_url();
+      if can_mirror(url, media_type) {
+        let path = mirrored_path(url);
+        used.insert(path.to_lowercase());
+        paths.insert(specifier.clone(), path);
+      }
+    }
+    for (specifier, media_type) in modules.iter() {
+      if paths.contains_key(specifier) {
+        continue;
+      }
+      let url = specifier.as_url();
+      let path = sanitized_path(url, media_type);
+      let extension = media_type.as_ts_extension();
+      let mut candidate = format!("{}{}", path, extension);
+      if url.query().is_some() || used.contains(&candidate.to_lowercase()) {
+        let hash = checksum::gen(&[url.as_str().as_bytes()]);
+        candidate = format!("{}_{}{}", path, &hash[..8], extension);
+      }
+      used.insert(candidate.to_lowercase());
+      paths.insert(specifier.clone(), candidate);
+    }
+
+    Self {
+      paths,
+      redirects: graph.get_redirects(),
+    }
+  }
+
+  fn resolve<'a>(
+    &'a self,
+    specifier: &'a ModuleSpecifier,
+  ) -> &'a ModuleSpecifier {
+    let mut specifier = specifier;
+    // guard against redirect cycles
+    for _ in 0..=self.redirects.len() {
+      match self.redirects.get(specifier) {
+        Some(redirect) => specifier = redirect,
+        None => break,
+      }
+    }
+    specifier
+  }
+
+  /// Return the path of the copy of a module, following redirects.
+  fn get(&self, specifier: &ModuleSpecifier) -> Option<&String> {
+    self.paths.get(self.resolve(specifier))
+  }
+
+  /// Return true if importing the mirrored path of the specifier loads the
+  /// copy of its module.
+  fn is_mirrored(&self, specifier: &ModuleSpecifier) -> bool {
+    self.get(specifier).map(|path| path.as_str())
+      == Some(mirrored_path(specifier.as_url()).as_str())
+  }
+
+  /// Return true if any module which is vendored is under the URL prefix.
+  fn has_prefix(&self, prefix: &str) -> bool {
+    self
+      .paths
+      .keys()
+      .any(|specifier| specifier.as_str().starts_with(prefix))
+  }
+
+  /// Return the mappings of the remote specifiers to the copies of the
+  /// modules, for the `imports` of the generated import map.
+  fn get_imports(&self, graph: &Graph) -> BTreeMap<String, String> {
+    let mut imports = BTreeMap::new();
+    for specifier in self.paths.keys() {
+      let url = specifier.as_url();
+      let origin = format!("{}/", url.origin().ascii_serialization());
+      imports.insert(origin, format!("./{}/", host_dir(url)));
+    }
+    for specifier in self.paths.keys().chain(self.redirects.keys()) {
+      if !is_remote(specifier) || self.is_mirrored(specifier) {
+        continue;
+      }
+      if let Some(path) = self.get(specifier) {
+        imports.insert(specifier.to_string(), format!("./{}", path));
+      }
+    }
+    for referrer in graph.get_modules() {
+      let dependencies = match graph.get_dependencies(&referrer) {
+        Some(dependencies) => dependencies,
+        None => continue,
+      };
+      for (raw, specifier) in dependencies {
+        let path = match self.get(&specifier) {
+          Some(path) => format!("./{}", path),
+          None => continue,
+        };
+        if raw.starts_with('/') && !raw.starts_with("//") {
+          // an import which is absolute to the host of a remote module is
+          // absolute to the root of the file system in its copy
+          if is_remote(&referrer) {
+            imports.insert(specifier.as_url().path().to_string(), path);
+          }
+        } else if is_relative_specifier(&raw) {
+          if is_remote(&referrer) && !self.is_mirrored(&specifier) {
+            let key = format!("./{}", mirrored_path(specifier.as_url()));
+            imports.insert(key, path);
+          }
+        } else if Url::parse(&raw).is_err() {
+          // a bare specifier, which was mapped by the given import map
+          imports.insert(raw, path);
+        }
+      }
+    }
+    imports
+  }
+
+  /// Rewrite an address of the given import map, which is resolved against
+  /// the URL of the import map, so that it is valid in the generated import
+  /// map and points to the copy of the module, if any.
+  fn rewrite_address(
+    &self,
+    address: &str,
+    base: &Url,
+    output_dir: &Path,
+  ) -> String {
+    let url = match base.join(address) {
+      Ok(url) => url,
+      Err(_) => return address.to_string(),
+    };
+    match url.scheme() {
+      "http" | "https" => {
+        if address.ends_with('/') {
+          if self.has_prefix(url.as_str()) {
+            return format!("./{}", mirrored_path(&url));
+          }
+        } else if let Some(path) = self.get(&ModuleSpecifier::from(url)) {
+          return format!("./{}", path);
+        }
+        address.to_string()
+      }
+      "file" => match url.to_file_path() {
+        Ok(path) => {
+          let mut specifier = relative_specifier(output_dir, &path);
+          if address.ends_with('/') && !specifier.ends_with('/') {
+            specifier.push('/');
+          }
+          specifier
+        }
+        Err(_) => address.to_string(),
+      },
+      _ => address.to_string(),
+    }
+  }
+
+  /// Rewrite the specifier map of the given import map, where keys which are
+  /// relative to the import map are rewritten like addresses.
+  fn rewrite_specifier_map(
+    &self,
+    map: &Map<String, Value>,
+    base: &Url,
+    output_dir: &Path,
+  ) -> Map<String, Value> {
+    map
+      .iter()
+      .map(|(key, value)| {
+        let key = if is_relative_specifier(key) {
+          self.rewrite_address(key, base, output_dir)
+        } else {
+          key.clone()
+        };
+        let value = match value {
+          Value::String(address) => {
+            Value::String(self.rewrite_address(address, base, output_dir))
+          }
+          value => value.clone(),
+        };
+        (key, value)
+      })
+      .collect()
+  }
+}
+
+/// Copy the remote modules of the graph to the output directory and generate
+/// an import map which maps their specifiers to the copies. The mappings of
+/// the import map which the graph was built with, given with its URL and its
+/// contents, are rewritten into the generated import map. Returns the number
+/// of modules which were vendored.
+pub fn vendor(
+  graph: &Graph,
+  output_dir: &Path,
+  maybe_import_map: Option<(Url, String)>,
+) -> Result<usize, AnyError> {
+  let modules = VendoredModules::new(graph);
+
+  for (specifier, path) in modules.paths.iter() {
+    let mut source = graph.get_source(specifier).unwrap();
+    let media_type = graph.get_media_type(specifier);
+    // the types of a module given with a header are lost in its copy, so they
+    // are referenced from its source instead
+    if let Some(types) = graph.get_types_header(specifier) {
+      if media_type == Some(MediaType::JavaScript)
+        || media_type == Some(MediaType::JSX)
+      {
+        source = format!("/// <reference types=\"{}\" />\n{}", types, source);
+      }
+    }
+    let file_path = output_dir.join(path);
+    fs::create_dir_all(file_path.parent().unwrap())?;
+    fs::write(&file_path, source)?;
+  }
+
+  let mut import_map = json!({});
+  if let Some((base, contents)) = maybe_import_map {
+    let value: Value = serde_json::from_str(&contents)?;
+    if let Some(imports) = value.get("imports").and_then(|v| v.as_object()) {
+      import_map["imports"] = Value::Object(
+        modules.rewrite_specifier_map(imports, &base, output_dir),
+      );
+    }
+    if let Some(scopes) = value.get("scopes").and_then(|v| v.as_object()) {
+      let scopes: Map<String, Value> = scopes
+        .iter()
+        .map(|(scope, map)| {
+          let scope = modules.rewrite_address(scope, &base, output_dir);
+          let map = match map.as_object() {
+            Some(map) => Value::Object(
+              modules.rewrite_specifier_map(map, &base, output_dir),
+            ),
+            None => map.clone(),
+          };
+          (scope, map)
+        })
+        .collect();
+      import_map["scopes"] = Value::Object(scopes);
+    }
+  }
+  if import_map.get("imports").is_none() {
+    import_map["imports"] = json!({});
+  }
+  let imports = import_map["imports"].as_object_mut().unwrap();
+  for (key, path) in modules.get_imports(graph) {
+    imports.insert(key, Value::String(path));
+  }
+
+  fs::create_dir_all(output_dir)?;
+  fs::write(
+    output_dir.join(IMPORT_MAP_NAME),
+    serde_json::to_string_pretty(&import_map)?,
+  )?;
+
+  Ok(modules.paths.len())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+  }
+
+  #[test]
+  fn test_mirrored_path() {
+    assert_eq!(
+      mirrored_path(&url("https://deno.land/std@0.80.0/fs/mod.ts")),
+      "deno.land/std@0.80.0/fs/mod.ts"
+    );
+    assert_eq!(
+      mirrored_path(&url("http://localhost:4545/cli/tests/a.ts")),
+      "localhost_4545/cli/tests/a.ts"
+    );
+  }
+
+  #[test]
+  fn test_can_mirror() {
+    assert!(can_mirror(
+      &url("https://deno.land/std@0.80.0/fs/mod.ts"),
+      &MediaType::TypeScript
+    ));
+    assert!(can_mirror(
+      &url("https://deno.land/x/mod.d.ts"),
+      &MediaType::Dts
+    ));
+    assert!(!can_mirror(
+      &url("https://esm.sh/react@17.0.1"),
+      &MediaType::JavaScript
+    ));
+    assert!(!can_mirror(
+      &url("https://esm.sh/react.js?target=deno"),
+      &MediaType::JavaScript
+    ));
+    assert!(!can_mirror(
+      &url("https://deno.land/x/mod.js"),
+      &MediaType::TypeScript
+    ));
+    assert!(!can_mirror(
+      &url("https://deno.land/x/a%20b.ts"),
+      &MediaType::TypeScript
+    ));
+  }
+
+  #[test]
+  fn test_sanitized_path() {
+    assert_eq!(
+      sanitized_path(
+        &url("https://esm.sh/react@17.0.1"),
+        &MediaType::JavaScript
+      ),
+      "esm.sh/react@17.0.1"
+    );
+    assert_eq!(
+      sanitized_path(&url("https://example.com/"), &MediaType::JavaScript),
+      "example.com/index"
+    );
+    assert_eq!(
+      sanitized_path(
+        &url("https://example.com/a%20b/c:d"),
+        &MediaType::TypeScript
+      ),
+      "example.com/a_20b/c_d"
+    );
+  }
+
+  #[test]
+  fn test_relative_specifier() {
+    assert_eq!(
+      relative_specifier(Path::new("/a/vendor"), Path::new("/a/src/mod.ts")),
+      "../src/mod.ts"
+    );
+    assert_eq!(
+      relative_specifier(
+        Path::new("/a/vendor"),
+        Path::new("/a/vendor/deno.land/mod.ts")
+      ),
+      "./deno.land/mod.ts"
+    );
+  }
+
+  #[test]
+  fn test_ensure_output_dir() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("vendor");
+    assert!(ensure_output_dir(&output_dir, false).is_ok());
+    fs::create_dir(&output_dir).unwrap();
+    assert!(ensure_output_dir(&output_dir, false).is_ok());
+    fs::write(output_dir.join("mod.ts"), "").unwrap();
+    assert!(ensure_output_dir(&output_dir, false).is_err());
+    assert!(ensure_output_dir(&output_dir, true).is_ok());
+  }
+}
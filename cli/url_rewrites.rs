@@ -0,0 +1,156 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+
+/// A rule which fetches the remote modules under a URL prefix from another
+/// prefix, like an internal mirror of a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UrlRewrite {
+  from: String,
+  to: String,
+}
+
+/// The URL rewrite rules, which are parsed from a string of `;` separated
+/// entries of the form `from=to`, where both are URL prefixes. The rules only
+/// change where a module is fetched from, the module is still identified by
+/// its original specifier.
+#[derive(Debug, Clone, Default)]
+pub struct UrlRewrites(Vec<UrlRewrite>);
+
+impl UrlRewrites {
+  pub fn new(maybe_rewrites_str: Option<String>) -> Self {
+    let mut rewrites = Vec::new();
+    if let Some(rewrites_str) = maybe_rewrites_str {
+      for rewrite_str in rewrites_str.split(';') {
+        let rewrite_str = rewrite_str.trim();
+        if rewrite_str.is_empty() {
+          continue;
+        }
+        let rewrite = rewrite_str.find('=').and_then(|index| {
+          let (from, to) = rewrite_str.split_at(index);
+          let from = Url::parse(from.trim()).ok()?;
+          let to = Url::parse(to[1..].trim()).ok()?;
+          let is_remote =
+            |url: &Url| url.scheme() == "http" || url.scheme() == "https";
+          if is_remote(&from) && is_remote(&to) {
+            // a prefix which is only an origin is parsed with a trailing slash
+            let mut to = to.to_string();
+            if from.as_str().ends_with('/') && !to.ends_with('/') {
+              to.push('/');
+            }
+            Some(UrlRewrite {
+              from: from.to_string(),
+              to,
+            })
+          } else {
+            None
+          }
+        });
+        if let Some(rewrite) = rewrite {
+          rewrites.push(rewrite);
+        } else {
+          error!("Badly formed URL rewrite discarded: \"{}\"", rewrite_str);
+        }
+      }
+      debug!("Parsed {} URL rewrite(s).", rewrites.len());
+    }
+
+    Self(rewrites)
+  }
+
+  /// Return the URL to fetch a remote specifier from, if a rule applies to it.
+  /// When several rules apply, the one with the longest prefix is used.
+  pub fn rewrite(&self, specifier: &ModuleSpecifier) -> Option<Url> {
+    let specifier = specifier.as_str();
+    self
+      .0
+      .iter()
+      .filter(|rewrite| specifier.starts_with(&rewrite.from))
+      .max_by_key(|rewrite| rewrite.from.len())
+      .and_then(|rewrite| {
+        let url = format!("{}{}", rewrite.to, &specifier[rewrite.from.len()..]);
+        Url::parse(&url).ok()
+      })
+  }
+
+  /// Map a URL fetched from a rewritten prefix back to its original prefix,
+  /// which is used for the location of the redirects of a mirror, so the
+  /// redirected modules are identified by their original specifier too.
+  pub fn restore(&self, url: &Url) -> Option<Url> {
+    let url = url.as_str();
+    self
+      .0
+      .iter()
+      .filter(|rewrite| url.starts_with(&rewrite.to))
+      .max_by_key(|rewrite| rewrite.to.len())
+      .and_then(|rewrite| {
+        let url = format!("{}{}", rewrite.from, &url[rewrite.to.len()..]);
+        Url::parse(&url).ok()
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn specifier(s: &str) -> ModuleSpecifier {
+    ModuleSpecifier::resolve_url(s).unwrap()
+  }
+
+  #[test]
+  fn test_url_rewrite() {
+    let url_rewrites = UrlRewrites::new(Some(
+      "https://deno.land/x/=https://mirror.example.com/deno/x/".to_string(),
+    ));
+    let fixture = specifier("https://deno.land/x/oak@v6.4.1/mod.ts");
+    assert_eq!(
+      url_rewrites.rewrite(&fixture).unwrap().as_str(),
+      "https://mirror.example.com/deno/x/oak@v6.4.1/mod.ts"
+    );
+    let fixture = specifier("https://deno.land/std/fs/mod.ts");
+    assert_eq!(url_rewrites.rewrite(&fixture), None);
+    let fixture = specifier("https://deno.land.example.com/x/mod.ts");
+    assert_eq!(url_rewrites.rewrite(&fixture), None);
+  }
+
+  #[test]
+  fn test_url_rewrites_multiple() {
+    let url_rewrites = UrlRewrites::new(Some(
+      "https://deno.land/=http://mirror:8080/deno/; https://deno.land/x/=http://mirror:8080/x/;https://esm.sh=http://mirror:8080/esm;;bad=http://mirror;https://cdn.skypack.dev/=file:///mirror/".to_string(),
+    ));
+    let fixture = specifier("https://deno.land/std/fs/mod.ts");
+    assert_eq!(
+      url_rewrites.rewrite(&fixture).unwrap().as_str(),
+      "http://mirror:8080/deno/std/fs/mod.ts"
+    );
+    let fixture = specifier("https://deno.land/x/oak/mod.ts");
+    assert_eq!(
+      url_rewrites.rewrite(&fixture).unwrap().as_str(),
+      "http://mirror:8080/x/oak/mod.ts"
+    );
+    let fixture = specifier("https://esm.sh/react?target=deno");
+    assert_eq!(
+      url_rewrites.rewrite(&fixture).unwrap().as_str(),
+      "http://mirror:8080/esm/react?target=deno"
+    );
+    let fixture = specifier("https://cdn.skypack.dev/react");
+    assert_eq!(url_rewrites.rewrite(&fixture), None);
+  }
+
+  #[test]
+  fn test_url_rewrite_restore() {
+    let url_rewrites = UrlRewrites::new(Some(
+      "https://deno.land/x/=https://mirror.example.com/deno/x/".to_string(),
+    ));
+    let url = Url::parse("https://mirror.example.com/deno/x/oak@v6.4.1/mod.ts")
+      .unwrap();
+    assert_eq!(
+      url_rewrites.restore(&url).unwrap().as_str(),
+      "https://deno.land/x/oak@v6.4.1/mod.ts"
+    );
+    let url = Url::parse("https://mirror.example.com/other/mod.ts").unwrap();
+    assert_eq!(url_rewrites.restore(&url), None);
+  }
+}
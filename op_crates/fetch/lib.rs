@@ -28,11 +28,15 @@ use reqwest::Response;
 use serde::Deserialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::From;
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use uuid::Uuid;
 
 pub use reqwest; // Re-export reqwest
 
@@ -263,30 +267,65 @@ where
   struct CreateHttpClientOptions {
     ca_file: Option<String>,
     ca_data: Option<String>,
+    client_cert_file: Option<String>,
+    client_key_file: Option<String>,
+    http2: Option<bool>,
   }
 
   let args: CreateHttpClientOptions = serde_json::from_value(args)?;
 
-  if let Some(ca_file) = args.ca_file.clone() {
+  {
     let permissions = state.borrow::<FP>();
-    permissions.check_read(&PathBuf::from(ca_file))?;
+    if let Some(ca_file) = &args.ca_file {
+      permissions.check_read(&PathBuf::from(ca_file))?;
+    }
+    if let Some(client_cert_file) = &args.client_cert_file {
+      permissions.check_read(&PathBuf::from(client_cert_file))?;
+    }
+    if let Some(client_key_file) = &args.client_key_file {
+      permissions.check_read(&PathBuf::from(client_key_file))?;
+    }
   }
 
-  let client =
-    create_http_client(args.ca_file.as_deref(), args.ca_data.as_deref())
-      .unwrap();
+  let client_identity_files = match (
+    args.client_cert_file.as_deref(),
+    args.client_key_file.as_deref(),
+  ) {
+    (Some(cert_file), Some(key_file)) => Some((cert_file, key_file)),
+    (None, None) => None,
+    _ => {
+      return Err(type_error(
+        "Both a client certificate and a client key file must be specified",
+      ))
+    }
+  };
+
+  let client = create_http_client(
+    args.ca_file.as_deref(),
+    args.ca_data.as_deref(),
+    client_identity_files,
+    args.http2.unwrap_or(true),
+  )?;
 
   let rid = state.resource_table.add(HttpClientResource::new(client));
   Ok(json!(rid))
 }
 
 /// Create new instance of async reqwest::Client. This client supports
-/// proxies and doesn't follow redirects.
+/// proxies and doesn't follow redirects. Unless `http2` is false, HTTP/2 is
+/// used with the servers which select it with ALPN. The client authenticates
+/// with the PEM encoded certificate chain and key of `client_identity_files`
+/// to the servers which request a client certificate.
 fn create_http_client(
   ca_file: Option<&str>,
   ca_data: Option<&str>,
+  client_identity_files: Option<(&str, &str)>,
+  http2: bool,
 ) -> Result<Client, AnyError> {
   let mut builder = Client::builder().redirect(Policy::none()).use_rustls_tls();
+  if !http2 {
+    builder = builder.http1_only();
+  }
   if let Some(ca_data) = ca_data {
     let ca_data_vec = ca_data.as_bytes().to_vec();
     let cert = reqwest::Certificate::from_pem(&ca_data_vec)?;
@@ -297,7 +336,94 @@ fn create_http_client(
     let cert = reqwest::Certificate::from_pem(&buf)?;
     builder = builder.add_root_certificate(cert);
   }
+  if let Some((cert_file, key_file)) = client_identity_files {
+    let mut buf = Vec::new();
+    File::open(cert_file)?.read_to_end(&mut buf)?;
+    File::open(key_file)?.read_to_end(&mut buf)?;
+    let identity = reqwest::Identity::from_pem(&buf)?;
+    builder = builder.identity(identity);
+  }
   builder
     .build()
     .map_err(|_| deno_core::error::generic_error("Unable to build http client"))
 }
+
+/// The contents of a `Blob` which has been registered with
+/// `URL.createObjectURL()`.
+#[derive(Debug, Clone)]
+pub struct Blob {
+  pub data: Vec<u8>,
+  pub media_type: String,
+}
+
+/// The blobs which are reachable through a `blob:` URL. The store is shared by
+/// the workers of a program and the module loader, so that object URLs can be
+/// imported and used by other workers until they are revoked.
+#[derive(Debug, Clone, Default)]
+pub struct BlobUrlStore(Arc<Mutex<HashMap<Url, Blob>>>);
+
+impl BlobUrlStore {
+  pub fn get(&self, url: &Url) -> Option<Blob> {
+    let blob_store = self.0.lock().unwrap();
+    let mut url = url.clone();
+    url.set_fragment(None);
+    blob_store.get(&url).cloned()
+  }
+
+  pub fn insert(&self, blob: Blob) -> Url {
+    let url = Url::parse(&format!("blob:null/{}", Uuid::new_v4())).unwrap();
+    let mut blob_store = self.0.lock().unwrap();
+    blob_store.insert(url.clone(), blob);
+    url
+  }
+
+  pub fn remove(&self, url: &Url) {
+    let mut blob_store = self.0.lock().unwrap();
+    blob_store.remove(url);
+  }
+}
+
+pub fn op_create_object_url(
+  state: &mut OpState,
+  args: Value,
+  zero_copy: &mut [ZeroCopyBuf],
+) -> Result<Value, AnyError> {
+  #[derive(Deserialize)]
+  struct CreateObjectUrlArgs {
+    r#type: String,
+  }
+
+  let args: CreateObjectUrlArgs = serde_json::from_value(args)?;
+  let data = match zero_copy.len() {
+    0 => Vec::new(),
+    1 => zero_copy[0].to_vec(),
+    _ => panic!("Invalid number of arguments"),
+  };
+  let blob = Blob {
+    data,
+    media_type: args.r#type,
+  };
+
+  let blob_url_store = state.borrow::<BlobUrlStore>();
+  let url = blob_url_store.insert(blob);
+  Ok(json!(url.as_str()))
+}
+
+pub fn op_revoke_object_url(
+  state: &mut OpState,
+  args: Value,
+  _zero_copy: &mut [ZeroCopyBuf],
+) -> Result<Value, AnyError> {
+  #[derive(Deserialize)]
+  struct RevokeObjectUrlArgs {
+    url: String,
+  }
+
+  let args: RevokeObjectUrlArgs = serde_json::from_value(args)?;
+  // invalid URLs are silently ignored, as in browsers
+  if let Ok(url) = Url::parse(&args.url) {
+    let blob_url_store = state.borrow::<BlobUrlStore>();
+    blob_url_store.remove(&url);
+  }
+  Ok(json!({}))
+}
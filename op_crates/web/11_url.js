@@ -734,13 +734,8 @@
       return this.href;
     }
 
-    static createObjectURL() {
-      throw new Error("Not implemented");
-    }
-
-    static revokeObjectURL() {
-      throw new Error("Not implemented");
-    }
+    // `URL.createObjectURL()` and `URL.revokeObjectURL()` are defined by
+    // "deno_fetch", which implements `Blob`.
   }
 
   function parseIpv4Number(s) {
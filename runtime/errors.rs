@@ -124,6 +124,14 @@ fn get_request_error_class(error: &reqwest::Error) -> &'static str {
     .unwrap_or("Http")
 }
 
+fn get_hyper_error_class(_error: &hyper::Error) -> &'static str {
+  "Http"
+}
+
+fn get_http_error_class(_error: &hyper::http::Error) -> &'static str {
+  "TypeError"
+}
+
 fn get_serde_json_error_class(
   error: &serde_json::error::Error,
 ) -> &'static str {
@@ -179,6 +187,11 @@ pub fn get_error_class_name(e: &AnyError) -> Option<&'static str> {
         .map(get_env_var_error_class)
     })
     .or_else(|| e.downcast_ref::<io::Error>().map(get_io_error_class))
+    .or_else(|| e.downcast_ref::<hyper::Error>().map(get_hyper_error_class))
+    .or_else(|| {
+      e.downcast_ref::<hyper::http::Error>()
+        .map(get_http_error_class)
+    })
     .or_else(|| {
       e.downcast_ref::<ModuleResolutionError>()
         .map(get_module_resolution_error_class)
@@ -0,0 +1,166 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+((window) => {
+  const core = window.Deno.core;
+  const { errors } = window.__bootstrap.errors;
+  const { Request, Response } = window.__bootstrap.fetch;
+  const { ReadableStream } = window.__bootstrap.streams;
+
+  const READ_BUFFER_SIZE = 16 * 1024;
+
+  function opHttpStart(rid) {
+    return core.jsonOpSync("op_http_start", { rid });
+  }
+
+  function opHttpRequestNext(rid) {
+    return core.jsonOpAsync("op_http_request_next", { rid });
+  }
+
+  function opHttpRequestRead(rid, buffer) {
+    return core.jsonOpAsync("op_http_request_read", { rid }, buffer);
+  }
+
+  function opHttpRespond(args, body) {
+    return core.jsonOpAsync("op_http_respond", args, ...(body ? [body] : []));
+  }
+
+  function opHttpResponseWrite(rid, chunk) {
+    return core.jsonOpAsync("op_http_response_write", { rid }, chunk);
+  }
+
+  function serveHttp(conn) {
+    const rid = opHttpStart(conn.rid);
+    return new HttpConn(rid);
+  }
+
+  class HttpConn {
+    #rid = 0;
+
+    constructor(rid) {
+      this.#rid = rid;
+    }
+
+    get rid() {
+      return this.#rid;
+    }
+
+    async nextRequest() {
+      let next;
+      try {
+        next = await opHttpRequestNext(this.#rid);
+      } catch (error) {
+        // The connection was closed with `close()`.
+        if (error instanceof errors.BadResource) {
+          return null;
+        }
+        throw error;
+      }
+      if (next === null) {
+        return null;
+      }
+
+      const {
+        requestBodyRid,
+        responseSenderRid,
+        method,
+        headers,
+        url,
+      } = next;
+      const body = requestBodyRid === null
+        ? null
+        : createRequestBodyStream(requestBodyRid);
+      const request = new Request(url, { body, method, headers });
+      const respondWith = createRespondWith(responseSenderRid);
+
+      return { request, respondWith };
+    }
+
+    close() {
+      core.close(this.#rid);
+    }
+
+    [Symbol.asyncIterator]() {
+      const httpConn = this;
+      return {
+        async next() {
+          const requestEvent = await httpConn.nextRequest();
+          if (requestEvent === null) {
+            return { value: undefined, done: true };
+          }
+          return { value: requestEvent, done: false };
+        },
+      };
+    }
+  }
+
+  function createRequestBodyStream(rid) {
+    return new ReadableStream({
+      type: "bytes",
+      async pull(controller) {
+        try {
+          const chunk = new Uint8Array(READ_BUFFER_SIZE);
+          const nread = await opHttpRequestRead(rid, chunk);
+          if (nread > 0) {
+            controller.enqueue(chunk.subarray(0, nread));
+          } else {
+            controller.close();
+            core.close(rid);
+          }
+        } catch (e) {
+          controller.error(e);
+          controller.close();
+          core.close(rid);
+        }
+      },
+      cancel() {
+        core.close(rid);
+      },
+    });
+  }
+
+  function createRespondWith(responseSenderRid) {
+    return async function respondWith(response) {
+      if (response instanceof Promise) {
+        response = await response;
+      }
+      if (!(response instanceof Response)) {
+        throw new TypeError(
+          "First argument to respondWith must be a Response or a promise resolving to a Response.",
+        );
+      }
+
+      const isStreaming = response._bodySource instanceof ReadableStream;
+      const body = isStreaming
+        ? null
+        : new Uint8Array(await response.arrayBuffer());
+      const { bodyRid } = await opHttpRespond({
+        rid: responseSenderRid,
+        status: response.status,
+        headers: [...response.headers],
+      }, body);
+
+      if (bodyRid !== null) {
+        try {
+          const reader = response.body.getReader();
+          while (true) {
+            const { done, value } = await reader.read();
+            if (done) break;
+            if (!(value instanceof Uint8Array)) {
+              await reader.cancel("value not a Uint8Array");
+              break;
+            }
+            await opHttpResponseWrite(bodyRid, value);
+          }
+        } finally {
+          // Closing the resource ends the body of the response.
+          core.close(bodyRid);
+        }
+      }
+    };
+  }
+
+  window.__bootstrap.http = {
+    serveHttp,
+    HttpConn,
+  };
+})(this);
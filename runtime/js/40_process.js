@@ -93,6 +93,11 @@
     cmd,
     cwd = undefined,
     env = {},
+    clearEnv = false,
+    uid = undefined,
+    gid = undefined,
+    newProcessGroup = false,
+    newSession = false,
     stdout = "inherit",
     stderr = "inherit",
     stdin = "inherit",
@@ -104,6 +109,11 @@
       cmd: cmd.map(String),
       cwd,
       env: Object.entries(env),
+      clearEnv,
+      uid,
+      gid,
+      newProcessGroup,
+      newSession,
       stdin: isRid(stdin) ? "" : stdin,
       stdout: isRid(stdout) ? "" : stdout,
       stderr: isRid(stderr) ? "" : stderr,
@@ -114,8 +124,32 @@
     return new Process(res);
   }
 
+  // Reads stdout and stderr concurrently while waiting for the process to
+  // exit, so a child blocked on writing to a full pipe can't deadlock it.
+  async function output({
+    stdin = "null",
+    stdout = "piped",
+    stderr = "piped",
+    ...options
+  }) {
+    const p = run({ ...options, stdin, stdout, stderr });
+    // Nothing is written to a piped stdin, the child would wait for it.
+    p.stdin?.close();
+    try {
+      const [status, stdoutOutput, stderrOutput] = await Promise.all([
+        p.status(),
+        p.stdout ? p.output() : null,
+        p.stderr ? p.stderrOutput() : null,
+      ]);
+      return { status, stdout: stdoutOutput, stderr: stderrOutput };
+    } finally {
+      p.close();
+    }
+  }
+
   window.__bootstrap.process = {
     run,
+    output,
     Process,
     kill: opKill,
   };
@@ -22,25 +22,83 @@
     return core.jsonOpAsync("op_start_tls", args);
   }
 
+  function opTlsListenerReload(args) {
+    return core.jsonOpSync("op_tls_listener_reload", args);
+  }
+
+  class TlsConn extends Conn {
+    #alpnProtocol = null;
+    #peerCertificates = null;
+
+    constructor(rid, remoteAddr, localAddr, alpnProtocol, peerCertificates) {
+      super(rid, remoteAddr, localAddr);
+      this.#alpnProtocol = alpnProtocol;
+      this.#peerCertificates = peerCertificates;
+    }
+
+    get alpnProtocol() {
+      return this.#alpnProtocol;
+    }
+
+    get peerCertificates() {
+      return this.#peerCertificates;
+    }
+  }
+
+  function createTlsConn(res) {
+    const peerCertificates = res.peerCertificates === null
+      ? null
+      : res.peerCertificates.map((cert) => new Uint8Array(cert));
+    return new TlsConn(
+      res.rid,
+      res.remoteAddr,
+      res.localAddr,
+      res.alpnProtocol,
+      peerCertificates,
+    );
+  }
+
   async function connectTls({
     port,
     hostname = "127.0.0.1",
     transport = "tcp",
     certFile = undefined,
+    caCerts = undefined,
+    clientCertFile = undefined,
+    clientKeyFile = undefined,
+    certChain = undefined,
+    privateKey = undefined,
+    alpnProtocols = undefined,
   }) {
     const res = await opConnectTls({
       port,
       hostname,
       transport,
       certFile,
+      caCerts,
+      clientCertFile,
+      clientKeyFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
     });
-    return new Conn(res.rid, res.remoteAddr, res.localAddr);
+    return createTlsConn(res);
   }
 
   class TLSListener extends Listener {
     async accept() {
       const res = await opAcceptTLS(this.rid);
-      return new Conn(res.rid, res.remoteAddr, res.localAddr);
+      return createTlsConn(res);
+    }
+
+    reloadCertificates({ certFile, keyFile, certChain, privateKey }) {
+      opTlsListenerReload({
+        rid: this.rid,
+        certFile,
+        keyFile,
+        certChain,
+        privateKey,
+      });
     }
   }
 
@@ -48,29 +106,56 @@
     port,
     certFile,
     keyFile,
+    certChain = undefined,
+    privateKey = undefined,
     hostname = "0.0.0.0",
     transport = "tcp",
+    alpnProtocols = undefined,
+    clientAuth = undefined,
+    clientCaFile = undefined,
+    clientCaCerts = undefined,
   }) {
     const res = opListenTls({
       port,
       certFile,
       keyFile,
+      certChain,
+      privateKey,
       hostname,
       transport,
+      alpnProtocols,
+      clientAuth,
+      clientCaFile,
+      clientCaCerts,
     });
     return new TLSListener(res.rid, res.localAddr);
   }
 
   async function startTls(
     conn,
-    { hostname = "127.0.0.1", certFile } = {},
+    {
+      hostname = "127.0.0.1",
+      certFile,
+      caCerts,
+      clientCertFile,
+      clientKeyFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
+    } = {},
   ) {
     const res = await opStartTls({
       rid: conn.rid,
       hostname,
       certFile,
+      caCerts,
+      clientCertFile,
+      clientKeyFile,
+      certChain,
+      privateKey,
+      alpnProtocols,
     });
-    return new Conn(res.rid, res.remoteAddr, res.localAddr);
+    return createTlsConn(res);
   }
 
   window.__bootstrap.tls = {
@@ -78,5 +163,6 @@
     listenTls,
     connectTls,
     TLSListener,
+    TlsConn,
   };
 })(this);
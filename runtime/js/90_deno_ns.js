@@ -100,6 +100,7 @@
     PermissionStatus: __bootstrap.permissions.PermissionStatus,
     openPlugin: __bootstrap.plugins.openPlugin,
     kill: __bootstrap.process.kill,
+    output: __bootstrap.process.output,
     setRaw: __bootstrap.tty.setRaw,
     consoleSize: __bootstrap.tty.consoleSize,
     DiagnosticCategory: __bootstrap.diagnostics.DiagnosticCategory,
@@ -116,6 +117,7 @@
     connect: __bootstrap.netUnstable.connect,
     listenDatagram: __bootstrap.netUnstable.listenDatagram,
     startTls: __bootstrap.tls.startTls,
+    serveHttp: __bootstrap.http.serveHttp,
     fstatSync: __bootstrap.fs.fstatSync,
     fstat: __bootstrap.fs.fstat,
     ftruncateSync: __bootstrap.fs.ftruncateSync,
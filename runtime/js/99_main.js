@@ -153,8 +153,7 @@ delete Object.prototype.__proto__;
     build.setBuildInfo(runtimeOptions.target);
     util.setLogDebug(runtimeOptions.debugFlag, source);
     // TODO(bartlomieju): a very crude way to disable
-    // source mapping of errors. This condition is true
-    // only for compiled standalone binaries.
+    // source mapping of errors.
     let prepareStackTrace;
     if (runtimeOptions.applySourceMaps) {
       prepareStackTrace = core.createPrepareStackTrace(
@@ -22,5 +22,6 @@ pub mod ops;
 pub mod permissions;
 pub mod resolve_addr;
 pub mod tokio_util;
+pub mod virtual_fs;
 pub mod web_worker;
 pub mod worker;
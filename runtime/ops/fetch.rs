@@ -2,11 +2,13 @@
 use crate::http_util;
 use crate::permissions::Permissions;
 use deno_fetch::reqwest;
+use deno_fetch::BlobUrlStore;
 
 pub fn init(
   rt: &mut deno_core::JsRuntime,
   user_agent: String,
   maybe_ca_file: Option<&str>,
+  blob_url_store: BlobUrlStore,
 ) {
   {
     let op_state = rt.op_state();
@@ -14,6 +16,7 @@ pub fn init(
     state.put::<reqwest::Client>({
       http_util::create_http_client(user_agent, maybe_ca_file).unwrap()
     });
+    state.put::<BlobUrlStore>(blob_url_store);
   }
   super::reg_json_async(rt, "op_fetch", deno_fetch::op_fetch::<Permissions>);
   super::reg_json_async(rt, "op_fetch_read", deno_fetch::op_fetch_read);
@@ -22,4 +25,14 @@ pub fn init(
     "op_create_http_client",
     deno_fetch::op_create_http_client::<Permissions>,
   );
+  super::reg_json_sync(
+    rt,
+    "op_create_object_url",
+    deno_fetch::op_create_object_url,
+  );
+  super::reg_json_sync(
+    rt,
+    "op_revoke_object_url",
+    deno_fetch::op_revoke_object_url,
+  );
 }
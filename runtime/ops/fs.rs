@@ -4,6 +4,8 @@ use super::io::std_file_resource;
 use super::io::StreamResource;
 use crate::fs_util::canonicalize_path;
 use crate::permissions::Permissions;
+use crate::virtual_fs::VirtualFile;
+use crate::virtual_fs::VirtualFs;
 use deno_core::error::custom_error;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
@@ -134,11 +136,34 @@ struct OpenOptions {
   create_new: bool,
 }
 
+/// Open the file from the `VirtualFs` of a standalone binary, if there is
+/// one and the file is only opened for reading. Embedded files are part of
+/// the executable, so no read permission is required for them.
+fn open_virtual_file(
+  state: &mut OpState,
+  args: &OpenArgs,
+) -> Result<Option<u32>, AnyError> {
+  let options = &args.options;
+  if !options.read
+    || options.write
+    || options.append
+    || options.create
+    || options.truncate
+    || options.create_new
+  {
+    return Ok(None);
+  }
+  let maybe_file = match state.try_borrow::<VirtualFs>() {
+    Some(virtual_fs) => virtual_fs.open(Path::new(&args.path))?,
+    None => return Ok(None),
+  };
+  Ok(maybe_file.map(|file| state.resource_table.add(file)))
+}
+
 fn open_helper(
   state: &mut OpState,
-  args: Value,
+  args: OpenArgs,
 ) -> Result<(PathBuf, std::fs::OpenOptions), AnyError> {
-  let args: OpenArgs = serde_json::from_value(args)?;
   let path = Path::new(&args.path).to_path_buf();
 
   let mut open_options = std::fs::OpenOptions::new();
@@ -182,6 +207,10 @@ fn op_open_sync(
   args: Value,
   _zero_copy: &mut [ZeroCopyBuf],
 ) -> Result<Value, AnyError> {
+  let args: OpenArgs = serde_json::from_value(args)?;
+  if let Some(rid) = open_virtual_file(state, &args)? {
+    return Ok(json!(rid));
+  }
   let (path, open_options) = open_helper(state, args)?;
   let std_file = open_options.open(path)?;
   let tokio_file = tokio::fs::File::from_std(std_file);
@@ -195,6 +224,10 @@ async fn op_open_async(
   args: Value,
   _zero_copy: BufVec,
 ) -> Result<Value, AnyError> {
+  let args: OpenArgs = serde_json::from_value(args)?;
+  if let Some(rid) = open_virtual_file(&mut state.borrow_mut(), &args)? {
+    return Ok(json!(rid));
+  }
   let (path, open_options) = open_helper(&mut state.borrow_mut(), args)?;
   let tokio_file = tokio::fs::OpenOptions::from(open_options)
     .open(path)
@@ -236,6 +269,9 @@ fn op_seek_sync(
   _zero_copy: &mut [ZeroCopyBuf],
 ) -> Result<Value, AnyError> {
   let (rid, seek_from) = seek_helper(args)?;
+  if let Some(file) = state.resource_table.get::<VirtualFile>(rid) {
+    return Ok(json!(file.seek(seek_from)?));
+  }
   let pos = std_file_resource(state, rid, |r| match r {
     Ok(std_file) => std_file.seek(seek_from).map_err(AnyError::from),
     Err(_) => Err(type_error(
@@ -251,6 +287,10 @@ async fn op_seek_async(
   _zero_copy: BufVec,
 ) -> Result<Value, AnyError> {
   let (rid, seek_from) = seek_helper(args)?;
+  let maybe_file = state.borrow().resource_table.get::<VirtualFile>(rid);
+  if let Some(file) = maybe_file {
+    return Ok(json!(file.seek(seek_from)?));
+  }
   // TODO(ry) This is a fake async op. We need to use poll_fn,
   // tokio::fs::File::start_seek and tokio::fs::File::poll_complete
   let pos = std_file_resource(&mut state.borrow_mut(), rid, |r| match r {
@@ -0,0 +1,547 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use crate::ops::io::StreamResource;
+use crate::ops::io::TcpStreamResource;
+use deno_core::error::bad_resource_id;
+use deno_core::error::custom_error;
+use deno_core::error::resource_unavailable;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::oneshot;
+use deno_core::futures::future::poll_fn;
+use deno_core::futures::future::FutureExt;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::stream::StreamExt;
+use deno_core::futures::task::waker_ref;
+use deno_core::futures::task::ArcWake;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::AsyncRefCell;
+use deno_core::BufVec;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ZeroCopyBuf;
+use hyper::body::Bytes;
+use hyper::body::HttpBody;
+use hyper::header::HOST;
+use hyper::server::conn::Http;
+use hyper::service::Service as HyperService;
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio_rustls::rustls::Session;
+
+pub fn init(rt: &mut deno_core::JsRuntime) {
+  super::reg_json_sync(rt, "op_http_start", op_http_start);
+  super::reg_json_async(rt, "op_http_request_next", op_http_request_next);
+  super::reg_json_async(rt, "op_http_request_read", op_http_request_read);
+  super::reg_json_async(rt, "op_http_respond", op_http_respond);
+  super::reg_json_async(rt, "op_http_response_write", op_http_response_write);
+}
+
+/// Runs the tasks hyper spawns for the streams of a HTTP/2 connection. They
+/// can't be spawned on the runtime as they aren't `Send`, so they are polled
+/// together with the connection instead.
+#[derive(Clone, Default)]
+struct LocalExecutor(
+  Rc<RefCell<FuturesUnordered<LocalBoxFuture<'static, ()>>>>,
+);
+
+impl LocalExecutor {
+  fn poll_tasks(&self, cx: &mut Context<'_>) {
+    let mut tasks = self.0.borrow_mut();
+    while let Poll::Ready(Some(())) = tasks.poll_next_unpin(cx) {}
+  }
+
+  fn clear(&self) {
+    *self.0.borrow_mut() = FuturesUnordered::new();
+  }
+}
+
+impl<Fut> hyper::rt::Executor<Fut> for LocalExecutor
+where
+  Fut: Future + 'static,
+  Fut::Output: 'static,
+{
+  fn execute(&self, fut: Fut) {
+    self.0.borrow().push(fut.map(|_| ()).boxed_local());
+  }
+}
+
+type ConnFuture = Pin<Box<dyn Future<Output = Result<(), hyper::Error>>>>;
+
+struct NextRequest {
+  request: Request<Body>,
+  response_tx: oneshot::Sender<Response<Body>>,
+}
+
+/// The hyper service of a connection, which hands each request over to the
+/// `op_http_request_next` op and waits for the response of `op_http_respond`.
+#[derive(Clone, Default)]
+struct Service {
+  inner: Rc<RefCell<Option<NextRequest>>>,
+}
+
+impl HyperService<Request<Body>> for Service {
+  type Response = Response<Body>;
+  type Error = AnyError;
+  type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, AnyError>>>>;
+
+  fn poll_ready(
+    &mut self,
+    _cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    // The connection is polled again by the op which takes the pending
+    // request, so there is no need to register a waker here.
+    if self.inner.borrow().is_some() {
+      Poll::Pending
+    } else {
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  fn call(&mut self, request: Request<Body>) -> Self::Future {
+    let (response_tx, response_rx) = oneshot::channel();
+    self.inner.borrow_mut().replace(NextRequest {
+      request,
+      response_tx,
+    });
+    async move {
+      response_rx.await.map_err(|_| {
+        custom_error("BrokenPipe", "The request was dropped without a response")
+      })
+    }
+    .boxed_local()
+  }
+}
+
+/// Wakes all the ops which are polling the same connection, as a future only
+/// wakes the waker of its last poll.
+#[derive(Default)]
+struct ConnWaker(Mutex<Vec<Waker>>);
+
+impl ConnWaker {
+  fn register(&self, waker: &Waker) {
+    let mut wakers = self.0.lock().unwrap();
+    if !wakers.iter().any(|w| w.will_wake(waker)) {
+      wakers.push(waker.clone());
+    }
+  }
+}
+
+impl ArcWake for ConnWaker {
+  fn wake_by_ref(arc_self: &Arc<Self>) {
+    let wakers = std::mem::take(&mut *arc_self.0.lock().unwrap());
+    for waker in wakers {
+      waker.wake();
+    }
+  }
+}
+
+struct ConnResource {
+  hyper_connection: RefCell<Option<ConnFuture>>,
+  executor: LocalExecutor,
+  deno_service: Service,
+  waker: Arc<ConnWaker>,
+  scheme: &'static str,
+  addr: SocketAddr,
+}
+
+impl ConnResource {
+  fn new<I>(io: I, scheme: &'static str, addr: SocketAddr, h2: bool) -> Self
+  where
+    I: AsyncRead + AsyncWrite + Unpin + 'static,
+  {
+    let executor = LocalExecutor::default();
+    let deno_service = Service::default();
+    let mut http = Http::new().with_executor(executor.clone());
+    if h2 {
+      http.http2_only(true);
+    } else {
+      http.http1_only(true);
+    }
+    let hyper_connection = http.serve_connection(io, deno_service.clone());
+    Self {
+      hyper_connection: RefCell::new(Some(Box::pin(hyper_connection))),
+      executor,
+      deno_service,
+      waker: Default::default(),
+      scheme,
+      addr,
+    }
+  }
+
+  /// Drives the connection, which reads the requests and writes the responses
+  /// of the client. It is polled by all the ops of the connection, so it
+  /// makes progress as long as one of them is pending. Returns `Ready(Ok(()))`
+  /// once the connection is closed.
+  fn poll(&self, cx: &mut Context<'_>) -> Poll<Result<(), AnyError>> {
+    let mut maybe_connection = self.hyper_connection.borrow_mut();
+    let connection = match maybe_connection.as_mut() {
+      Some(connection) => connection,
+      None => return Poll::Ready(Ok(())),
+    };
+    self.waker.register(cx.waker());
+    let waker = waker_ref(&self.waker);
+    let mut conn_cx = Context::from_waker(&waker);
+    let result = connection.as_mut().poll(&mut conn_cx);
+    self.executor.poll_tasks(&mut conn_cx);
+    match result {
+      Poll::Ready(result) => {
+        maybe_connection.take();
+        self.executor.clear();
+        ArcWake::wake_by_ref(&self.waker);
+        Poll::Ready(result.map_err(AnyError::from))
+      }
+      Poll::Pending => Poll::Pending,
+    }
+  }
+
+  fn url(&self, request: &Request<Body>) -> String {
+    let uri = request.uri();
+    if uri.scheme().is_some() {
+      return uri.to_string();
+    }
+    let host = request
+      .headers()
+      .get(HOST)
+      .and_then(|host| host.to_str().ok())
+      .map(String::from)
+      .unwrap_or_else(|| self.addr.to_string());
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    format!("{}://{}{}", self.scheme, host, path)
+  }
+}
+
+impl Resource for ConnResource {
+  fn name(&self) -> Cow<str> {
+    "httpConnection".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    // Dropping the connection closes the underlying stream, the pending ops
+    // of the connection are woken up so they observe that it was closed.
+    self.hyper_connection.borrow_mut().take();
+    self.executor.clear();
+    ArcWake::wake_by_ref(&self.waker);
+  }
+}
+
+struct RequestBody {
+  body: Body,
+  remainder: Bytes,
+}
+
+struct RequestBodyResource {
+  body: AsyncRefCell<RequestBody>,
+  conn: Rc<ConnResource>,
+  cancel: CancelHandle,
+}
+
+impl Resource for RequestBodyResource {
+  fn name(&self) -> Cow<str> {
+    "httpRequestBody".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel()
+  }
+}
+
+struct ResponseSenderResource {
+  sender: oneshot::Sender<Response<Body>>,
+  conn: Rc<ConnResource>,
+}
+
+impl Resource for ResponseSenderResource {
+  fn name(&self) -> Cow<str> {
+    "httpResponseSender".into()
+  }
+}
+
+struct ResponseBodyResource {
+  body: AsyncRefCell<hyper::body::Sender>,
+  conn: Rc<ConnResource>,
+}
+
+impl Resource for ResponseBodyResource {
+  fn name(&self) -> Cow<str> {
+    "httpResponseBody".into()
+  }
+}
+
+#[derive(Deserialize)]
+struct HttpArgs {
+  rid: u32,
+}
+
+fn op_http_start(
+  state: &mut OpState,
+  args: Value,
+  _zero_copy: &mut [ZeroCopyBuf],
+) -> Result<Value, AnyError> {
+  super::check_unstable(state, "Deno.serveHttp");
+  let args: HttpArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+
+  let conn_resource =
+    if state.resource_table.get::<TcpStreamResource>(rid).is_some() {
+      let resource_rc =
+        state.resource_table.take::<TcpStreamResource>(rid).unwrap();
+      let resource =
+        Rc::try_unwrap(resource_rc).map_err(|_| resource_unavailable())?;
+      let (read_half, write_half) = resource.into_inner();
+      let tcp_stream = read_half.reunite(write_half)?;
+      let addr = tcp_stream.local_addr()?;
+      ConnResource::new(tcp_stream, "http", addr, false)
+    } else if state
+      .resource_table
+      .get::<StreamResource>(rid)
+      .map_or(false, |r| r.is_server_tls_stream())
+    {
+      let resource_rc =
+        state.resource_table.take::<StreamResource>(rid).unwrap();
+      let resource =
+        Rc::try_unwrap(resource_rc).map_err(|_| resource_unavailable())?;
+      let tls_stream = resource.into_server_tls_stream().unwrap();
+      let (tcp_stream, tls_session) = tls_stream.get_ref();
+      let addr = tcp_stream.local_addr()?;
+      // HTTP/2 is only served when the client selected it with ALPN.
+      let h2 = tls_session.get_alpn_protocol() == Some(b"h2");
+      ConnResource::new(tls_stream, "https", addr, h2)
+    } else {
+      return Err(bad_resource_id());
+    };
+
+  let rid = state.resource_table.add(conn_resource);
+  Ok(json!(rid))
+}
+
+async fn op_http_request_next(
+  state: Rc<RefCell<OpState>>,
+  args: Value,
+  _zero_copy: BufVec,
+) -> Result<Value, AnyError> {
+  let args: HttpArgs = serde_json::from_value(args)?;
+  let conn_resource = state
+    .borrow()
+    .resource_table
+    .get::<ConnResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+
+  let maybe_next_request = poll_fn(|cx| {
+    let result = conn_resource.poll(cx);
+    if let Some(next_request) =
+      conn_resource.deno_service.inner.borrow_mut().take()
+    {
+      return Poll::Ready(Ok(Some(next_request)));
+    }
+    match result {
+      Poll::Ready(Ok(())) => Poll::Ready(Ok(None)),
+      Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+      Poll::Pending => Poll::Pending,
+    }
+  })
+  .await?;
+  let NextRequest {
+    request,
+    response_tx,
+  } = match maybe_next_request {
+    Some(next_request) => next_request,
+    None => return Ok(Value::Null),
+  };
+
+  let url = conn_resource.url(&request);
+  let method = request.method().to_string();
+  let headers = request
+    .headers()
+    .iter()
+    .map(|(key, value)| {
+      (
+        key.to_string(),
+        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+      )
+    })
+    .collect::<Vec<_>>();
+  let body = request.into_body();
+
+  let mut state = state.borrow_mut();
+  let maybe_request_body_rid = if body.is_end_stream() {
+    None
+  } else {
+    Some(state.resource_table.add(RequestBodyResource {
+      body: AsyncRefCell::new(RequestBody {
+        body,
+        remainder: Bytes::new(),
+      }),
+      conn: conn_resource.clone(),
+      cancel: Default::default(),
+    }))
+  };
+  let response_sender_rid = state.resource_table.add(ResponseSenderResource {
+    sender: response_tx,
+    conn: conn_resource.clone(),
+  });
+
+  Ok(json!({
+    "requestBodyRid": maybe_request_body_rid,
+    "responseSenderRid": response_sender_rid,
+    "method": method,
+    "headers": headers,
+    "url": url,
+  }))
+}
+
+async fn op_http_request_read(
+  state: Rc<RefCell<OpState>>,
+  args: Value,
+  zero_copy: BufVec,
+) -> Result<Value, AnyError> {
+  assert_eq!(zero_copy.len(), 1, "Invalid number of arguments");
+  let mut zero_copy = zero_copy[0].clone();
+  let args: HttpArgs = serde_json::from_value(args)?;
+
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<RequestBodyResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+  let conn_resource = resource.conn.clone();
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let mut body = RcRef::map(&resource, |r| &r.body).borrow_mut().await;
+
+  let nread = poll_fn(|cx| {
+    while body.remainder.is_empty() {
+      // The body is received by the connection, so it has to be driven while
+      // waiting for the next chunk.
+      if let Poll::Ready(Err(e)) = conn_resource.poll(cx) {
+        return Poll::Ready(Err(e));
+      }
+      match Pin::new(&mut body.body).poll_data(cx) {
+        Poll::Ready(Some(Ok(chunk))) => body.remainder = chunk,
+        Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e.into())),
+        Poll::Ready(None) => return Poll::Ready(Ok(0)),
+        Poll::Pending => return Poll::Pending,
+      }
+    }
+    let nread = body.remainder.len().min(zero_copy.len());
+    let chunk = body.remainder.split_to(nread);
+    zero_copy[..nread].copy_from_slice(&chunk);
+    Poll::Ready(Ok(nread))
+  })
+  .try_or_cancel(cancel)
+  .await?;
+
+  Ok(json!(nread))
+}
+
+#[derive(Deserialize)]
+struct RespondArgs {
+  rid: u32,
+  status: u16,
+  headers: Vec<(String, String)>,
+}
+
+async fn op_http_respond(
+  state: Rc<RefCell<OpState>>,
+  args: Value,
+  zero_copy: BufVec,
+) -> Result<Value, AnyError> {
+  let args: RespondArgs = serde_json::from_value(args)?;
+
+  let resource_rc = state
+    .borrow_mut()
+    .resource_table
+    .take::<ResponseSenderResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+  let ResponseSenderResource { sender, conn } =
+    Rc::try_unwrap(resource_rc).map_err(|_| resource_unavailable())?;
+
+  let mut builder = Response::builder().status(args.status);
+  for (key, value) in args.headers.iter() {
+    builder = builder.header(key.as_str(), value.as_str());
+  }
+  // Without a buffer, the body of the response is streamed with
+  // `op_http_response_write` and ends when its resource is closed.
+  let (body, maybe_body_sender) = match zero_copy.len() {
+    0 => {
+      let (body_sender, body) = Body::channel();
+      (body, Some(body_sender))
+    }
+    1 => (Body::from(zero_copy[0].to_vec()), None),
+    _ => panic!("Invalid number of arguments"),
+  };
+  let response = builder.body(body)?;
+
+  if sender.send(response).is_err() {
+    return Err(custom_error(
+      "BrokenPipe",
+      "The connection was closed before the response was sent",
+    ));
+  }
+
+  let maybe_body_rid = maybe_body_sender.map(|body_sender| {
+    state.borrow_mut().resource_table.add(ResponseBodyResource {
+      body: AsyncRefCell::new(body_sender),
+      conn: conn.clone(),
+    })
+  });
+
+  // Give the connection a chance to write the response right away.
+  poll_fn(|cx| match conn.poll(cx) {
+    Poll::Ready(result) => Poll::Ready(result),
+    Poll::Pending => Poll::Ready(Ok(())),
+  })
+  .await?;
+
+  Ok(json!({ "bodyRid": maybe_body_rid }))
+}
+
+async fn op_http_response_write(
+  state: Rc<RefCell<OpState>>,
+  args: Value,
+  zero_copy: BufVec,
+) -> Result<Value, AnyError> {
+  assert_eq!(zero_copy.len(), 1, "Invalid number of arguments");
+  let args: HttpArgs = serde_json::from_value(args)?;
+
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<ResponseBodyResource>(args.rid)
+    .ok_or_else(bad_resource_id)?;
+  let mut body = RcRef::map(&resource, |r| &r.body).borrow_mut().await;
+  let mut send_data = body
+    .send_data(Bytes::from(zero_copy[0].to_vec()))
+    .boxed_local();
+
+  poll_fn(|cx| {
+    // The chunk is only accepted once the connection has written the previous
+    // ones, so it has to be driven while waiting.
+    if let Poll::Ready(Err(e)) = resource.conn.poll(cx) {
+      return Poll::Ready(Err(e));
+    }
+    send_data.poll_unpin(cx).map_err(AnyError::from)
+  })
+  .await?;
+
+  Ok(json!({}))
+}
@@ -3,6 +3,7 @@
 use super::dispatch_minimal::minimal_op;
 use super::dispatch_minimal::MinimalOp;
 use crate::metrics::metrics_op;
+use crate::virtual_fs::VirtualFile;
 use deno_core::error::bad_resource_id;
 use deno_core::error::resource_unavailable;
 use deno_core::error::type_error;
@@ -203,6 +204,19 @@ where
 pub type TcpStreamResource =
   FullDuplexResource<tcp::OwnedReadHalf, tcp::OwnedWriteHalf>;
 
+impl TcpStreamResource {
+  /// Returns the file descriptor of the socket, e.g. to pass it to a child
+  /// process.
+  #[cfg(unix)]
+  pub fn raw_fd(self: &Rc<Self>) -> Result<std::os::unix::io::RawFd, AnyError> {
+    use std::os::unix::io::AsRawFd;
+    let wr = RcRef::map(self, |r| &r.wr)
+      .try_borrow()
+      .ok_or_else(resource_unavailable)?;
+    Ok(wr.as_ref().as_raw_fd())
+  }
+}
+
 impl Resource for TcpStreamResource {
   fn name(&self) -> Cow<str> {
     "tcpStream".into()
@@ -313,6 +327,43 @@ impl StreamResource {
     }
   }
 
+  pub fn is_server_tls_stream(&self) -> bool {
+    self.server_tls_stream.is_some()
+  }
+
+  pub fn into_server_tls_stream(self) -> Option<ServerTlsStream<TcpStream>> {
+    self.server_tls_stream.map(AsyncRefCell::into_inner)
+  }
+
+  /// Returns the file descriptor of a unix socket or a pipe of a child
+  /// process, e.g. to pass it to another child process. TLS streams have no
+  /// usable descriptor, their data is encrypted in this process.
+  #[cfg(unix)]
+  pub fn raw_fd(self: &Rc<Self>) -> Result<std::os::unix::io::RawFd, AnyError> {
+    use deno_core::error::not_supported;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::io::RawFd;
+
+    fn stream_fd<T: AsRawFd + 'static>(
+      cell: RcRef<AsyncRefCell<T>>,
+    ) -> Result<RawFd, AnyError> {
+      let stream = cell.try_borrow().ok_or_else(resource_unavailable)?;
+      Ok(stream.as_raw_fd())
+    }
+
+    if self.unix_stream.is_some() {
+      stream_fd(RcRef::map(self, |r| r.unix_stream.as_ref().unwrap()))
+    } else if self.child_stdin.is_some() {
+      stream_fd(RcRef::map(self, |r| r.child_stdin.as_ref().unwrap()))
+    } else if self.child_stdout.is_some() {
+      stream_fd(RcRef::map(self, |r| r.child_stdout.as_ref().unwrap()))
+    } else if self.child_stderr.is_some() {
+      stream_fd(RcRef::map(self, |r| r.child_stderr.as_ref().unwrap()))
+    } else {
+      Err(not_supported())
+    }
+  }
+
   async fn read(self: Rc<Self>, buf: &mut [u8]) -> Result<usize, AnyError> {
     // TODO(bartlomieju): in the future, it would be better for `StreamResource`
     // to be an enum instead a struct with many `Option` fields, however I
@@ -471,17 +522,23 @@ pub fn op_read(
 
   if is_sync {
     MinimalOp::Sync({
-      // First we look up the rid in the resource table.
-      std_file_resource(&mut state.borrow_mut(), rid as u32, move |r| match r {
-        Ok(std_file) => {
-          use std::io::Read;
-          std_file
-            .read(&mut zero_copy[0])
-            .map(|n: usize| n as i32)
-            .map_err(AnyError::from)
-        }
-        Err(_) => Err(type_error("sync read not allowed on this resource")),
-      })
+      let mut state = state.borrow_mut();
+      if let Some(file) = state.resource_table.get::<VirtualFile>(rid as u32) {
+        // Files of a `VirtualFs` are read from memory.
+        file.read(&mut zero_copy[0]).map(|n: usize| n as i32)
+      } else {
+        // First we look up the rid in the resource table.
+        std_file_resource(&mut state, rid as u32, move |r| match r {
+          Ok(std_file) => {
+            use std::io::Read;
+            std_file
+              .read(&mut zero_copy[0])
+              .map(|n: usize| n as i32)
+              .map_err(AnyError::from)
+          }
+          Err(_) => Err(type_error("sync read not allowed on this resource")),
+        })
+      }
     })
   } else {
     let mut zero_copy = zero_copy[0].clone();
@@ -498,6 +555,8 @@ pub fn op_read(
           stream.read(&mut zero_copy).await?
         } else if let Some(stream) = resource.downcast_rc::<StreamResource>() {
           stream.clone().read(&mut zero_copy).await?
+        } else if let Some(file) = resource.downcast_rc::<VirtualFile>() {
+          file.read(&mut zero_copy)?
         } else {
           return Err(bad_resource_id());
         };
@@ -1,6 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
-use super::io::{std_file_resource, StreamResource};
+use super::io::{std_file_resource, StreamResource, TcpStreamResource};
 use crate::permissions::Permissions;
 use deno_core::error::bad_resource_id;
 use deno_core::error::type_error;
@@ -40,6 +40,53 @@ fn clone_file(
   })
 }
 
+/// Creates a stdio stream of a child process from a resource. Files can be
+/// passed on every platform, sockets and pipes only on unix, where the child
+/// gets a duplicate of their file descriptor.
+fn resource_stdio(
+  state: &mut OpState,
+  rid: u32,
+) -> Result<std::process::Stdio, AnyError> {
+  #[cfg(unix)]
+  {
+    use std::os::unix::io::FromRawFd;
+
+    let raw_fd = if let Some(resource) =
+      state.resource_table.get::<TcpStreamResource>(rid)
+    {
+      Some(resource.raw_fd()?)
+    } else if let Some(resource) =
+      state.resource_table.get::<StreamResource>(rid)
+    {
+      if resource.fs_file.is_none() {
+        Some(resource.raw_fd()?)
+      } else {
+        None
+      }
+    } else {
+      None
+    };
+    if let Some(raw_fd) = raw_fd {
+      use nix::fcntl::{fcntl, FcntlArg};
+      // The duplicate is only inherited as the stdio stream of the child.
+      let fd = fcntl(raw_fd, FcntlArg::F_DUPFD_CLOEXEC(0))?;
+      let file = unsafe { std::fs::File::from_raw_fd(fd) };
+      return Ok(file.into());
+    }
+  }
+  #[cfg(not(unix))]
+  {
+    use deno_core::error::not_supported;
+
+    if state.resource_table.get::<TcpStreamResource>(rid).is_some() {
+      return Err(not_supported());
+    }
+  }
+
+  let file = clone_file(state, rid)?;
+  Ok(file.into())
+}
+
 fn subprocess_stdio_map(s: &str) -> Result<std::process::Stdio, AnyError> {
   match s {
     "inherit" => Ok(std::process::Stdio::inherit()),
@@ -55,6 +102,14 @@ struct RunArgs {
   cmd: Vec<String>,
   cwd: Option<String>,
   env: Vec<(String, String)>,
+  #[serde(default)]
+  clear_env: bool,
+  uid: Option<u32>,
+  gid: Option<u32>,
+  #[serde(default)]
+  new_process_group: bool,
+  #[serde(default)]
+  new_session: bool,
   stdin: String,
   stdout: String,
   stderr: String,
@@ -91,38 +146,49 @@ fn op_run(
   let env = run_args.env;
   let cwd = run_args.cwd;
 
-  let mut c = Command::new(args.get(0).unwrap());
+  if run_args.clear_env {
+    super::check_unstable(state, "Deno.run#clearEnv");
+  }
+  if run_args.uid.is_some() || run_args.gid.is_some() {
+    super::check_unstable(state, "Deno.run#uid");
+  }
+  if run_args.new_process_group || run_args.new_session {
+    super::check_unstable(state, "Deno.run#newProcessGroup");
+  }
+
+  let mut c = std::process::Command::new(args.get(0).unwrap());
   (1..args.len()).for_each(|i| {
     let arg = args.get(i).unwrap();
     c.arg(arg);
   });
   cwd.map(|d| c.current_dir(d));
+  if run_args.clear_env {
+    c.env_clear();
+  }
   for (key, value) in &env {
     c.env(key, value);
   }
+  set_process_options(&mut c, &run_args)?;
 
-  // TODO: make this work with other resources, eg. sockets
   if !run_args.stdin.is_empty() {
     c.stdin(subprocess_stdio_map(run_args.stdin.as_ref())?);
   } else {
-    let file = clone_file(state, run_args.stdin_rid)?;
-    c.stdin(file);
+    c.stdin(resource_stdio(state, run_args.stdin_rid)?);
   }
 
   if !run_args.stdout.is_empty() {
     c.stdout(subprocess_stdio_map(run_args.stdout.as_ref())?);
   } else {
-    let file = clone_file(state, run_args.stdout_rid)?;
-    c.stdout(file);
+    c.stdout(resource_stdio(state, run_args.stdout_rid)?);
   }
 
   if !run_args.stderr.is_empty() {
     c.stderr(subprocess_stdio_map(run_args.stderr.as_ref())?);
   } else {
-    let file = clone_file(state, run_args.stderr_rid)?;
-    c.stderr(file);
+    c.stderr(resource_stdio(state, run_args.stderr_rid)?);
   }
 
+  let mut c = Command::from(c);
   // We want to kill child when it's closed
   c.kill_on_drop(true);
 
@@ -174,6 +240,68 @@ fn op_run(
   }))
 }
 
+/// Sets the user, group and process group or session of a child process.
+#[cfg(unix)]
+fn set_process_options(
+  c: &mut std::process::Command,
+  run_args: &RunArgs,
+) -> Result<(), AnyError> {
+  use std::os::unix::process::CommandExt;
+
+  if let Some(uid) = run_args.uid {
+    c.uid(uid);
+  }
+  if let Some(gid) = run_args.gid {
+    c.gid(gid);
+  }
+  if run_args.new_process_group && run_args.new_session {
+    return Err(type_error(
+      "A new session already starts a new process group",
+    ));
+  }
+  // The closures run in the forked child before exec, where only async signal
+  // safe functions may be called, which setsid() and setpgid() are.
+  if run_args.new_session {
+    unsafe {
+      c.pre_exec(|| {
+        if libc::setsid() == -1 {
+          return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+  } else if run_args.new_process_group {
+    unsafe {
+      c.pre_exec(|| {
+        if libc::setpgid(0, 0) == -1 {
+          return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+  }
+  Ok(())
+}
+
+/// Sets the process group of a child process, users, groups and sessions are
+/// not supported on Windows.
+#[cfg(not(unix))]
+fn set_process_options(
+  c: &mut std::process::Command,
+  run_args: &RunArgs,
+) -> Result<(), AnyError> {
+  use deno_core::error::not_supported;
+  use std::os::windows::process::CommandExt;
+
+  if run_args.uid.is_some() || run_args.gid.is_some() || run_args.new_session {
+    return Err(not_supported());
+  }
+  if run_args.new_process_group {
+    c.creation_flags(winapi::um::winbase::CREATE_NEW_PROCESS_GROUP);
+  }
+  Ok(())
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RunStatusArgs {
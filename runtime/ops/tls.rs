@@ -9,6 +9,7 @@ use deno_core::error::bad_resource;
 use deno_core::error::bad_resource_id;
 use deno_core::error::custom_error;
 use deno_core::error::generic_error;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
@@ -36,7 +37,9 @@ use tokio_rustls::{rustls::ClientConfig, TlsConnector};
 use tokio_rustls::{
   rustls::{
     internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys},
-    Certificate, NoClientAuth, PrivateKey, ServerConfig,
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient,
+    Certificate, NoClientAuth, PrivateKey, RootCertStore, ServerConfig,
+    Session,
   },
   TlsAcceptor,
 };
@@ -47,6 +50,7 @@ pub fn init(rt: &mut deno_core::JsRuntime) {
   super::reg_json_async(rt, "op_connect_tls", op_connect_tls);
   super::reg_json_sync(rt, "op_listen_tls", op_listen_tls);
   super::reg_json_async(rt, "op_accept_tls", op_accept_tls);
+  super::reg_json_sync(rt, "op_tls_listener_reload", op_tls_listener_reload);
 }
 
 #[derive(Deserialize)]
@@ -56,6 +60,12 @@ struct ConnectTLSArgs {
   hostname: String,
   port: u16,
   cert_file: Option<String>,
+  ca_certs: Option<Vec<String>>,
+  client_cert_file: Option<String>,
+  client_key_file: Option<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
+  alpn_protocols: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
@@ -63,7 +73,13 @@ struct ConnectTLSArgs {
 struct StartTLSArgs {
   rid: u32,
   cert_file: Option<String>,
+  ca_certs: Option<Vec<String>>,
+  client_cert_file: Option<String>,
+  client_key_file: Option<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
   hostname: String,
+  alpn_protocols: Option<Vec<String>>,
 }
 
 async fn op_start_tls(
@@ -87,6 +103,11 @@ async fn op_start_tls(
     if let Some(path) = cert_file.clone() {
       permissions.check_read(Path::new(&path))?;
     }
+    check_client_cert_read(
+      permissions,
+      &args.client_cert_file,
+      &args.client_key_file,
+    )?;
   }
 
   let resource_rc = state
@@ -110,11 +131,30 @@ async fn op_start_tls(
     let reader = &mut BufReader::new(key_file);
     config.root_store.add_pem_file(reader).unwrap();
   }
+  if let Some(ca_certs) = &args.ca_certs {
+    add_ca_certs(&mut config.root_store, ca_certs)?;
+  }
+  if let Some(alpn_protocols) = args.alpn_protocols {
+    config.alpn_protocols = get_alpn_protocols(alpn_protocols);
+  }
+  let client_cert = load_cert_and_key(
+    args.cert_chain.as_deref(),
+    args.private_key.as_deref(),
+    args.client_cert_file.as_deref(),
+    args.client_key_file.as_deref(),
+  )?;
+  if let Some((certs, key)) = client_cert {
+    config
+      .set_single_client_cert(certs, key)
+      .map_err(|e| custom_error("InvalidData", e.to_string()))?;
+  }
 
   let tls_connector = TlsConnector::from(Arc::new(config));
   let dnsname =
     DNSNameRef::try_from_ascii_str(&domain).expect("Invalid DNS lookup");
   let tls_stream = tls_connector.connect(dnsname, tcp_stream).await?;
+  let alpn_protocol = get_alpn_protocol(tls_stream.get_ref().1);
+  let peer_certificates = get_peer_certificates(tls_stream.get_ref().1);
 
   let rid = {
     let mut state_ = state.borrow_mut();
@@ -124,6 +164,8 @@ async fn op_start_tls(
   };
   Ok(json!({
       "rid": rid,
+      "alpnProtocol": alpn_protocol,
+      "peerCertificates": peer_certificates,
       "localAddr": {
         "hostname": local_addr.ip().to_string(),
         "port": local_addr.port(),
@@ -144,6 +186,15 @@ async fn op_connect_tls(
 ) -> Result<Value, AnyError> {
   let args: ConnectTLSArgs = serde_json::from_value(args)?;
   let cert_file = args.cert_file.clone();
+  if args.alpn_protocols.is_some() {
+    super::check_unstable2(&state, "Deno.connectTls#alpnProtocols");
+  }
+  if args.ca_certs.is_some() {
+    super::check_unstable2(&state, "Deno.connectTls#caCerts");
+  }
+  if args.cert_chain.is_some() || args.private_key.is_some() {
+    super::check_unstable2(&state, "Deno.connectTls#certChain");
+  }
   {
     let s = state.borrow();
     let permissions = s.borrow::<Permissions>();
@@ -151,6 +202,11 @@ async fn op_connect_tls(
     if let Some(path) = cert_file.clone() {
       permissions.check_read(Path::new(&path))?;
     }
+    check_client_cert_read(
+      permissions,
+      &args.client_cert_file,
+      &args.client_key_file,
+    )?;
   }
   let mut domain = args.hostname.clone();
   if domain.is_empty() {
@@ -173,10 +229,29 @@ async fn op_connect_tls(
     let reader = &mut BufReader::new(key_file);
     config.root_store.add_pem_file(reader).unwrap();
   }
+  if let Some(ca_certs) = &args.ca_certs {
+    add_ca_certs(&mut config.root_store, ca_certs)?;
+  }
+  if let Some(alpn_protocols) = args.alpn_protocols {
+    config.alpn_protocols = get_alpn_protocols(alpn_protocols);
+  }
+  let client_cert = load_cert_and_key(
+    args.cert_chain.as_deref(),
+    args.private_key.as_deref(),
+    args.client_cert_file.as_deref(),
+    args.client_key_file.as_deref(),
+  )?;
+  if let Some((certs, key)) = client_cert {
+    config
+      .set_single_client_cert(certs, key)
+      .map_err(|e| custom_error("InvalidData", e.to_string()))?;
+  }
   let tls_connector = TlsConnector::from(Arc::new(config));
   let dnsname =
     DNSNameRef::try_from_ascii_str(&domain).expect("Invalid DNS lookup");
   let tls_stream = tls_connector.connect(dnsname, tcp_stream).await?;
+  let alpn_protocol = get_alpn_protocol(tls_stream.get_ref().1);
+  let peer_certificates = get_peer_certificates(tls_stream.get_ref().1);
   let rid = {
     let mut state_ = state.borrow_mut();
     state_
@@ -185,6 +260,8 @@ async fn op_connect_tls(
   };
   Ok(json!({
       "rid": rid,
+      "alpnProtocol": alpn_protocol,
+      "peerCertificates": peer_certificates,
       "localAddr": {
         "hostname": local_addr.ip().to_string(),
         "port": local_addr.port(),
@@ -198,6 +275,142 @@ async fn op_connect_tls(
   }))
 }
 
+fn get_alpn_protocols(alpn_protocols: Vec<String>) -> Vec<Vec<u8>> {
+  alpn_protocols.into_iter().map(String::into_bytes).collect()
+}
+
+/// Returns the application protocol which was negotiated with ALPN during the
+/// handshake of a TLS session, if any.
+fn get_alpn_protocol(session: &dyn Session) -> Option<String> {
+  session
+    .get_alpn_protocol()
+    .map(|protocol| String::from_utf8_lossy(protocol).into_owned())
+}
+
+/// Returns the DER encoded certificate chain the peer presented during the
+/// handshake of a TLS session, if any.
+fn get_peer_certificates(session: &dyn Session) -> Option<Vec<Vec<u8>>> {
+  session
+    .get_peer_certificates()
+    .map(|certs| certs.into_iter().map(|cert| cert.0).collect())
+}
+
+fn check_client_cert_read(
+  permissions: &Permissions,
+  client_cert_file: &Option<String>,
+  client_key_file: &Option<String>,
+) -> Result<(), AnyError> {
+  if let Some(path) = client_cert_file {
+    permissions.check_read(Path::new(path))?;
+  }
+  if let Some(path) = client_key_file {
+    permissions.check_read(Path::new(path))?;
+  }
+  Ok(())
+}
+
+/// Adds the certificates of PEM encoded CA certificates to a root store.
+fn add_ca_certs(
+  root_store: &mut RootCertStore,
+  ca_certs: &[String],
+) -> Result<(), AnyError> {
+  for ca_cert in ca_certs {
+    let (valid_count, _) = root_store
+      .add_pem_file(&mut ca_cert.as_bytes())
+      .map_err(|_| {
+        custom_error("InvalidData", "Unable to decode CA certificate")
+      })?;
+    if valid_count == 0 {
+      return Err(custom_error(
+        "InvalidData",
+        "No certificates found in CA certificate",
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// Loads the certificate chain and private key of a TLS endpoint, either from
+/// PEM strings or from PEM files. Returns `None` if neither is specified.
+fn load_cert_and_key(
+  cert_chain: Option<&str>,
+  private_key: Option<&str>,
+  cert_file: Option<&str>,
+  key_file: Option<&str>,
+) -> Result<Option<(Vec<Certificate>, PrivateKey)>, AnyError> {
+  match (cert_chain, private_key, cert_file, key_file) {
+    (Some(cert_chain), Some(private_key), None, None) => Ok(Some((
+      load_certs_from_pem(cert_chain.as_bytes())?,
+      load_keys_from_pem(private_key.as_bytes())?.remove(0),
+    ))),
+    (None, None, Some(cert_file), Some(key_file)) => Ok(Some((
+      load_certs(cert_file)?,
+      load_keys(key_file)?.remove(0),
+    ))),
+    (None, None, None, None) => Ok(None),
+    _ => Err(type_error(
+      "Either a certificate chain and a private key, or a certificate file and a key file must be specified",
+    )),
+  }
+}
+
+/// Replaces the certificate chain and key a TLS listener presents to clients.
+fn set_server_cert(
+  config: &mut ServerConfig,
+  certs: Vec<Certificate>,
+  key: PrivateKey,
+) -> Result<(), AnyError> {
+  config
+    .set_single_cert(certs, key)
+    .map_err(|e| custom_error("InvalidData", e.to_string()))
+}
+
+/// Creates the config of a TLS listener. With a client auth mode of
+/// `"optional"` or `"required"`, the certificates clients present are verified
+/// with the CAs in `client_ca_file` and `client_ca_certs`.
+fn create_server_config(
+  client_auth: Option<&str>,
+  client_ca_file: Option<&str>,
+  client_ca_certs: Option<&[String]>,
+) -> Result<ServerConfig, AnyError> {
+  let client_auth = match client_auth {
+    Some(client_auth) => client_auth,
+    None => return Ok(ServerConfig::new(NoClientAuth::new())),
+  };
+  if client_ca_file.is_none() && client_ca_certs.is_none() {
+    return Err(type_error(
+      "A client CA file or CA certificates are required to verify client certificates",
+    ));
+  }
+  let mut root_store = RootCertStore::empty();
+  if let Some(client_ca_file) = client_ca_file {
+    let reader = &mut BufReader::new(File::open(client_ca_file)?);
+    let (valid_count, _) = root_store.add_pem_file(reader).map_err(|_| {
+      custom_error("InvalidData", "Unable to decode client CA file")
+    })?;
+    if valid_count == 0 {
+      return Err(custom_error(
+        "InvalidData",
+        "No certificates found in client CA file",
+      ));
+    }
+  }
+  if let Some(client_ca_certs) = client_ca_certs {
+    add_ca_certs(&mut root_store, client_ca_certs)?;
+  }
+  let verifier = match client_auth {
+    "optional" => AllowAnyAnonymousOrAuthenticatedClient::new(root_store),
+    "required" => AllowAnyAuthenticatedClient::new(root_store),
+    _ => {
+      return Err(type_error(format!(
+        "Invalid client auth mode \"{}\"",
+        client_auth
+      )))
+    }
+  };
+  Ok(ServerConfig::new(verifier))
+}
+
 fn load_certs(path: &str) -> Result<Vec<Certificate>, AnyError> {
   let cert_file = File::open(path)?;
   let reader = &mut BufReader::new(cert_file);
@@ -213,6 +426,19 @@ fn load_certs(path: &str) -> Result<Vec<Certificate>, AnyError> {
   Ok(certs)
 }
 
+fn load_certs_from_pem(pem: &[u8]) -> Result<Vec<Certificate>, AnyError> {
+  let certs = certs(&mut &pem[..])
+    .map_err(|_| custom_error("InvalidData", "Unable to decode certificate"))?;
+
+  if certs.is_empty() {
+    let e =
+      custom_error("InvalidData", "No certificates found in certificate chain");
+    return Err(e);
+  }
+
+  Ok(certs)
+}
+
 fn key_decode_err() -> AnyError {
   custom_error("InvalidData", "Unable to decode key")
 }
@@ -252,9 +478,26 @@ fn load_keys(path: &str) -> Result<Vec<PrivateKey>, AnyError> {
   Ok(keys)
 }
 
+fn load_keys_from_pem(pem: &[u8]) -> Result<Vec<PrivateKey>, AnyError> {
+  let mut keys =
+    rsa_private_keys(&mut &pem[..]).map_err(|_| key_decode_err())?;
+
+  if keys.is_empty() {
+    keys = pkcs8_private_keys(&mut &pem[..]).map_err(|_| key_decode_err())?;
+  }
+
+  if keys.is_empty() {
+    return Err(custom_error("InvalidData", "No keys found in private key"));
+  }
+
+  Ok(keys)
+}
+
 pub struct TlsListenerResource {
   listener: AsyncRefCell<TcpListener>,
-  tls_acceptor: TlsAcceptor,
+  // Replaced when the certificates are reloaded, connections which are already
+  // accepted keep using the config they were accepted with.
+  tls_config: RefCell<Arc<ServerConfig>>,
   cancel: CancelHandle,
 }
 
@@ -274,8 +517,14 @@ struct ListenTlsArgs {
   transport: String,
   hostname: String,
   port: u16,
-  cert_file: String,
-  key_file: String,
+  cert_file: Option<String>,
+  key_file: Option<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
+  alpn_protocols: Option<Vec<String>>,
+  client_auth: Option<String>,
+  client_ca_file: Option<String>,
+  client_ca_certs: Option<Vec<String>>,
 }
 
 fn op_listen_tls(
@@ -286,19 +535,44 @@ fn op_listen_tls(
   let args: ListenTlsArgs = serde_json::from_value(args)?;
   assert_eq!(args.transport, "tcp");
 
-  let cert_file = args.cert_file;
-  let key_file = args.key_file;
+  if args.alpn_protocols.is_some() {
+    super::check_unstable(state, "Deno.listenTls#alpnProtocols");
+  }
+  if args.client_auth.is_some() {
+    super::check_unstable(state, "Deno.listenTls#clientAuth");
+  }
+  if args.cert_chain.is_some() || args.private_key.is_some() {
+    super::check_unstable(state, "Deno.listenTls#certChain");
+  }
   {
     let permissions = state.borrow::<Permissions>();
     permissions.check_net(&(&args.hostname, Some(args.port)))?;
-    permissions.check_read(Path::new(&cert_file))?;
-    permissions.check_read(Path::new(&key_file))?;
+    if let Some(path) = &args.cert_file {
+      permissions.check_read(Path::new(path))?;
+    }
+    if let Some(path) = &args.key_file {
+      permissions.check_read(Path::new(path))?;
+    }
+    if let Some(path) = &args.client_ca_file {
+      permissions.check_read(Path::new(path))?;
+    }
+  }
+  let (certs, key) = load_cert_and_key(
+    args.cert_chain.as_deref(),
+    args.private_key.as_deref(),
+    args.cert_file.as_deref(),
+    args.key_file.as_deref(),
+  )?
+  .ok_or_else(|| type_error("A certificate and a key must be specified"))?;
+  let mut config = create_server_config(
+    args.client_auth.as_deref(),
+    args.client_ca_file.as_deref(),
+    args.client_ca_certs.as_deref(),
+  )?;
+  set_server_cert(&mut config, certs, key)?;
+  if let Some(alpn_protocols) = args.alpn_protocols {
+    config.alpn_protocols = get_alpn_protocols(alpn_protocols);
   }
-  let mut config = ServerConfig::new(NoClientAuth::new());
-  config
-    .set_single_cert(load_certs(&cert_file)?, load_keys(&key_file)?.remove(0))
-    .expect("invalid key or certificate");
-  let tls_acceptor = TlsAcceptor::from(Arc::new(config));
   let addr = resolve_addr_sync(&args.hostname, args.port)?
     .next()
     .ok_or_else(|| generic_error("No resolved address found"))?;
@@ -307,7 +581,7 @@ fn op_listen_tls(
   let local_addr = listener.local_addr()?;
   let tls_listener_resource = TlsListenerResource {
     listener: AsyncRefCell::new(listener),
-    tls_acceptor,
+    tls_config: RefCell::new(Arc::new(config)),
     cancel: Default::default(),
   };
 
@@ -362,11 +636,13 @@ async fn op_accept_tls(
     .get::<TlsListenerResource>(rid)
     .ok_or_else(|| bad_resource("Listener has been closed"))?;
   let cancel = RcRef::map(&resource, |r| &r.cancel);
-  let tls_acceptor = resource.tls_acceptor.clone();
+  let tls_acceptor = TlsAcceptor::from(resource.tls_config.borrow().clone());
   let tls_stream = tls_acceptor
     .accept(tcp_stream)
     .try_or_cancel(cancel)
     .await?;
+  let alpn_protocol = get_alpn_protocol(tls_stream.get_ref().1);
+  let peer_certificates = get_peer_certificates(tls_stream.get_ref().1);
 
   let rid = {
     let mut state_ = state.borrow_mut();
@@ -377,6 +653,8 @@ async fn op_accept_tls(
 
   Ok(json!({
     "rid": rid,
+    "alpnProtocol": alpn_protocol,
+    "peerCertificates": peer_certificates,
     "localAddr": {
       "transport": "tcp",
       "hostname": local_addr.ip().to_string(),
@@ -389,3 +667,49 @@ async fn op_accept_tls(
     }
   }))
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TlsListenerReloadArgs {
+  rid: u32,
+  cert_file: Option<String>,
+  key_file: Option<String>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
+}
+
+/// Replaces the certificate chain and key of a TLS listener, which are used
+/// for the connections accepted from then on.
+fn op_tls_listener_reload(
+  state: &mut OpState,
+  args: Value,
+  _zero_copy: &mut [ZeroCopyBuf],
+) -> Result<Value, AnyError> {
+  super::check_unstable(state, "Deno.TlsListener.reloadCertificates");
+  let args: TlsListenerReloadArgs = serde_json::from_value(args)?;
+  {
+    let permissions = state.borrow::<Permissions>();
+    if let Some(path) = &args.cert_file {
+      permissions.check_read(Path::new(path))?;
+    }
+    if let Some(path) = &args.key_file {
+      permissions.check_read(Path::new(path))?;
+    }
+  }
+  let resource = state
+    .resource_table
+    .get::<TlsListenerResource>(args.rid)
+    .ok_or_else(|| bad_resource("Listener has been closed"))?;
+  let (certs, key) = load_cert_and_key(
+    args.cert_chain.as_deref(),
+    args.private_key.as_deref(),
+    args.cert_file.as_deref(),
+    args.key_file.as_deref(),
+  )?
+  .ok_or_else(|| type_error("A certificate and a key must be specified"))?;
+  // The client auth and ALPN settings of the listener are kept.
+  let mut config = ServerConfig::clone(&resource.tls_config.borrow());
+  set_server_cert(&mut config, certs, key)?;
+  resource.tls_config.replace(Arc::new(config));
+  Ok(json!({}))
+}
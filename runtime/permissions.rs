@@ -8,6 +8,7 @@ use deno_core::error::AnyError;
 use deno_core::url;
 use deno_core::ModuleSpecifier;
 use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashSet;
 use std::env::current_dir;
 use std::fmt;
@@ -88,7 +89,7 @@ fn resolve_fs_allowlist(allow: &Option<Vec<PathBuf>>) -> HashSet<PathBuf> {
   }
 }
 
-#[derive(Clone, Debug, PartialEq, Default)]
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct PermissionsOptions {
   pub allow_env: bool,
   pub allow_hrtime: bool,
@@ -574,22 +575,24 @@ impl Permissions {
   }
 
   /// A helper function that determines if the module specifier is a local or
-  /// remote, and performs a read or net check for the specifier.
+  /// remote, and performs a read or net check for the specifier. The source of
+  /// a `data:` URL is inline, and the source of a `blob:` URL was created by the
+  /// program itself, so they do not require any permission.
   pub fn check_specifier(
     &self,
     specifier: &ModuleSpecifier,
   ) -> Result<(), AnyError> {
     let url = specifier.as_url();
-    if url.scheme() == "file" {
-      match url.to_file_path() {
+    match url.scheme() {
+      "file" => match url.to_file_path() {
         Ok(path) => self.check_read(&path),
         Err(_) => Err(uri_error(format!(
           "Invalid file path.\n  Specifier: {}",
           specifier
         ))),
-      }
-    } else {
-      self.check_net_url(url)
+      },
+      "data" | "blob" => Ok(()),
+      _ => self.check_net_url(url),
     }
   }
 
@@ -920,6 +923,20 @@ mod tests {
           .unwrap(),
         false,
       ),
+      (
+        ModuleSpecifier::resolve_url_or_path(
+          "data:application/javascript,export%20const%20a%20%3D%201%3B",
+        )
+        .unwrap(),
+        true,
+      ),
+      (
+        ModuleSpecifier::resolve_url_or_path(
+          "blob:null/a0a1a2a3-b0b1-c0c1-d0d1-e0e1e2e3e4e5",
+        )
+        .unwrap(),
+        true,
+      ),
     ];
 
     if cfg!(target_os = "windows") {
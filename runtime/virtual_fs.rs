@@ -0,0 +1,121 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use crate::fs_util::normalize_path;
+use deno_core::error::AnyError;
+use deno_core::Resource;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A read-only tree of files, like the static assets embedded in a
+/// standalone binary. When a `VirtualFs` is put in the `OpState`, files that
+/// are opened for reading are looked up in it before the host file system.
+///
+/// The files are stored relative to a root directory, the directory they were
+/// in when the tree was created, which doesn't have to exist on the host.
+#[derive(Clone, Debug, Default)]
+pub struct VirtualFs {
+  root: Arc<PathBuf>,
+  files: Arc<HashMap<PathBuf, Arc<[u8]>>>,
+}
+
+impl VirtualFs {
+  /// Create a virtual file system from a map of paths relative to `root` to
+  /// the contents of the files.
+  pub fn new(root: PathBuf, files: HashMap<PathBuf, Vec<u8>>) -> Self {
+    let files = files
+      .into_iter()
+      .map(|(path, contents)| (path, Arc::from(contents)))
+      .collect();
+    Self {
+      root: Arc::new(root),
+      files: Arc::new(files),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.files.is_empty()
+  }
+
+  /// Open a file of the tree. A relative `path` is resolved against the root
+  /// of the tree, not the current working directory, so a file is found
+  /// wherever the process runs. An absolute `path` is found if it is inside
+  /// the root.
+  pub fn open(&self, path: &Path) -> Result<Option<VirtualFile>, AnyError> {
+    let path = normalize_path(path);
+    let path = if path.is_absolute() {
+      match path.strip_prefix(self.root.as_path()) {
+        Ok(path) => path,
+        Err(_) => return Ok(None),
+      }
+    } else {
+      path.as_path()
+    };
+    Ok(self.files.get(path).map(|contents| VirtualFile {
+      cursor: RefCell::new(Cursor::new(contents.clone())),
+    }))
+  }
+}
+
+/// A file opened from a `VirtualFs`, which is stored in the resource table
+/// like any other file.
+#[derive(Debug)]
+pub struct VirtualFile {
+  cursor: RefCell<Cursor<Arc<[u8]>>>,
+}
+
+impl VirtualFile {
+  pub fn read(&self, buf: &mut [u8]) -> Result<usize, AnyError> {
+    Ok(self.cursor.borrow_mut().read(buf)?)
+  }
+
+  pub fn seek(&self, pos: SeekFrom) -> Result<u64, AnyError> {
+    Ok(self.cursor.borrow_mut().seek(pos)?)
+  }
+}
+
+impl Resource for VirtualFile {
+  fn name(&self) -> Cow<str> {
+    "virtualFile".into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn open_and_read() {
+    let root = std::env::temp_dir().join("project");
+    let mut files = HashMap::new();
+    files.insert(PathBuf::from("assets/hello.txt"), b"hello world".to_vec());
+    let vfs = VirtualFs::new(root.clone(), files);
+    assert!(!vfs.is_empty());
+    assert!(vfs.open(Path::new("assets/missing.txt")).unwrap().is_none());
+    assert!(vfs.open(Path::new("./assets/hello.txt")).unwrap().is_some());
+    assert!(vfs
+      .open(&std::env::temp_dir().join("assets/hello.txt"))
+      .unwrap()
+      .is_none());
+
+    let file = vfs
+      .open(&root.join("assets/../assets/hello.txt"))
+      .unwrap()
+      .unwrap();
+    let mut buf = [0; 5];
+    assert_eq!(file.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+    assert_eq!(file.seek(SeekFrom::Current(1)).unwrap(), 6);
+    let mut buf = [0; 16];
+    assert_eq!(file.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"world");
+    assert_eq!(file.read(&mut buf).unwrap(), 0);
+  }
+}
@@ -24,6 +24,7 @@ use deno_core::JsRuntime;
 use deno_core::ModuleLoader;
 use deno_core::ModuleSpecifier;
 use deno_core::RuntimeOptions;
+use deno_fetch::BlobUrlStore;
 use std::env;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
@@ -154,6 +155,9 @@ pub struct WebWorkerOptions {
   /// Sets `Deno.noColor` in JS runtime.
   pub no_color: bool,
   pub get_error_class_fn: Option<GetErrorClassFn>,
+  /// The blobs of the object URLs created with `URL.createObjectURL()`, which
+  /// are shared with the other workers of the program.
+  pub blob_url_store: BlobUrlStore,
 }
 
 impl WebWorker {
@@ -220,6 +224,7 @@ impl WebWorker {
         js_runtime,
         options.user_agent.clone(),
         options.ca_filepath.as_deref(),
+        options.blob_url_store.clone(),
       );
       ops::timers::init(js_runtime);
       ops::worker_host::init(
@@ -244,6 +249,7 @@ impl WebWorker {
       if options.use_deno_namespace {
         ops::fs_events::init(js_runtime);
         ops::fs::init(js_runtime);
+        ops::http::init(js_runtime);
         ops::net::init(js_runtime);
         ops::os::init(js_runtime);
         ops::permissions::init(js_runtime);
@@ -496,6 +502,7 @@ mod tests {
       ts_version: "x".to_string(),
       no_color: true,
       get_error_class_fn: None,
+      blob_url_store: BlobUrlStore::default(),
     };
 
     let mut worker = WebWorker::from_options(
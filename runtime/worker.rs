@@ -20,6 +20,7 @@ use deno_core::ModuleId;
 use deno_core::ModuleLoader;
 use deno_core::ModuleSpecifier;
 use deno_core::RuntimeOptions;
+use deno_fetch::BlobUrlStore;
 use std::env;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -63,6 +64,9 @@ pub struct WorkerOptions {
   /// Sets `Deno.noColor` in JS runtime.
   pub no_color: bool,
   pub get_error_class_fn: Option<GetErrorClassFn>,
+  /// The blobs of the object URLs created with `URL.createObjectURL()`, which
+  /// are shared with the other workers of the program.
+  pub blob_url_store: BlobUrlStore,
 }
 
 impl MainWorker {
@@ -115,6 +119,7 @@ impl MainWorker {
         js_runtime,
         options.user_agent.clone(),
         options.ca_filepath.as_deref(),
+        options.blob_url_store.clone(),
       );
       ops::timers::init(js_runtime);
       ops::worker_host::init(
@@ -132,6 +137,7 @@ impl MainWorker {
       );
       ops::fs_events::init(js_runtime);
       ops::fs::init(js_runtime);
+      ops::http::init(js_runtime);
       ops::io::init(js_runtime);
       ops::net::init(js_runtime);
       ops::os::init(js_runtime);
@@ -282,6 +288,7 @@ mod tests {
       ts_version: "x".to_string(),
       no_color: true,
       get_error_class_fn: None,
+      blob_url_store: BlobUrlStore::default(),
     };
 
     MainWorker::from_options(main_module, permissions, &options)
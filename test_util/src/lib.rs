@@ -54,6 +54,7 @@ const DOUBLE_REDIRECTS_PORT: u16 = 4548;
 const INF_REDIRECTS_PORT: u16 = 4549;
 const REDIRECT_ABSOLUTE_PORT: u16 = 4550;
 const HTTPS_PORT: u16 = 5545;
+const H2_HTTPS_PORT: u16 = 5546;
 const WS_PORT: u16 = 4242;
 const WSS_PORT: u16 = 4243;
 
@@ -412,6 +413,10 @@ async fn main_server(req: Request<Body>) -> hyper::Result<Response<Body>> {
       );
       Ok(res)
     }
+    (_, "/http_version") => {
+      let version = format!("{:?}", req.version());
+      Ok(Response::new(version.into()))
+    }
     (_, "/bad_redirect") => {
       let mut res = Response::new(Body::empty());
       *res.status_mut() = StatusCode::FOUND;
@@ -557,6 +562,25 @@ async fn main_server(req: Request<Body>) -> hyper::Result<Response<Body>> {
       );
       Ok(res)
     }
+    (_, "/cli/tests/subdir/auth_required.ts") => {
+      let authorized = req
+        .headers()
+        .get("authorization")
+        .map(|v| v.as_bytes() == b"Bearer abcdef123456789")
+        .unwrap_or(false);
+      if !authorized {
+        let mut res = Response::new(Body::empty());
+        *res.status_mut() = StatusCode::NOT_FOUND;
+        return Ok(res);
+      }
+      let mut res =
+        Response::new(Body::from("console.log('Hello from a private host');"));
+      res.headers_mut().insert(
+        "Content-type",
+        HeaderValue::from_static("application/typescript"),
+      );
+      Ok(res)
+    }
     (_, "/cli/tests/subdir/no_js_ext@1.0.0") => {
       let mut res = Response::new(Body::from(
         r#"import { printHello } from "./mod2.ts";
@@ -679,13 +703,19 @@ async fn wrap_main_server() {
   }
 }
 
-async fn wrap_main_https_server() {
-  let main_server_https_addr = SocketAddr::from(([127, 0, 0, 1], HTTPS_PORT));
+/// Serves the main server over TLS, where `alpn_protocols` are the protocols
+/// the server selects from with ALPN, like `h2` for HTTP/2.
+async fn wrap_main_https_server(port: u16, alpn_protocols: &[&str]) {
+  let main_server_https_addr = SocketAddr::from(([127, 0, 0, 1], port));
   let cert_file = "std/http/testdata/tls/localhost.crt";
   let key_file = "std/http/testdata/tls/localhost.key";
-  let tls_config = get_tls_config(cert_file, key_file)
+  let mut tls_config = get_tls_config(cert_file, key_file)
     .await
     .expect("Cannot get TLS config");
+  Arc::get_mut(&mut tls_config).unwrap().alpn_protocols = alpn_protocols
+    .iter()
+    .map(|protocol| protocol.as_bytes().to_vec())
+    .collect();
   let mut tcp = TcpListener::bind(&main_server_https_addr)
     .await
     .expect("Cannot bind TCP");
@@ -743,7 +773,9 @@ pub async fn run_all_servers() {
   let wss_server_fut = run_wss_server(&wss_addr);
 
   let main_server_fut = wrap_main_server();
-  let main_server_https_fut = wrap_main_https_server();
+  let main_server_https_fut = wrap_main_https_server(HTTPS_PORT, &[]);
+  let main_server_h2_https_fut =
+    wrap_main_https_server(H2_HTTPS_PORT, &["h2", "http/1.1"]);
 
   let mut server_fut = async {
     futures::join!(
@@ -756,6 +788,7 @@ pub async fn run_all_servers() {
       abs_redirect_server_fut,
       main_server_fut,
       main_server_https_fut,
+      main_server_h2_https_fut,
     )
   }
   .boxed();